@@ -0,0 +1,135 @@
+<?php
+namespace Sqlx;
+
+use Revolt\EventLoop;
+use Sqlx\Exceptions\SqlxException;
+
+/**
+ * Fiber-friendly queries for Revolt-based event loops (AMPHP, ...).
+ *
+ * Driver methods block the calling thread until their statement is done, which
+ * stalls every Fiber sharing the event loop. This adapter starts each query in
+ * the background with queryAllAsync(), queryMaybeRowAsync() or executeAsync()
+ * and suspends only the current Fiber until the completion descriptor of the
+ * returned Sqlx\Pending becomes readable, so many Fibers can share one driver
+ * concurrently.
+ *
+ * Without revolt/event-loop installed, queries simply block.
+ *
+ * LIMITATIONS:
+ * - Only queryAll(), queryMaybeRow() and execute() are supported; transactions
+ *   and the other driver methods still block
+ * - Inside a transaction or withConnection(), queries run immediately, one
+ *   after another, on that connection
+ * - Completion descriptors require a Unix system
+ */
+final class Async
+{
+    /** @var \Sqlx\MySqlDriver|\Sqlx\PgDriver|\Sqlx\MssqlDriver $driver */
+    private $driver;
+
+    /**
+     * @param \Sqlx\MySqlDriver|\Sqlx\PgDriver|\Sqlx\MssqlDriver $driver
+     */
+    public function __construct($driver)
+    {
+        $this->driver = $driver;
+    }
+
+    /**
+     * Returns the wrapped driver.
+     *
+     * @return \Sqlx\MySqlDriver|\Sqlx\PgDriver|\Sqlx\MssqlDriver
+     */
+    public function driver()
+    {
+        return $this->driver;
+    }
+
+    /**
+     * Runs a query without blocking the event loop and returns all rows.
+     *
+     * @throws \Sqlx\Exceptions\SqlxException If the query fails
+     */
+    public function queryAll(string $query, ?array $params = null): array
+    {
+        return self::await($this->driver->queryAllAsync($query, $params));
+    }
+
+    /**
+     * Runs a query without blocking the event loop and returns its first row,
+     * or null if there is none.
+     *
+     * @throws \Sqlx\Exceptions\SqlxException If the query fails
+     */
+    public function queryMaybeRow(string $query, ?array $params = null): array|object|null
+    {
+        return self::await($this->driver->queryMaybeRowAsync($query, $params));
+    }
+
+    /**
+     * Runs a statement without blocking the event loop and returns the number
+     * of affected rows.
+     *
+     * @throws \Sqlx\Exceptions\SqlxException If the statement fails
+     */
+    public function execute(string $query, ?array $params = null): int
+    {
+        return self::await($this->driver->executeAsync($query, $params));
+    }
+
+    /**
+     * Runs queries concurrently without blocking the event loop and returns
+     * their rows keyed like $queries.
+     *
+     * Unlike Driver::parallel(), a failed query throws its exception once all
+     * queries are done.
+     *
+     * @param array<string|int, string|array{0: string, 1?: array|null}> $queries
+     * @throws \Sqlx\Exceptions\SqlxException If a query fails
+     */
+    public function all(array $queries): array
+    {
+        $pending = [];
+        foreach ($queries as $key => $query) {
+            [$sql, $params] = is_array($query) ? [$query[0], $query[1] ?? null] : [$query, null];
+            $pending[$key] = $this->driver->queryAllAsync($sql, $params);
+        }
+
+        $results = [];
+        $error = null;
+        foreach ($pending as $key => $handle) {
+            try {
+                $results[$key] = self::await($handle);
+            } catch (SqlxException $e) {
+                $error ??= $e;
+            }
+        }
+        if ($error !== null) {
+            throw $error;
+        }
+        return $results;
+    }
+
+    /**
+     * Suspends the current Fiber until the query is done and returns its
+     * result. Outside a Fiber, runs the event loop meanwhile.
+     *
+     * @throws \Sqlx\Exceptions\SqlxException If the query fails
+     */
+    public static function await(Pending $pending): mixed
+    {
+        if (!$pending->isReady() && class_exists(EventLoop::class)) {
+            $stream = fopen('php://fd/' . $pending->completionFd(), 'r');
+            $suspension = EventLoop::getSuspension();
+            $watcher = EventLoop::onReadable($stream, static fn () => $suspension->resume());
+            try {
+                $suspension->suspend();
+            } finally {
+                EventLoop::cancel($watcher);
+                fclose($stream);
+            }
+        }
+        return $pending->wait();
+    }
+}
@@ -0,0 +1,273 @@
+//! Normalized statement fingerprints.
+//!
+//! A fingerprint identifies the *shape* of a statement independently of the
+//! values it is run with. It is derived from the parsed [`Ast`] of the query
+//! template, so:
+//!
+//! - every placeholder (`$id`, `:name`, `?`) becomes `?`;
+//! - `IN`/`NOT IN` lists and `PAGINATE` clauses become a single `?`, whatever
+//!   the number of values bound to them;
+//! - inline string and numeric literals become `?` as well, and are counted by
+//!   kind so that callers can tell a statement that bypasses placeholders;
+//! - comments are dropped, whitespace is collapsed and unquoted words are
+//!   lowercased; quoted identifiers are kept verbatim;
+//! - conditional blocks are kept as `{{ ... }}`: a template has one fingerprint
+//!   whichever blocks end up rendered.
+//!
+//! ```sql
+//! SELECT * FROM users WHERE id IN :ids AND status = 'active' -- dashboard
+//! ```
+//!
+//! normalizes to `select * from users where id in ( ? ) and status = ?`.
+
+use super::{Ast, Settings};
+
+/// The normalized form of a statement and its stable hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fingerprint {
+    /// 16 hex digits of the FNV-1a hash of `normalized`; stable across processes.
+    pub hash: String,
+    /// The normalized statement text.
+    pub normalized: String,
+    /// Inline literals that were replaced by `?`.
+    pub literals: Literals,
+}
+
+/// Numbers of inline literals in a statement, by kind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Literals {
+    /// String literals (`'x'`, `N'x'`).
+    pub strings: usize,
+    /// Numeric literals (`10`, `1.5e3`).
+    pub numbers: usize,
+}
+
+impl Ast {
+    /// Computes the fingerprint of this AST.
+    ///
+    /// `settings` tells the tokenizer how string literals are escaped and which
+    /// comment styles exist.
+    #[must_use]
+    pub fn fingerprint(&self, settings: &Settings) -> Fingerprint {
+        let mut tokens = Vec::new();
+        let mut literals = Literals::default();
+        self.fingerprint_tokens(settings, &mut tokens, &mut literals);
+
+        // Collapse `?, ?, ?` value lists into a single `?`.
+        let mut collapsed: Vec<String> = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            let len = collapsed.len();
+            if token == "?" && len >= 2 && collapsed[len - 1] == "," && collapsed[len - 2] == "?" {
+                collapsed.pop();
+                continue;
+            }
+            collapsed.push(token);
+        }
+        while collapsed.last().is_some_and(|t| t == ";") {
+            collapsed.pop();
+        }
+
+        let normalized = collapsed.join(" ");
+        Fingerprint {
+            hash: format!("{:016x}", fnv1a(normalized.as_bytes())),
+            normalized,
+            literals,
+        }
+    }
+
+    fn fingerprint_tokens(
+        &self,
+        settings: &Settings,
+        out: &mut Vec<String>,
+        literals: &mut Literals,
+    ) {
+        match self {
+            Ast::Root { branches, .. } | Ast::Nested(branches) => {
+                for branch in branches {
+                    branch.fingerprint_tokens(settings, out, literals);
+                }
+            }
+            Ast::Raw(sql) => tokenize(sql, settings, out, literals),
+            Ast::Placeholder { .. } => out.push("?".to_string()),
+            Ast::ConditionalBlock { branches, .. } => {
+                out.push("{{".to_string());
+                for branch in branches {
+                    branch.fingerprint_tokens(settings, out, literals);
+                }
+                out.push("}}".to_string());
+            }
+            Ast::InClause { expr, .. } | Ast::NotInClause { expr, .. } => {
+                tokenize(expr, settings, out, literals);
+                if matches!(self, Ast::NotInClause { .. }) {
+                    out.push("not".to_string());
+                }
+                out.extend(["in", "(", "?", ")"].map(String::from));
+            }
+            Ast::PaginateClause { .. } => {
+                out.extend(["paginate", "?"].map(String::from));
+            }
+        }
+    }
+}
+
+/// Splits a raw SQL fragment into normalized tokens.
+fn tokenize(sql: &str, settings: &Settings, out: &mut Vec<String>, literals: &mut Literals) {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if c.is_whitespace() {
+            i += 1;
+        } else if (c == '-' && next == Some('-')) || (c == '#' && settings.comment_hash) {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && next == Some('*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+        } else if c == '\'' || (matches!(c, 'N' | 'n') && next == Some('\'')) {
+            if c != '\'' {
+                i += 1;
+            }
+            i = skip_string(&chars, i, settings);
+            out.push("?".to_string());
+            literals.strings += 1;
+        } else if c == '"' || c == '`' {
+            let start = i;
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                i += 1;
+            }
+            i += 1;
+            out.push(chars[start..i.min(chars.len())].iter().collect());
+        } else if c.is_ascii_digit() {
+            i = skip_number(&chars, i);
+            out.push("?".to_string());
+            literals.numbers += 1;
+        } else if c.is_alphanumeric() || c == '_' || c == '$' || c == '@' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '$' | '@'))
+            {
+                i += 1;
+            }
+            out.push(chars[start..i].iter().collect::<String>().to_lowercase());
+        } else {
+            out.push(c.to_string());
+            i += 1;
+        }
+    }
+}
+
+/// Returns the index just past the string literal opening at `start`.
+///
+/// Mirrors [`Ast::parse`]: `''` always escapes a quote, and a backslash escapes
+/// the next character unless `escaping_double_single_quotes` is set.
+fn skip_string(chars: &[char], start: usize, settings: &Settings) -> usize {
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if !settings.escaping_double_single_quotes => i += 2,
+            '\'' if chars.get(i + 1) == Some(&'\'') => i += 2,
+            '\'' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    chars.len()
+}
+
+/// Returns the index just past the numeric literal starting at `start`.
+fn skip_number(chars: &[char], start: usize) -> usize {
+    let mut i = start;
+    while i < chars.len()
+        && (chars[i].is_ascii_alphanumeric()
+            || chars[i] == '.'
+            || (matches!(chars[i], '+' | '-') && matches!(chars[i - 1], 'e' | 'E')))
+    {
+        i += 1;
+    }
+    i
+}
+
+/// 64-bit FNV-1a.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> Settings {
+        Settings {
+            collapsible_in_enabled: true,
+            escaping_double_single_quotes: true,
+            placeholder_dollar_sign: true,
+            max_placeholders: 65535,
+            ..Settings::default()
+        }
+    }
+
+    fn fingerprint(sql: &str) -> Fingerprint {
+        let settings = settings();
+        Ast::parse(sql, &settings).unwrap().fingerprint(&settings)
+    }
+
+    #[test]
+    fn ignores_values_layout_and_comments() {
+        let a = fingerprint("SELECT * FROM users WHERE id = $id -- by id");
+        let b = fingerprint("select *\n  from users\n where id=:id /* note */");
+        assert_eq!(a, b);
+        assert_eq!(a.normalized, "select * from users where id = ?");
+        assert_eq!(a.hash.len(), 16);
+        assert_eq!(a.literals, Literals::default());
+    }
+
+    #[test]
+    fn normalizes_literals_and_in_lists() {
+        let inline =
+            fingerprint("SELECT * FROM t WHERE a IN (1, 2, 3) AND b = 'x''y' AND c = 1.5e3");
+        let fewer = fingerprint("SELECT * FROM t WHERE a IN (7) AND b = N'z' AND c = 2");
+        assert_eq!(inline.normalized, fewer.normalized);
+        assert_eq!(
+            inline.literals,
+            Literals {
+                strings: 1,
+                numbers: 4
+            }
+        );
+
+        let bound = fingerprint("SELECT * FROM t WHERE a IN :ids");
+        assert_eq!(bound.normalized, "select * from t where a in ( ? )");
+        assert_eq!(bound.literals, Literals::default());
+    }
+
+    #[test]
+    fn keeps_identifiers_and_conditional_blocks() {
+        let fp = fingerprint(r#"SELECT "Name", col1 FROM t {{ WHERE x = :x }}"#);
+        assert_eq!(
+            fp.normalized,
+            r#"select "Name" , col1 from t {{ where x = ? }}"#
+        );
+        assert_ne!(fp.hash, fingerprint("SELECT col2 FROM t").hash);
+    }
+
+    #[test]
+    fn honours_hash_comments_and_backslash_escapes() {
+        let settings = Settings {
+            comment_hash: true,
+            ..Settings::default()
+        };
+        let fp = Ast::parse("SELECT 'it\\'s' # note", &settings)
+            .unwrap()
+            .fingerprint(&settings);
+        assert_eq!(fp.normalized, "select ?");
+        assert_eq!(fp.literals.strings, 1);
+    }
+}
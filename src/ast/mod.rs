@@ -30,14 +30,18 @@
 
 use crate::error::Error as SqlxError;
 use crate::param_value::{ParameterValue, ParamsMap, Placeholder, write::ParamVecWriteSqlTo};
+use crate::time_zone::TimeZone;
 use crate::utils::strip_prefix::StripPrefixWordIgnoreAsciiCase;
 use std::collections::BTreeSet;
 use std::fmt::Debug;
 use std::fmt::Write;
 use trim_in_place::TrimInPlace;
+mod fingerprint;
 #[cfg(test)]
 mod tests;
 
+pub use fingerprint::{Fingerprint, Literals};
+
 /// Type constraint for a placeholder, used for compile-time type checking of parameters.
 ///
 /// When a placeholder has a type constraint, the provided value must match
@@ -52,6 +56,7 @@ mod tests;
 /// - `?ud`, `:price!ud` - unsigned decimal (>= 0, int/float/numeric string; rejects null)
 /// - `?s`, `:name!s` - string (rejects null)
 /// - `?j`, `:data!j` - JSON (object, array, or explicit Json wrapper; rejects null)
+/// - `?x`, `:data!x` - binary (string or `Binary` wrapper, bound as raw bytes; rejects null)
 ///
 /// ## Nullable types (use `n` prefix)
 /// - `?n`, `:data!n` - nullable mixed (any type including null)
@@ -61,6 +66,7 @@ mod tests;
 /// - `?nud`, `:price!nud` - nullable unsigned decimal
 /// - `?ns`, `:name!ns` - nullable string
 /// - `?nj`, `:data!nj` - nullable JSON
+/// - `?nx`, `:data!nx` - nullable binary
 ///
 /// ## Array types (for IN clauses)
 /// - `?ia`, `:ids!ia` - array of integers
@@ -103,6 +109,8 @@ pub enum PlaceholderType {
     Json,
     /// Array of JSON values (`ja`). Accepts `ParameterValue::Array` where all elements are JSON-compatible.
     JsonArray,
+    /// Binary type (`x`). Accepts `ParameterValue::Binary` or `ParameterValue::String`, bound as bytes.
+    Binary,
 }
 
 impl PlaceholderType {
@@ -143,6 +151,7 @@ impl PlaceholderType {
             'd' => Some((Self::Decimal, 1)),
             's' => Some((Self::String, 1)),
             'j' => Some((Self::Json, 1)),
+            'x' => Some((Self::Binary, 1)),
             _ => None,
         }
     }
@@ -189,6 +198,7 @@ impl PlaceholderType {
             Self::StringArray => "sa",
             Self::Json => "j",
             Self::JsonArray => "ja",
+            Self::Binary => "x",
         }
     }
 
@@ -208,6 +218,7 @@ impl PlaceholderType {
             Self::StringArray => "string array",
             Self::Json => "JSON",
             Self::JsonArray => "JSON array",
+            Self::Binary => "binary",
         }
     }
 
@@ -251,12 +262,13 @@ impl PlaceholderType {
     /// * `nullable` - Whether null values are allowed
     #[must_use]
     pub fn matches(&self, value: &ParameterValue, nullable: bool) -> bool {
-        match (self, value) {
+        match (self, value.revealed()) {
             // Null is only allowed if nullable flag is set
             (_, ParameterValue::Null) => nullable,
             // Scalar types
             (Self::Int, ParameterValue::Int(_))
             | (Self::String, ParameterValue::String(_))
+            | (Self::Binary, ParameterValue::Binary(_) | ParameterValue::String(_))
             | (
                 Self::Json,
                 ParameterValue::Json(_) | ParameterValue::Object(_) | ParameterValue::Array(_),
@@ -267,29 +279,38 @@ impl PlaceholderType {
             // Array types - validate element types (nulls in arrays always allowed)
             (Self::IntArray, ParameterValue::Array(arr)) => arr
                 .iter()
+                .map(ParameterValue::revealed)
                 .all(|v| matches!(v, ParameterValue::Int(_) | ParameterValue::Null)),
-            (Self::UnsignedIntArray, ParameterValue::Array(arr)) => arr.iter().all(|v| {
-                matches!(v, ParameterValue::Int(n) if *n >= 0) || matches!(v, ParameterValue::Null)
-            }),
+            (Self::UnsignedIntArray, ParameterValue::Array(arr)) => {
+                arr.iter().map(ParameterValue::revealed).all(|v| {
+                    matches!(v, ParameterValue::Int(n) if *n >= 0)
+                        || matches!(v, ParameterValue::Null)
+                })
+            }
             (Self::DecimalArray, ParameterValue::Array(arr)) => arr
                 .iter()
+                .map(ParameterValue::revealed)
                 .all(|v| matches!(v, ParameterValue::Null) || Self::is_decimal(v)),
             (Self::UnsignedDecimalArray, ParameterValue::Array(arr)) => arr
                 .iter()
+                .map(ParameterValue::revealed)
                 .all(|v| matches!(v, ParameterValue::Null) || Self::is_unsigned_decimal(v)),
             (Self::StringArray, ParameterValue::Array(arr)) => arr
                 .iter()
+                .map(ParameterValue::revealed)
                 .all(|v| matches!(v, ParameterValue::String(_) | ParameterValue::Null)),
             // JSON array - each element must be JSON-compatible
-            (Self::JsonArray, ParameterValue::Array(arr)) => arr.iter().all(|v| {
-                matches!(
-                    v,
-                    ParameterValue::Json(_)
-                        | ParameterValue::Object(_)
-                        | ParameterValue::Array(_)
-                        | ParameterValue::Null
-                )
-            }),
+            (Self::JsonArray, ParameterValue::Array(arr)) => {
+                arr.iter().map(ParameterValue::revealed).all(|v| {
+                    matches!(
+                        v,
+                        ParameterValue::Json(_)
+                            | ParameterValue::Object(_)
+                            | ParameterValue::Array(_)
+                            | ParameterValue::Null
+                    )
+                })
+            }
             _ => false,
         }
     }
@@ -306,12 +327,14 @@ impl PlaceholderType {
             ParameterValue::Array(_) => "array",
             ParameterValue::Object(_) => "object",
             ParameterValue::Json(_) => "json",
-            ParameterValue::DateTime(_) => "datetime",
+            ParameterValue::DateTime(_) | ParameterValue::DateTimeTz(_) => "datetime",
+            ParameterValue::Binary(_) => "binary",
             ParameterValue::ByClauseRendered(_) => "ByClause",
             ParameterValue::SelectClauseRendered(_) => "SelectClause",
             ParameterValue::PaginateClauseRendered(_) => "PaginateClause",
             ParameterValue::Builder(_) => "Builder",
             ParameterValue::Identifier(_) => "Identifier",
+            ParameterValue::Secret(secret) => Self::value_type_name(&secret.value),
         }
     }
 }
@@ -421,6 +444,18 @@ pub enum IdentifierQuoteStyle {
     Bracket,
 }
 
+/// Literal syntax for byte strings in inline SQL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BinaryLiteralStyle {
+    /// `PostgreSQL`: `'\xdeadbeef'::bytea`
+    #[default]
+    Bytea,
+    /// `MySQL`/ANSI SQL: `X'deadbeef'`
+    XString,
+    /// Microsoft SQL Server: `0xdeadbeef`
+    HexNumber,
+}
+
 /// Settings that control how the SQL parser handles comments, escaping, and
 /// optional features like collapsible `IN` clauses.
 /// Settings that determine how placeholders and identifiers are rendered:
@@ -459,6 +494,15 @@ pub struct Settings {
     pub upsert_style: UpsertStyle,
     /// Identifier quoting style (double quotes, backticks, or brackets)
     pub identifier_quote_style: IdentifierQuoteStyle,
+    /// Literal syntax for binary values (`'\x..'::bytea`, `X'..'` or `0x..`)
+    pub binary_literal_style: BinaryLiteralStyle,
+    /// Render `Secret` values as `'***'` when inlining (used for logging only)
+    pub redact_secrets: bool,
+    /// Bind offset-aware date-times with their offset (`timestamptz`,
+    /// `DATETIMEOFFSET`) instead of as wall-clock time in `time_zone`
+    pub datetime_offsets: bool,
+    /// Session time zone (`OPT_TIMEZONE`)
+    pub time_zone: Option<TimeZone>,
 }
 
 impl Ast {
@@ -1098,7 +1142,7 @@ impl Ast {
                     }
                     if let Some(val) = values.get(name) {
                         // Reject null for non-nullable placeholders (regardless of type constraint)
-                        if !*nullable && matches!(val, ParameterValue::Null) {
+                        if !*nullable && matches!(val.revealed(), ParameterValue::Null) {
                             return Err(SqlxError::TypeMismatch {
                                 placeholder: name.clone(),
                                 expected: expected_type.map_or_else(
@@ -1120,10 +1164,24 @@ impl Ast {
                         }
                         // Wrap Array/Object in Json when ?j placeholder is used
                         if *expected_type == Some(PlaceholderType::Json)
-                            && matches!(val, ParameterValue::Array(_) | ParameterValue::Object(_))
+                            && matches!(
+                                val.revealed(),
+                                ParameterValue::Array(_) | ParameterValue::Object(_)
+                            )
                         {
                             ParameterValue::Json(Box::new(val.clone()))
                                 .write_sql_to(sql, out_vals, settings)?;
+                        } else if *expected_type == Some(PlaceholderType::Binary)
+                            && let ParameterValue::String(s) = val.revealed()
+                        {
+                            // Bind strings as raw bytes when ?x placeholder is used
+                            let binary = ParameterValue::Binary(s.clone().into_bytes());
+                            if val.contains_secret() {
+                                binary.into_secret()
+                            } else {
+                                binary
+                            }
+                            .write_sql_to(sql, out_vals, settings)?;
                         } else if *expected_type == Some(PlaceholderType::JsonArray) {
                             // Wrap each element in Json when ?ja placeholder is used
                             if let ParameterValue::Array(arr) = val {
@@ -1131,7 +1189,7 @@ impl Ast {
                                     .iter()
                                     .map(|v| {
                                         if matches!(
-                                            v,
+                                            v.revealed(),
                                             ParameterValue::Array(_) | ParameterValue::Object(_)
                                         ) {
                                             ParameterValue::Json(Box::new(v.clone()))
@@ -1571,3 +1571,57 @@ fn test_positional_nullable_in_conditional_block() {
     );
     assert_eq!(params, vec![ParameterValue::Int(5)]);
 }
+
+// ============================================================================
+// Binary Placeholder Tests
+// ============================================================================
+
+/// Test that ?x binds strings and Binary values as raw bytes
+#[test]
+fn test_typed_placeholder_binary() {
+    let ast = into_ast("UPDATE files SET content = ?x WHERE id = ?i");
+    let (query, params) = ast
+        .render(
+            [
+                ("0", ParameterValue::String("abc".into())),
+                ("1", ParameterValue::Int(7)),
+            ],
+            &SETTINGS,
+        )
+        .expect("Rendering should succeed");
+    collapsed_eq!(&query, "UPDATE files SET content = $1 WHERE id = $2");
+    assert_eq!(
+        params,
+        vec![
+            ParameterValue::Binary(b"abc".to_vec()),
+            ParameterValue::Int(7)
+        ]
+    );
+
+    let result = ast.render(
+        [("0", ParameterValue::Int(1)), ("1", ParameterValue::Int(7))],
+        &SETTINGS,
+    );
+    assert!(
+        result.is_err(),
+        "Integer should be invalid for ?x placeholder"
+    );
+}
+
+/// Test that binary values are inlined as bytea hex literals
+#[test]
+fn test_binary_inline_literal() {
+    let ast = into_ast("SELECT * FROM files WHERE content = :content!nx");
+    let inline = crate::ast::Settings {
+        max_placeholders: 0,
+        ..SETTINGS
+    };
+    let mut vals = ParamsMap::default();
+    vals.insert("content".into(), ParameterValue::Binary(vec![0xca, 0xfe]));
+    let (query, params) = ast.render(vals, &inline).expect("Rendering should succeed");
+    collapsed_eq!(
+        &query,
+        "SELECT * FROM files WHERE content = '\\xcafe'::bytea"
+    );
+    assert!(params.is_empty());
+}
@@ -0,0 +1,152 @@
+//! Statements collected by `$driver->batch()` and sent to the database together.
+//!
+//! The callback passed to `batch()` queues statements on a `Sqlx\Batch`; once it
+//! returns, the driver runs them all on one connection and returns one result
+//! per statement, in order.
+//!
+//! The statements are sent one after another with their parameters bound, on the
+//! ongoing transaction or pinned connection if there is one. No transaction is
+//! started for the batch, so outside of one each statement commits on its own.
+//! What it saves over separate calls is the connection checkout and the per-call
+//! overhead of the driver, not round trips: sqlx ends every statement with a
+//! sync, so they are not pipelined on PostgreSQL either.
+//!
+//! # PHP Usage
+//!
+//! ```php
+//! [$inserted, $rows] = $driver->batch(function (Sqlx\Batch $b) use ($events) {
+//!     foreach ($events as $event) {
+//!         $b->execute('INSERT INTO audit (event) VALUES (?)', [$event]);
+//!     }
+//!     $b->queryAll('SELECT count(*) AS n FROM audit');
+//! });
+//! ```
+
+use crate::param_value::ParameterValue;
+use crate::query_hook::QueryKind;
+use ext_php_rs::prelude::ModuleBuilder;
+use ext_php_rs::{php_class, php_impl};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Registers the `Batch` class with the PHP module builder.
+pub fn build(module: ModuleBuilder) -> ModuleBuilder {
+    module.class::<Batch>()
+}
+
+/// What a batched statement returns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchKind {
+    /// The number of affected rows.
+    Execute,
+    /// All rows.
+    QueryAll,
+}
+
+impl BatchKind {
+    /// Returns the kind reported to the query hooks.
+    #[must_use]
+    pub const fn query_kind(self) -> QueryKind {
+        match self {
+            Self::Execute => QueryKind::Execute,
+            Self::QueryAll => QueryKind::Query,
+        }
+    }
+}
+
+/// A statement queued on a [`Batch`].
+#[derive(Clone, Debug)]
+pub struct BatchStatement {
+    pub kind: BatchKind,
+    pub query: String,
+    pub parameters: Option<BTreeMap<String, ParameterValue>>,
+}
+
+/// Result of a batched statement.
+pub enum BatchOutcome<R> {
+    /// Affected rows of an `execute()`.
+    Affected(u64),
+    /// Rows of a `queryAll()`.
+    Rows(Vec<R>),
+}
+
+impl<R> BatchOutcome<R> {
+    /// Returns the affected or fetched row count, for the query hook.
+    pub fn count(&self) -> u64 {
+        match self {
+            Self::Affected(rows) => *rows,
+            Self::Rows(rows) => u64::try_from(rows.len()).unwrap_or(u64::MAX),
+        }
+    }
+}
+
+/// Statements queued by the callback of `$driver->batch()`.
+///
+/// Clones share their queue, so the object handed to PHP and the driver see the
+/// same statements.
+#[php_class]
+#[php(name = "Sqlx\\Batch")]
+#[derive(Clone, Default)]
+pub struct Batch {
+    statements: Arc<Mutex<Vec<BatchStatement>>>,
+}
+
+impl Batch {
+    /// Removes and returns the queued statements.
+    pub fn take(&self) -> Vec<BatchStatement> {
+        std::mem::take(&mut *self.queue())
+    }
+
+    fn queue(&self) -> std::sync::MutexGuard<'_, Vec<BatchStatement>> {
+        self.statements
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn push(
+        &self,
+        kind: BatchKind,
+        query: String,
+        parameters: Option<BTreeMap<String, ParameterValue>>,
+    ) -> usize {
+        let mut statements = self.queue();
+        statements.push(BatchStatement {
+            kind,
+            query,
+            parameters,
+        });
+        statements.len() - 1
+    }
+}
+
+#[php_impl]
+impl Batch {
+    /// Queues a statement whose result is its number of affected rows.
+    ///
+    /// # Returns
+    /// The index of its result in the array returned by `batch()`.
+    pub fn execute(
+        &self,
+        query: String,
+        parameters: Option<BTreeMap<String, ParameterValue>>,
+    ) -> usize {
+        self.push(BatchKind::Execute, query, parameters)
+    }
+
+    /// Queues a statement whose result is all of its rows.
+    ///
+    /// # Returns
+    /// The index of its result in the array returned by `batch()`.
+    pub fn query_all(
+        &self,
+        query: String,
+        parameters: Option<BTreeMap<String, ParameterValue>>,
+    ) -> usize {
+        self.push(BatchKind::QueryAll, query, parameters)
+    }
+
+    /// Returns the number of queued statements.
+    pub fn count(&self) -> usize {
+        self.queue().len()
+    }
+}
@@ -0,0 +1,118 @@
+//! Binary parameter values.
+//!
+//! PHP strings are byte strings, but a parameter string is bound as text, which
+//! the server validates against the connection encoding. `Sqlx\Binary` marks a
+//! string as raw bytes: it is bound natively as `bytea` on `PostgreSQL`, and as
+//! `VARBINARY`/`BLOB` on `MySQL` and SQL Server. Where the driver renders values
+//! into SQL text — `quote()`, `dryInline()`, inlined overflow parameters — it
+//! becomes a hex literal in the driver's dialect: `'\xcafe'::bytea`, `X'cafe'`
+//! or `0xcafe`.
+//!
+//! The `?x` placeholder type binds a plain string the same way. Strings that are
+//! not valid UTF-8 are treated as binary without wrapping.
+//!
+//! # PHP Usage
+//!
+//! ```php
+//! $driver->execute(
+//!     'INSERT INTO files (name, content) VALUES (?, ?)',
+//!     ['logo.png', new Sqlx\Binary(file_get_contents('logo.png'))],
+//! );
+//! $driver->execute('UPDATE files SET content = ?x WHERE id = ?', [$bytes, 7]);
+//! $driver->quote(new Sqlx\Binary("\xca\xfe"));
+//! // '\xcafe'::bytea
+//! ```
+
+use crate::ast::{BinaryLiteralStyle, Settings};
+use ext_php_rs::prelude::ModuleBuilder;
+use ext_php_rs::{php_class, php_impl};
+use std::fmt::Write;
+
+/// Registers the `Binary` class with the PHP module builder.
+pub fn build(module: ModuleBuilder) -> ModuleBuilder {
+    module.class::<Binary>()
+}
+
+/// A byte string bound as a binary value.
+#[php_class]
+#[php(name = "Sqlx\\Binary")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Binary {
+    /// The raw bytes.
+    pub(crate) bytes: Vec<u8>,
+}
+
+#[php_impl]
+impl Binary {
+    /// Wraps a byte string so it is bound as binary data.
+    ///
+    /// # Arguments
+    /// - `bytes`: The raw bytes; need not be valid UTF-8.
+    #[must_use]
+    pub fn __construct(bytes: ext_php_rs::binary::Binary<u8>) -> Self {
+        Self {
+            bytes: bytes.into(),
+        }
+    }
+
+    /// Returns the wrapped bytes.
+    #[must_use]
+    pub fn bytes(&self) -> ext_php_rs::binary::Binary<u8> {
+        self.bytes.clone().into()
+    }
+
+    /// Returns the bytes as lowercase hex digits.
+    #[must_use]
+    pub fn hex(&self) -> String {
+        to_hex(&self.bytes)
+    }
+}
+
+/// Encodes `bytes` as lowercase hex digits.
+#[must_use]
+pub fn to_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        let _ = write!(hex, "{byte:02x}");
+    }
+    hex
+}
+
+/// Renders `bytes` as a hex literal in the dialect of `settings`.
+#[must_use]
+pub fn hex_literal(bytes: &[u8], settings: &Settings) -> String {
+    let hex = to_hex(bytes);
+    match settings.binary_literal_style {
+        BinaryLiteralStyle::Bytea => format!("'\\x{hex}'::bytea"),
+        BinaryLiteralStyle::XString => format!("X'{hex}'"),
+        BinaryLiteralStyle::HexNumber => format!("0x{hex}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_hex() {
+        assert_eq!(to_hex(&[]), "");
+        assert_eq!(to_hex(&[0x00, 0x0f, 0xca, 0xfe, 0xff]), "000fcafeff");
+    }
+
+    #[test]
+    fn test_hex_literal() {
+        let bytes = [0xde, 0xad, 0xbe, 0xef];
+        let literal = |binary_literal_style| {
+            hex_literal(
+                &bytes,
+                &Settings {
+                    binary_literal_style,
+                    ..Settings::default()
+                },
+            )
+        };
+        assert_eq!(literal(BinaryLiteralStyle::Bytea), "'\\xdeadbeef'::bytea");
+        assert_eq!(literal(BinaryLiteralStyle::XString), "X'deadbeef'");
+        assert_eq!(literal(BinaryLiteralStyle::HexNumber), "0xdeadbeef");
+    }
+}
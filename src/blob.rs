@@ -0,0 +1,504 @@
+//! Binary values read and written in chunks.
+//!
+//! Fetching a `bytea`, `LONGBLOB` or `VARBINARY(MAX)` column materializes the
+//! whole value as one PHP string. `Sqlx\Blob` instead reads it a chunk at a
+//! time, one statement per chunk, so memory stays bounded by the chunk size:
+//!
+//! - `openBlob()` opens a binary column of one row, read-only. Chunks are read
+//!   with `SUBSTRING()`, except for compressed `PostgreSQL` values, which are
+//!   first copied into a temporary large object.
+//! - `largeObject()` opens or creates a `PostgreSQL` large object, readable and
+//!   writable, through the server-side `lo_get()` and `lo_put()` functions.
+//!
+//! `copyTo()` and `copyFrom()` move the bytes between a blob and a PHP stream
+//! resource. `toStream()` returns the value as a readable stream resource,
+//! spooled through `php://temp`, so only one chunk is ever held in memory.
+//!
+//! A stream resource is not accepted as a parameter: a bound value is sent
+//! whole, so it would have to be read to its end into memory. Write values
+//! larger than memory through `largeObject()->copyFrom()` instead.
+//!
+//! Each chunk is a separate statement: it goes through the query hooks, the
+//! ongoing transaction and the retry policy like any other statement. Chunks of
+//! a column are therefore read under separate snapshots unless a `REPEATABLE
+//! READ` transaction is open. On `PostgreSQL`, `SUBSTRING()` of a compressed
+//! value decompresses it whole, so such a value is decompressed once into a
+//! temporary large object, read through `lo_get()` and unlinked on `close()`.
+//!
+//! # PHP Usage
+//!
+//! ```php
+//! $blob = $driver->openBlob('files', 'content', ['id' => 7]);
+//! $blob->copyTo(fopen('php://output', 'wb'));
+//!
+//! $stream = $driver->openBlob('files', 'content', ['id' => 8])->toStream();
+//! while (!feof($stream)) { $hash->update(fread($stream, 8192)); }
+//!
+//! $blob = $driver->largeObject();
+//! $blob->copyFrom(fopen($_FILES['upload']['tmp_name'], 'rb'));
+//! $driver->execute('INSERT INTO files (name, content_oid) VALUES (?, ?)', ['big.iso', $blob->oid()]);
+//! ```
+
+use crate::error::Error as SqlxError;
+use crate::param_value::{ParameterValue, ParamsMap};
+use ext_php_rs::convert::IntoZvalDyn;
+use ext_php_rs::flags::DataType;
+use ext_php_rs::prelude::ModuleBuilder;
+use ext_php_rs::types::{ZendCallable, Zval};
+use ext_php_rs::{php_class, php_impl};
+use std::sync::Arc;
+
+/// Registers the `Blob` class with the PHP module builder.
+pub fn build(module: ModuleBuilder) -> ModuleBuilder {
+    module.class::<Blob>()
+}
+
+/// Bytes fetched per statement by `read()`, `copyTo()` and `copyFrom()`.
+pub const BLOB_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Storage a [`Blob`] reads from and writes to.
+pub trait BlobSource {
+    /// Returns the size in bytes.
+    ///
+    /// # Errors
+    /// Returns an error if the statement fails.
+    fn size(&self) -> crate::error::Result<u64>;
+
+    /// Reads up to `length` bytes starting at `offset`.
+    ///
+    /// # Errors
+    /// Returns an error if the statement fails or the value is gone.
+    fn read_at(&self, offset: u64, length: usize) -> crate::error::Result<Vec<u8>>;
+
+    /// Writes `bytes` at `offset`.
+    ///
+    /// # Errors
+    /// Returns an error if the source is read-only or the statement fails.
+    fn write_at(&self, offset: u64, bytes: &[u8]) -> crate::error::Result<()>;
+
+    /// Returns the OID of a large object.
+    fn oid(&self) -> Option<i64> {
+        None
+    }
+
+    /// Releases what the source keeps on the server, when the blob is closed.
+    fn release(&self) {}
+}
+
+/// Runs the statements behind a [`Blob`] through a driver.
+pub trait BlobQuery {
+    /// Runs `query` and returns the first column of its first row, or `None` if
+    /// there is no row.
+    ///
+    /// # Errors
+    /// Returns an error if the statement fails.
+    fn blob_value(&self, query: &str, parameters: ParamsMap) -> crate::error::Result<Option<Zval>>;
+
+    /// Runs `query` for its effect.
+    ///
+    /// # Errors
+    /// Returns an error if the statement fails.
+    fn blob_execute(&self, query: &str, parameters: ParamsMap) -> crate::error::Result<()>;
+}
+
+/// Reads an integer result, such as a size or an OID.
+///
+/// # Errors
+/// Returns [`SqlxError::Conversion`] if `value` is not an integer.
+pub fn value_to_u64(value: &Zval) -> crate::error::Result<u64> {
+    value
+        .long()
+        .and_then(|value| u64::try_from(value).ok())
+        .ok_or_else(|| SqlxError::Conversion {
+            message: format!(
+                "expected a non-negative integer, got {:?}",
+                value.get_type()
+            ),
+        })
+}
+
+/// A binary column of one row, read with `SUBSTRING()`.
+///
+/// Each chunk is its own statement, so outside a `REPEATABLE READ` transaction
+/// chunks may come from different versions of the row if it is updated while
+/// being read. On `PostgreSQL`, `SUBSTRING()` of a compressed TOAST value
+/// decompresses the whole value for every chunk, so `openBlob()` only uses this
+/// for values stored uncompressed.
+pub struct ColumnBlob<D> {
+    driver: Arc<D>,
+    /// `SELECT` of one chunk, with `$blob_offset` (1-based) and `$blob_length`.
+    chunk_query: String,
+    /// The row's key values.
+    key: ParamsMap,
+    size: u64,
+}
+
+impl<D: BlobQuery> ColumnBlob<D> {
+    /// Opens the value selected by `chunk_query`, whose size `length_query`
+    /// returns. Both select from the row matching `key`.
+    ///
+    /// Returns `None` if the value is `NULL`.
+    ///
+    /// # Errors
+    /// Returns [`SqlxError::Other`] if no row matches, or the statement's error.
+    pub fn open(
+        driver: Arc<D>,
+        length_query: &str,
+        chunk_query: String,
+        key: ParamsMap,
+    ) -> crate::error::Result<Option<Self>> {
+        let size = driver
+            .blob_value(length_query, key.clone())?
+            .ok_or_else(|| SqlxError::Other("openBlob: no row matches the key".to_string()))?;
+        if size.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            size: value_to_u64(&size)?,
+            driver,
+            chunk_query,
+            key,
+        }))
+    }
+}
+
+impl<D: BlobQuery> BlobSource for ColumnBlob<D> {
+    fn size(&self) -> crate::error::Result<u64> {
+        Ok(self.size)
+    }
+
+    fn read_at(&self, offset: u64, length: usize) -> crate::error::Result<Vec<u8>> {
+        let offset = i64::try_from(offset + 1).map_err(|_| SqlxError::InvalidParameter {
+            name: "offset".to_string(),
+            expected: "an offset within the value".to_string(),
+        })?;
+        let mut parameters = self.key.clone();
+        parameters.insert("blob_offset".to_string(), ParameterValue::Int(offset));
+        parameters.insert(
+            "blob_length".to_string(),
+            ParameterValue::Int(i64::try_from(length).unwrap_or(i64::MAX)),
+        );
+        self.driver
+            .blob_value(&self.chunk_query, parameters)?
+            .and_then(|chunk| chunk.binary::<u8>())
+            .ok_or_else(|| SqlxError::Other("openBlob: the value is gone".to_string()))
+    }
+
+    fn write_at(&self, _offset: u64, _bytes: &[u8]) -> crate::error::Result<()> {
+        Err(read_only_column())
+    }
+}
+
+/// The error writing to a blob opened with `openBlob()`.
+#[must_use]
+pub fn read_only_column() -> SqlxError {
+    SqlxError::Other(
+        "Blobs opened with openBlob() are read-only; bind a Sqlx\\Binary, or use a large object to write in chunks"
+            .to_string(),
+    )
+}
+
+/// Calls the PHP function `name`.
+fn call(name: &str, params: Vec<&dyn IntoZvalDyn>) -> crate::error::Result<Zval> {
+    ZendCallable::try_from_name(name)
+        .and_then(|function| function.try_call(params))
+        .map_err(|err| SqlxError::Other(format!("{name}() failed: {err}")))
+}
+
+/// A binary value read and written in chunks: a column of one row, or a
+/// `PostgreSQL` large object.
+#[php_class]
+#[php(name = "Sqlx\\Blob")]
+pub struct Blob {
+    source: Box<dyn BlobSource>,
+    size: u64,
+    position: u64,
+    /// Bytes read ahead, starting at `buffer_start`.
+    buffer: Vec<u8>,
+    buffer_start: u64,
+    closed: bool,
+}
+
+impl Blob {
+    /// Wraps `source`, reading its size.
+    ///
+    /// # Errors
+    /// Returns an error if the size can't be read.
+    pub fn new(source: impl BlobSource + 'static) -> crate::error::Result<Self> {
+        Ok(Self {
+            size: source.size()?,
+            source: Box::new(source),
+            position: 0,
+            buffer: Vec::new(),
+            buffer_start: 0,
+            closed: false,
+        })
+    }
+
+    fn ensure_open(&self) -> crate::error::Result<()> {
+        if self.closed {
+            return Err(SqlxError::Other("Blob has been closed".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Returns up to `length` bytes at the current position, fetching a chunk
+    /// of at least [`BLOB_CHUNK_SIZE`] bytes when the buffer runs out.
+    fn read_bytes(&mut self, length: usize) -> crate::error::Result<Vec<u8>> {
+        self.ensure_open()?;
+        if length == 0 || self.position >= self.size {
+            return Ok(Vec::new());
+        }
+        let buffer_end = self.buffer_start + self.buffer.len() as u64;
+        if self.position < self.buffer_start || self.position >= buffer_end {
+            self.buffer = self
+                .source
+                .read_at(self.position, length.max(BLOB_CHUNK_SIZE))?;
+            self.buffer_start = self.position;
+            if self.buffer.is_empty() {
+                // The value shrank since it was opened.
+                self.size = self.position;
+                return Ok(Vec::new());
+            }
+        }
+        let start = usize::try_from(self.position - self.buffer_start).unwrap_or(usize::MAX);
+        let end = self.buffer.len().min(start.saturating_add(length));
+        let bytes = self.buffer[start..end].to_vec();
+        self.position += bytes.len() as u64;
+        Ok(bytes)
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> crate::error::Result<()> {
+        self.ensure_open()?;
+        self.source.write_at(self.position, bytes)?;
+        self.buffer.clear();
+        self.position += bytes.len() as u64;
+        self.size = self.size.max(self.position);
+        Ok(())
+    }
+}
+
+#[php_impl]
+impl Blob {
+    /// Reads up to `length` bytes (8192 by default) from the current position.
+    ///
+    /// Returns an empty string at the end of the value.
+    ///
+    /// # Exceptions
+    /// Throws if the blob is closed or the statement fails.
+    pub fn read(
+        &mut self,
+        length: Option<i64>,
+    ) -> crate::error::Result<ext_php_rs::binary::Binary<u8>> {
+        let length =
+            usize::try_from(length.unwrap_or(8192)).map_err(|_| SqlxError::InvalidParameter {
+                name: "length".to_string(),
+                expected: "a non-negative integer".to_string(),
+            })?;
+        Ok(self.read_bytes(length)?.into())
+    }
+
+    /// Writes `bytes` at the current position; returns the number of bytes written.
+    ///
+    /// # Exceptions
+    /// Throws if the blob is read-only or closed, or the statement fails.
+    pub fn write(&mut self, bytes: ext_php_rs::binary::Binary<u8>) -> crate::error::Result<i64> {
+        let bytes: Vec<u8> = bytes.into();
+        self.write_bytes(&bytes)?;
+        Ok(i64::try_from(bytes.len()).unwrap_or(i64::MAX))
+    }
+
+    /// Moves the current position to `offset` bytes from the start.
+    ///
+    /// # Exceptions
+    /// Throws if `offset` is negative or the blob is closed.
+    pub fn seek(&mut self, offset: i64) -> crate::error::Result<()> {
+        self.ensure_open()?;
+        self.position = u64::try_from(offset).map_err(|_| SqlxError::InvalidParameter {
+            name: "offset".to_string(),
+            expected: "a non-negative integer".to_string(),
+        })?;
+        Ok(())
+    }
+
+    /// Returns the current position.
+    #[must_use]
+    pub fn tell(&self) -> i64 {
+        i64::try_from(self.position).unwrap_or(i64::MAX)
+    }
+
+    /// Returns true once the current position reached the end of the value.
+    #[must_use]
+    pub fn eof(&self) -> bool {
+        self.position >= self.size
+    }
+
+    /// Returns the size of the value in bytes.
+    #[must_use]
+    pub fn size(&self) -> i64 {
+        i64::try_from(self.size).unwrap_or(i64::MAX)
+    }
+
+    /// Returns the OID of a large object, or null for a column.
+    #[must_use]
+    pub fn oid(&self) -> Option<i64> {
+        self.source.oid()
+    }
+
+    /// Writes the rest of the value to a PHP stream, a chunk at a time; returns
+    /// the number of bytes copied.
+    ///
+    /// # Exceptions
+    /// Throws if the blob is closed, a statement fails or the stream can't be written.
+    pub fn copy_to(&mut self, stream: &Zval) -> crate::error::Result<i64> {
+        let mut copied: i64 = 0;
+        loop {
+            let chunk = self.read_bytes(BLOB_CHUNK_SIZE)?;
+            if chunk.is_empty() {
+                return Ok(copied);
+            }
+            let length = i64::try_from(chunk.len()).unwrap_or(i64::MAX);
+            let mut data = Zval::new();
+            data.set_binary(chunk);
+            if call("fwrite", vec![stream, &data])?.long() != Some(length) {
+                return Err(SqlxError::Other(
+                    "copyTo: failed to write to the stream".to_string(),
+                ));
+            }
+            copied += length;
+        }
+    }
+
+    /// Writes the rest of a PHP stream at the current position, a chunk at a
+    /// time; returns the number of bytes copied.
+    ///
+    /// # Exceptions
+    /// Throws if the blob is read-only or closed, a statement fails or the
+    /// stream can't be read.
+    pub fn copy_from(&mut self, stream: &Zval) -> crate::error::Result<i64> {
+        let chunk_size = i64::try_from(BLOB_CHUNK_SIZE).unwrap_or(i64::MAX);
+        let mut copied: i64 = 0;
+        loop {
+            let chunk = call("fread", vec![stream, &chunk_size])?
+                .binary::<u8>()
+                .ok_or_else(|| {
+                    SqlxError::Other("copyFrom: failed to read from the stream".to_string())
+                })?;
+            if chunk.is_empty() {
+                return Ok(copied);
+            }
+            self.write_bytes(&chunk)?;
+            copied += i64::try_from(chunk.len()).unwrap_or(i64::MAX);
+        }
+    }
+
+    /// Copies the rest of the value, a chunk at a time, into a `php://temp`
+    /// stream and returns it rewound.
+    ///
+    /// `php://temp` keeps the first chunk in memory and spills the rest to a
+    /// temporary file, so the value never sits in memory as a whole. To send it
+    /// on without the temporary copy, use `copyTo()`.
+    ///
+    /// # Exceptions
+    /// Throws if the blob is closed, a statement fails or the temporary stream
+    /// can't be opened or written.
+    pub fn to_stream(&mut self) -> crate::error::Result<Zval> {
+        let path = format!("php://temp/maxmemory:{BLOB_CHUNK_SIZE}");
+        let stream = call("fopen", vec![&path, &"w+b".to_string()])?;
+        if !matches!(stream.get_type(), DataType::Resource) {
+            return Err(SqlxError::Other(format!("toStream: failed to open {path}")));
+        }
+        self.copy_to(&stream)?;
+        call("rewind", vec![&stream])?;
+        Ok(stream)
+    }
+
+    /// Closes the blob, dropping the temporary copy of a compressed column;
+    /// further reads and writes throw.
+    pub fn close(&mut self) {
+        if !self.closed {
+            self.source.release();
+        }
+        self.closed = true;
+        self.buffer = Vec::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// An in-memory source counting its reads.
+    struct MemorySource {
+        bytes: RefCell<Vec<u8>>,
+        reads: Rc<RefCell<usize>>,
+    }
+
+    impl BlobSource for MemorySource {
+        fn size(&self) -> crate::error::Result<u64> {
+            Ok(self.bytes.borrow().len() as u64)
+        }
+
+        fn read_at(&self, offset: u64, length: usize) -> crate::error::Result<Vec<u8>> {
+            *self.reads.borrow_mut() += 1;
+            let bytes = self.bytes.borrow();
+            let start = bytes.len().min(usize::try_from(offset).unwrap());
+            let end = bytes.len().min(start + length);
+            Ok(bytes[start..end].to_vec())
+        }
+
+        fn write_at(&self, offset: u64, bytes: &[u8]) -> crate::error::Result<()> {
+            let mut stored = self.bytes.borrow_mut();
+            let offset = usize::try_from(offset).unwrap();
+            if stored.len() < offset + bytes.len() {
+                stored.resize(offset + bytes.len(), 0);
+            }
+            stored[offset..offset + bytes.len()].copy_from_slice(bytes);
+            Ok(())
+        }
+    }
+
+    fn blob(bytes: Vec<u8>) -> (Blob, Rc<RefCell<usize>>) {
+        let reads = Rc::new(RefCell::new(0));
+        let source = MemorySource {
+            bytes: RefCell::new(bytes),
+            reads: reads.clone(),
+        };
+        (Blob::new(source).unwrap(), reads)
+    }
+
+    #[test]
+    fn test_reads_through_a_chunk_buffer() {
+        let (mut blob, reads) = blob((0..=255).collect());
+        assert_eq!(blob.read_bytes(4).unwrap(), [0, 1, 2, 3]);
+        assert_eq!(blob.read_bytes(2).unwrap(), [4, 5]);
+        assert_eq!(*reads.borrow(), 1);
+
+        blob.seek(254).unwrap();
+        assert_eq!(blob.read_bytes(10).unwrap(), [254, 255]);
+        assert!(blob.eof());
+        assert!(blob.read_bytes(10).unwrap().is_empty());
+        assert_eq!(*reads.borrow(), 1);
+    }
+
+    #[test]
+    fn test_writes_extend_and_invalidate_the_buffer() {
+        let (mut blob, _) = blob(vec![1, 2, 3]);
+        assert_eq!(blob.read_bytes(1).unwrap(), [1]);
+        blob.write_bytes(&[9, 9, 9, 9]).unwrap();
+        assert_eq!((blob.size(), blob.tell()), (5, 5));
+
+        blob.seek(0).unwrap();
+        assert_eq!(blob.read_bytes(10).unwrap(), [1, 9, 9, 9, 9]);
+    }
+
+    #[test]
+    fn test_closed_blob_rejects_reads() {
+        let (mut blob, _) = blob(vec![1]);
+        blob.close();
+        assert!(blob.read_bytes(1).is_err());
+        assert!(blob.write_bytes(&[1]).is_err());
+    }
+}
@@ -0,0 +1,400 @@
+//! Circuit breaker for failing databases (`OPT_CIRCUIT_BREAKER`).
+//!
+//! Without a breaker, every request made while the database is down waits for
+//! `acquire_timeout` and then for its retries, which ties up all PHP workers.
+//! The breaker watches the outcome of each statement and moves between three
+//! states:
+//!
+//! - **closed**: statements run normally. After `failures` consecutive
+//!   connection-class errors (connection refused or dropped, pool timeouts, I/O
+//!   and TLS errors) the circuit opens. Any other outcome, including a query
+//!   error, proves the database is reachable and resets the count.
+//! - **open**: statements fail immediately with a `CircuitOpenException`, without
+//!   touching the pool, until `cooldown` has elapsed.
+//! - **half-open**: up to `probes` statements are let through. A success closes
+//!   the circuit; a connection-class error opens it for another `cooldown`.
+//!
+//! The breaker belongs to the connection pool, so drivers sharing a pool through
+//! `OPT_PERSISTENT_NAME` share its state across requests.
+//!
+//! # Example
+//!
+//! ```php
+//! $driver = DriverFactory::make([
+//!     DriverOptions::OPT_URL => 'postgres://localhost/app',
+//!     DriverOptions::OPT_PERSISTENT_NAME => 'app',
+//!     DriverOptions::OPT_CIRCUIT_BREAKER => ['failures' => 5, 'cooldown' => '30s'],
+//! ]);
+//! $driver->circuitState(); // "closed", "open" or "half_open"
+//! ```
+
+use crate::error::{Error as SqlxError, Result};
+use crate::param_value::ParameterValue;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Consecutive connection-class errors that open the circuit by default.
+pub const DEFAULT_FAILURES: u32 = 5;
+
+/// How long the circuit stays open by default.
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Parsed `OPT_CIRCUIT_BREAKER` settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive connection-class errors that open the circuit.
+    pub failures: u32,
+    /// How long the circuit stays open before letting probes through.
+    pub cooldown: Duration,
+    /// Statements let through at a time while half-open.
+    pub probes: u32,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failures: DEFAULT_FAILURES,
+            cooldown: DEFAULT_COOLDOWN,
+            probes: 1,
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    /// Parses the `OPT_CIRCUIT_BREAKER` value: `true` for the defaults, or an
+    /// array with any of the keys `failures`, `cooldown` and `probes`.
+    ///
+    /// # Errors
+    /// Returns a configuration error for unknown keys or invalid values.
+    pub fn parse(value: &ParameterValue) -> Result<Self> {
+        const OPTION: &str = "circuit_breaker";
+        let map = match value {
+            ParameterValue::Bool(true) => return Ok(Self::default()),
+            ParameterValue::Array(items) if items.is_empty() => return Ok(Self::default()),
+            ParameterValue::Object(map) => map,
+            _ => {
+                return Err(SqlxError::config(
+                    OPTION,
+                    "must be true or an array (failures, cooldown, probes)",
+                ));
+            }
+        };
+        let positive = |key: &str, value: &ParameterValue| match value {
+            ParameterValue::Int(n) if *n > 0 => u32::try_from(*n)
+                .map_err(|_| SqlxError::config(OPTION, format!("`{key}` is too large"))),
+            _ => Err(SqlxError::config(
+                OPTION,
+                format!("`{key}` must be a positive integer"),
+            )),
+        };
+        let mut config = Self::default();
+        for (key, value) in map {
+            match (key.as_str(), value) {
+                (_, ParameterValue::Null) => {}
+                ("failures", value) => config.failures = positive(key, value)?,
+                ("probes", value) => config.probes = positive(key, value)?,
+                ("cooldown", ParameterValue::String(value)) => {
+                    config.cooldown = parse_duration::parse(value)
+                        .map_err(|e| SqlxError::config(OPTION, format!("`cooldown`: {e}")))?;
+                }
+                ("cooldown", ParameterValue::Int(value)) => {
+                    config.cooldown = Duration::from_secs(u64::try_from(*value).map_err(|_| {
+                        SqlxError::config(OPTION, "`cooldown` must be non-negative")
+                    })?);
+                }
+                ("cooldown", _) => {
+                    return Err(SqlxError::config(
+                        OPTION,
+                        "`cooldown` must be a duration string or an integer number of seconds",
+                    ));
+                }
+                _ => {
+                    return Err(SqlxError::config(
+                        OPTION,
+                        format!("unknown key `{key}` (expected failures, cooldown or probes)"),
+                    ));
+                }
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// The state reported by `circuitState()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Statements run normally.
+    Closed,
+    /// Statements fail fast.
+    Open,
+    /// Probe statements are let through.
+    HalfOpen,
+}
+
+impl CircuitState {
+    /// Returns the name passed to PHP.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Closed => "closed",
+            Self::Open => "open",
+            Self::HalfOpen => "half_open",
+        }
+    }
+}
+
+/// Internal state of the breaker.
+#[derive(Debug, Clone, Copy)]
+enum State {
+    /// Counting consecutive connection-class errors.
+    Closed { failures: u32 },
+    /// Failing fast until `until`.
+    Open { until: Instant },
+    /// `probes` statements are in flight.
+    HalfOpen { probes: u32 },
+}
+
+/// A circuit breaker shared by every statement sent through one pool.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    state: Mutex<State>,
+}
+
+impl CircuitBreaker {
+    /// Creates a closed breaker.
+    #[must_use]
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(State::Closed { failures: 0 }),
+        }
+    }
+
+    /// Returns the current state. An open circuit whose cooldown has elapsed is
+    /// reported as half-open: the next statement is a probe.
+    #[must_use]
+    pub fn state(&self) -> CircuitState {
+        match *self.state.lock().unwrap_or_else(PoisonError::into_inner) {
+            State::Closed { .. } => CircuitState::Closed,
+            State::Open { until } if Instant::now() < until => CircuitState::Open,
+            State::Open { .. } | State::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Asks to run a statement. Every successful call must be followed by
+    /// [`Self::record`] or [`Self::cancel`] once the statement has run;
+    /// [`Self::permit`] does so even if the statement never completes.
+    ///
+    /// # Errors
+    /// Returns [`SqlxError::CircuitOpen`] while the circuit is open, or while
+    /// half-open with all probes already in flight.
+    pub fn acquire(&self) -> Result<()> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        match *state {
+            State::Closed { .. } => Ok(()),
+            State::Open { until } => {
+                let now = Instant::now();
+                if now < until {
+                    return Err(SqlxError::CircuitOpen {
+                        retry_in_ms: u64::try_from((until - now).as_millis()).unwrap_or(u64::MAX),
+                    });
+                }
+                *state = State::HalfOpen { probes: 1 };
+                Ok(())
+            }
+            State::HalfOpen { probes } if probes < self.config.probes => {
+                *state = State::HalfOpen { probes: probes + 1 };
+                Ok(())
+            }
+            State::HalfOpen { .. } => Err(SqlxError::CircuitOpen { retry_in_ms: 0 }),
+        }
+    }
+
+    /// Records the outcome of a statement let through by [`Self::acquire`].
+    ///
+    /// `connection_failure` is true if it failed with a connection-class error.
+    pub fn record(&self, connection_failure: bool) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        *state = match (*state, connection_failure) {
+            (_, false) => State::Closed { failures: 0 },
+            (State::Closed { failures }, true) if failures + 1 < self.config.failures => {
+                State::Closed {
+                    failures: failures + 1,
+                }
+            }
+            // A probe from an earlier half-open period may report after the
+            // circuit reopened; it doesn't extend the cooldown.
+            (State::Open { until }, true) => State::Open { until },
+            (State::Closed { .. } | State::HalfOpen { .. }, true) => State::Open {
+                until: Instant::now() + self.config.cooldown,
+            },
+        };
+    }
+
+    /// Gives back a statement let through by [`Self::acquire`] whose outcome
+    /// says nothing about the database, such as running out of request deadline.
+    pub fn cancel(&self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if let State::HalfOpen { probes } = *state {
+            *state = State::HalfOpen {
+                probes: probes.saturating_sub(1),
+            };
+        }
+    }
+
+    /// Asks to run a statement, like [`Self::acquire`], and returns a permit that
+    /// reports its outcome.
+    ///
+    /// # Errors
+    /// Returns [`SqlxError::CircuitOpen`] while the circuit is open, or while
+    /// half-open with all probes already in flight.
+    pub fn permit(self: &Arc<Self>) -> Result<CircuitPermit> {
+        self.acquire()?;
+        Ok(CircuitPermit {
+            breaker: Some(self.clone()),
+        })
+    }
+}
+
+/// A statement let through by [`CircuitBreaker::permit`].
+///
+/// A permit dropped without [`Self::settle`] (the statement panicked, or its
+/// task was aborted) is given back with [`CircuitBreaker::cancel`], so a
+/// half-open circuit doesn't wait forever for a probe that never reports.
+#[must_use]
+pub struct CircuitPermit {
+    breaker: Option<Arc<CircuitBreaker>>,
+}
+
+impl CircuitPermit {
+    /// Reports the result of the statement: running out of request deadline is
+    /// given back with [`CircuitBreaker::cancel`], anything else is recorded.
+    pub fn settle<T>(mut self, result: &Result<T>) {
+        let Some(breaker) = self.breaker.take() else {
+            return;
+        };
+        match result {
+            Err(SqlxError::DeadlineExceeded { .. }) => breaker.cancel(),
+            result => breaker.record(result.as_ref().is_err_and(SqlxError::is_connection_failure)),
+        }
+    }
+}
+
+impl Drop for CircuitPermit {
+    fn drop(&mut self) {
+        if let Some(breaker) = self.breaker.take() {
+            breaker.cancel();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::param_value::ParamsMap;
+
+    fn breaker(failures: u32, cooldown: Duration) -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerConfig {
+            failures,
+            cooldown,
+            probes: 1,
+        })
+    }
+
+    #[test]
+    fn opens_after_consecutive_connection_failures() {
+        let breaker = breaker(3, Duration::from_secs(30));
+        for _ in 0..2 {
+            breaker.acquire().unwrap();
+            breaker.record(true);
+        }
+        // A query error proves the database is reachable
+        breaker.acquire().unwrap();
+        breaker.record(false);
+        for _ in 0..2 {
+            breaker.acquire().unwrap();
+            breaker.record(true);
+        }
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        breaker.acquire().unwrap();
+        breaker.record(true);
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(matches!(
+            breaker.acquire(),
+            Err(SqlxError::CircuitOpen { retry_in_ms }) if retry_in_ms > 0
+        ));
+    }
+
+    #[test]
+    fn half_open_lets_probes_through() {
+        let breaker = breaker(1, Duration::ZERO);
+        breaker.acquire().unwrap();
+        breaker.record(true);
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        // One probe at a time
+        breaker.acquire().unwrap();
+        assert!(breaker.acquire().is_err());
+
+        // A cancelled probe frees its slot
+        breaker.cancel();
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        breaker.acquire().unwrap();
+
+        // A failed probe reopens the circuit, a successful one closes it
+        breaker.record(true);
+        breaker.acquire().unwrap();
+        breaker.record(false);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn dropped_permit_frees_its_probe() {
+        let breaker = Arc::new(breaker(1, Duration::ZERO));
+        breaker
+            .permit()
+            .unwrap()
+            .settle(&Err::<(), _>(SqlxError::PoolExhausted { timeout_ms: 0 }));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        drop(breaker.permit().unwrap());
+        let permit = breaker.permit().unwrap();
+        assert!(breaker.permit().is_err());
+
+        permit.settle(&Ok(()));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn parses_config() {
+        let config = CircuitBreakerConfig::parse(&ParameterValue::Object(ParamsMap::from([
+            ("failures".to_string(), ParameterValue::Int(3)),
+            ("cooldown".to_string(), "10s".into()),
+        ])))
+        .unwrap();
+        assert_eq!(config.failures, 3);
+        assert_eq!(config.cooldown, Duration::from_secs(10));
+        assert_eq!(config.probes, 1);
+
+        assert_eq!(
+            CircuitBreakerConfig::parse(&ParameterValue::Bool(true)).unwrap(),
+            CircuitBreakerConfig::default()
+        );
+        assert!(
+            CircuitBreakerConfig::parse(&ParameterValue::Object(ParamsMap::from([(
+                "failures".to_string(),
+                ParameterValue::Int(0)
+            )])))
+            .is_err()
+        );
+        assert!(
+            CircuitBreakerConfig::parse(&ParameterValue::Object(ParamsMap::from([(
+                "threshold".to_string(),
+                ParameterValue::Int(3)
+            )])))
+            .is_err()
+        );
+    }
+}
@@ -3,6 +3,7 @@ use crate::error::Error as SqlxError;
 use crate::lazy_row::{LazyRow, LazyRowJson};
 use crate::param_value::ParameterValue;
 use crate::select_clause::SelectClauseRendered;
+use crate::time_zone::TimeZone;
 use ext_php_rs::ZvalConvert;
 use ext_php_rs::boxed::ZBox;
 use ext_php_rs::convert::IntoZval;
@@ -123,8 +124,13 @@ pub trait Conversion: Row {
     /// - Implements `JsonSerializable` for `json_encode()`
     /// - Lazily decodes large JSON values via `__get`
     ///
-    /// The `associative_arrays` parameter controls how nested JSON objects are decoded.
-    fn into_zval(self, associative_arrays: bool) -> crate::error::Result<Zval>
+    /// The `associative_arrays` parameter controls how nested JSON objects are decoded,
+    /// and `time_zone` (`OPT_TIMEZONE`) the zone `TIMESTAMPTZ` values are rendered in.
+    fn into_zval(
+        self,
+        associative_arrays: bool,
+        time_zone: Option<TimeZone>,
+    ) -> crate::error::Result<Zval>
     where
         Self: Sized,
     {
@@ -138,7 +144,7 @@ pub trait Conversion: Row {
             zend_array::with_capacity(u32::try_from(columns.len())?),
             |mut array, column| -> crate::error::Result<ZBox<zend_array>> {
                 let column_name = column.name();
-                let value = self.column_value_into_zval(column, associative_arrays)?;
+                let value = self.column_value_into_zval(column, associative_arrays, time_zone)?;
 
                 // Check if the value contains a LazyRowJson (deferred JSON parsing)
                 #[cfg(feature = "lazy-row")]
@@ -226,12 +232,13 @@ pub trait Conversion: Row {
     /// constructor is **not** invoked (matching PDO's `FETCH_CLASS` behavior).
     /// Columns without a usable name fall back to an `_{ordinal}` property.
     ///
-    /// `associative_arrays` controls how nested JSON values inside columns are
-    /// decoded, exactly as in [`Conversion::into_zval`].
+    /// `associative_arrays` and `time_zone` control how column values are
+    /// rendered, exactly as in [`Conversion::into_zval`].
     fn into_class_zval(
         self,
         ce: &ClassEntry,
         associative_arrays: bool,
+        time_zone: Option<TimeZone>,
     ) -> crate::error::Result<Zval>
     where
         Self: Sized,
@@ -239,7 +246,7 @@ pub trait Conversion: Row {
         let mut object = zend_object::new(ce);
         for column in self.columns() {
             let column_name = column.name();
-            let value = self.column_value_into_zval(column, associative_arrays)?;
+            let value = self.column_value_into_zval(column, associative_arrays, time_zone)?;
             let prop_name = if !column_name.is_empty() && column_name != "?column?" {
                 Cow::Borrowed(column_name)
             } else {
@@ -266,13 +273,16 @@ pub trait Conversion: Row {
         self,
         target: &ResolvedTarget,
         associative_arrays: bool,
+        time_zone: Option<TimeZone>,
     ) -> crate::error::Result<Zval>
     where
         Self: Sized,
     {
         match target {
-            ResolvedTarget::Single(ce) => self.into_class_zval(ce, associative_arrays),
-            ResolvedTarget::Map(aliases) => self.into_aliased_row_zval(aliases, associative_arrays),
+            ResolvedTarget::Single(ce) => self.into_class_zval(ce, associative_arrays, time_zone),
+            ResolvedTarget::Map(aliases) => {
+                self.into_aliased_row_zval(aliases, associative_arrays, time_zone)
+            }
         }
     }
 
@@ -286,6 +296,7 @@ pub trait Conversion: Row {
         self,
         aliases: &[(String, &'static ClassEntry)],
         associative_arrays: bool,
+        time_zone: Option<TimeZone>,
     ) -> crate::error::Result<Zval>
     where
         Self: Sized,
@@ -297,7 +308,8 @@ pub trait Conversion: Row {
             let mut instance = zend_object::new(ce);
             for column in columns {
                 if let Some(prop_name) = column.name().strip_prefix(prefix.as_str()) {
-                    let value = self.column_value_into_zval(column, associative_arrays)?;
+                    let value =
+                        self.column_value_into_zval(column, associative_arrays, time_zone)?;
                     instance.set_property(prop_name, value).map_err(|err| {
                         SqlxError::Conversion {
                             message: format!("{err:?}"),
@@ -325,6 +337,7 @@ pub trait Conversion: Row {
     /// # Arguments
     /// - `column`: Reference to the column in the row.
     /// - `associative_arrays`: Whether to render complex types as associative arrays
+    /// - `time_zone`: Time zone `TIMESTAMPTZ` values are rendered in (`OPT_TIMEZONE`)
     ///
     /// # Returns
     /// A PHP-compatible `Zval` value
@@ -332,6 +345,7 @@ pub trait Conversion: Row {
         &self,
         column: &C,
         associative_arrays: bool,
+        time_zone: Option<TimeZone>,
     ) -> crate::error::Result<Zval>
     where
         C: Column<Database = D>;
@@ -339,6 +353,7 @@ pub trait Conversion: Row {
     fn column_value_into_array_key<'a, C, D>(
         &self,
         column: &C,
+        time_zone: Option<TimeZone>,
     ) -> crate::error::Result<ArrayKey<'a>>
     where
         C: Column<Database = D>;
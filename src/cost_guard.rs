@@ -0,0 +1,451 @@
+//! Development-mode query cost guard (`OPT_COST_GUARD`).
+//!
+//! When enabled, every distinct rendered statement is `EXPLAIN`ed once, before
+//! its first execution, and the plan summary is checked against the configured
+//! thresholds:
+//!
+//! - `max_cost`: total estimated cost, in the database's own cost units;
+//! - `max_rows`: estimated number of rows produced;
+//! - `seq_scan_min_rows`: a sequential/full table scan on a table holding at
+//!   least this many rows (per the database's statistics). `0` flags every scan.
+//!
+//! The verdict is cached per rendered shape, so a query that renders to the same
+//! SQL (same AST, same `IN` list length, same conditional blocks) is never
+//! explained twice. Violations are either reported to the `onQuery` hook as its
+//! `$warnings` argument (`'action' => 'warn'`, the default) or thrown as a
+//! `NotPermittedException` before the statement runs (`'action' => 'throw'`).
+//!
+//! Statements that cannot be explained (DDL, `SET`, ...) are never checked, and a
+//! failing `EXPLAIN` (e.g. a temporary table only visible to the current
+//! transaction) counts as no violation: the guard must not break the application
+//! it is watching. A failed `EXPLAIN` is not cached as a verdict: the statement
+//! is remembered as failed until the end of the request, so it isn't explained
+//! again on every execution, and is checked again by later requests.
+//!
+//! Inside a transaction or on a pinned connection, the `EXPLAIN` runs on that
+//! connection, so it sees the session's temporary tables and uncommitted
+//! changes.
+//!
+//! # Example
+//!
+//! ```php
+//! $driver = DriverFactory::make([
+//!     DriverOptions::OPT_URL => 'postgres://localhost/app',
+//!     DriverOptions::OPT_COST_GUARD => [
+//!         'max_cost' => 10_000,
+//!         'seq_scan_min_rows' => 50_000,
+//!         'action' => 'throw',
+//!     ],
+//! ]);
+//! ```
+
+use crate::error::{Error as SqlxError, Result};
+use crate::explain::{PlanSummary, leading_keyword};
+use crate::param_value::ParameterValue;
+use std::collections::HashSet;
+use std::sync::{Mutex, PoisonError};
+use threadsafe_lru::LruCache;
+
+/// What the guard does when a statement exceeds a threshold.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CostGuardAction {
+    /// Report the violations to the `onQuery` hook and run the statement.
+    #[default]
+    Warn,
+    /// Refuse to run the statement.
+    Throw,
+}
+
+/// Parsed `OPT_COST_GUARD` settings.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CostGuardConfig {
+    /// Maximum total estimated cost.
+    pub max_cost: Option<f64>,
+    /// Maximum estimated number of rows produced.
+    pub max_rows: Option<f64>,
+    /// Flag sequential scans on tables with at least this many rows.
+    pub seq_scan_min_rows: Option<u64>,
+    /// What to do on a violation.
+    pub action: CostGuardAction,
+}
+
+impl CostGuardConfig {
+    /// Parses the `OPT_COST_GUARD` options array.
+    ///
+    /// # Errors
+    /// Returns a configuration error for unknown keys, invalid values, or an
+    /// array that sets no threshold at all.
+    pub fn parse(value: &ParameterValue) -> Result<Self> {
+        const OPTION: &str = "cost_guard";
+        let ParameterValue::Object(map) = value else {
+            return Err(SqlxError::config(
+                OPTION,
+                "must be an array of thresholds (max_cost, max_rows, seq_scan_min_rows)",
+            ));
+        };
+        let mut config = Self::default();
+        for (key, value) in map {
+            match (key.as_str(), value) {
+                (_, ParameterValue::Null) => {}
+                ("max_cost", value) => config.max_cost = Some(non_negative(key, value)?),
+                ("max_rows", value) => config.max_rows = Some(non_negative(key, value)?),
+                ("seq_scan_min_rows", ParameterValue::Int(n)) => {
+                    config.seq_scan_min_rows = Some(u64::try_from(*n).map_err(|_| {
+                        SqlxError::config(OPTION, "`seq_scan_min_rows` must be non-negative")
+                    })?);
+                }
+                ("seq_scan_min_rows", _) => {
+                    return Err(SqlxError::config(
+                        OPTION,
+                        "`seq_scan_min_rows` must be an integer",
+                    ));
+                }
+                ("action", ParameterValue::String(action)) => {
+                    config.action = match action.as_str() {
+                        "warn" => CostGuardAction::Warn,
+                        "throw" => CostGuardAction::Throw,
+                        _ => {
+                            return Err(SqlxError::config(
+                                OPTION,
+                                "`action` must be \"warn\" or \"throw\"",
+                            ));
+                        }
+                    };
+                }
+                ("action", _) => {
+                    return Err(SqlxError::config(OPTION, "`action` must be a string"));
+                }
+                _ => {
+                    return Err(SqlxError::config(
+                        OPTION,
+                        format!(
+                            "unknown key `{key}` (expected max_cost, max_rows, seq_scan_min_rows or action)"
+                        ),
+                    ));
+                }
+            }
+        }
+        if config.max_cost.is_none()
+            && config.max_rows.is_none()
+            && config.seq_scan_min_rows.is_none()
+        {
+            return Err(SqlxError::config(
+                OPTION,
+                "set at least one of max_cost, max_rows or seq_scan_min_rows",
+            ));
+        }
+        Ok(config)
+    }
+
+    /// Checks a plan summary against the thresholds and describes every violation.
+    ///
+    /// `table_rows` returns the database's row-count estimate for a table; it is
+    /// only consulted for sequentially scanned tables, and only when
+    /// `seq_scan_min_rows` is above zero. Tables of unknown size are not flagged.
+    pub fn violations(
+        &self,
+        summary: &PlanSummary,
+        mut table_rows: impl FnMut(&str) -> Option<u64>,
+    ) -> Vec<String> {
+        let mut violations = Vec::new();
+        if let (Some(max), Some(cost)) = (self.max_cost, summary.total_cost)
+            && cost > max
+        {
+            violations.push(format!("estimated cost {cost} exceeds max_cost {max}"));
+        }
+        if let (Some(max), Some(rows)) = (self.max_rows, summary.estimated_rows)
+            && rows > max
+        {
+            violations.push(format!("estimated rows {rows} exceed max_rows {max}"));
+        }
+        if let Some(min) = self.seq_scan_min_rows {
+            for table in &summary.seq_scan_tables {
+                if min == 0 {
+                    violations.push(format!("sequential scan on `{table}`"));
+                } else if let Some(rows) = table_rows(table)
+                    && rows >= min
+                {
+                    violations.push(format!(
+                        "sequential scan on `{table}` (~{rows} rows, seq_scan_min_rows {min})"
+                    ));
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// Checks a non-negative numeric threshold.
+fn non_negative(key: &str, value: &ParameterValue) -> Result<f64> {
+    #[allow(clippy::cast_precision_loss)]
+    let number = match value {
+        ParameterValue::Int(n) => *n as f64,
+        ParameterValue::Float(f) => *f,
+        _ => {
+            return Err(SqlxError::config(
+                "cost_guard",
+                format!("`{key}` must be a number"),
+            ));
+        }
+    };
+    if number < 0.0 {
+        return Err(SqlxError::config(
+            "cost_guard",
+            format!("`{key}` must be non-negative"),
+        ));
+    }
+    Ok(number)
+}
+
+/// The cost guard of a driver: its settings plus the per-shape verdict cache.
+pub struct CostGuard {
+    /// Thresholds and action.
+    pub config: CostGuardConfig,
+    /// Violations found for each rendered statement (empty when it passed).
+    verdicts: LruCache<String, Vec<String>>,
+    /// Rendered statements whose `EXPLAIN` failed during the current request.
+    failed: Mutex<HashSet<String>>,
+    /// Failed statements remembered at most, the capacity of `verdicts`.
+    failed_capacity: usize,
+}
+
+impl std::fmt::Debug for CostGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CostGuard")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CostGuard {
+    /// Creates a guard whose verdict cache has the same geometry as the AST cache.
+    #[must_use]
+    pub fn new(config: CostGuardConfig, shard_count: usize, shard_size: usize) -> Self {
+        Self {
+            config,
+            verdicts: LruCache::new(shard_count, shard_size),
+            failed: Mutex::new(HashSet::new()),
+            failed_capacity: shard_count.saturating_mul(shard_size),
+        }
+    }
+
+    /// Checks a rendered statement, explaining it on first sight.
+    ///
+    /// `inspect` runs the `EXPLAIN` and returns the violations, or `None` if the
+    /// statement could not be explained. A statement that could not be explained
+    /// passes, and isn't explained again until [`Self::forget_failures`] is
+    /// called at the end of the request. Returns the violations to report as
+    /// warnings.
+    ///
+    /// # Errors
+    /// Returns [`SqlxError::CostExceeded`] if there are violations and the action
+    /// is [`CostGuardAction::Throw`].
+    pub fn check(
+        &self,
+        sql: &str,
+        inspect: impl FnOnce() -> Option<Vec<String>>,
+    ) -> Result<Vec<String>> {
+        if !is_explainable(sql) {
+            return Ok(Vec::new());
+        }
+        let violations = if let Some(verdict) = self.verdicts.get(sql) {
+            verdict
+        } else if self.failed().contains(sql) {
+            return Ok(Vec::new());
+        } else if let Some(verdict) = inspect() {
+            self.verdicts.insert(sql.to_owned(), verdict.clone());
+            verdict
+        } else {
+            let mut failed = self.failed();
+            if failed.len() >= self.failed_capacity {
+                failed.clear();
+            }
+            failed.insert(sql.to_owned());
+            return Ok(Vec::new());
+        };
+        if !violations.is_empty() && self.config.action == CostGuardAction::Throw {
+            return Err(SqlxError::CostExceeded {
+                sql: sql.to_owned(),
+                violations,
+            });
+        }
+        Ok(violations)
+    }
+
+    /// Forgets the statements whose `EXPLAIN` failed, so that they are explained
+    /// again. Called at the end of each request.
+    pub fn forget_failures(&self) {
+        self.failed().clear();
+    }
+
+    fn failed(&self) -> std::sync::MutexGuard<'_, HashSet<String>> {
+        self.failed.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Returns true if `sql` is a DML statement that `EXPLAIN` accepts.
+///
+/// Leading whitespace, comments and parentheses are skipped.
+#[must_use]
+pub fn is_explainable(sql: &str) -> bool {
+    matches!(
+        leading_keyword(sql).as_str(),
+        "SELECT" | "WITH" | "INSERT" | "UPDATE" | "DELETE" | "MERGE"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn config(entries: &[(&str, ParameterValue)]) -> Result<CostGuardConfig> {
+        CostGuardConfig::parse(&ParameterValue::Object(
+            entries
+                .iter()
+                .map(|(k, v)| ((*k).to_string(), v.clone()))
+                .collect::<BTreeMap<_, _>>(),
+        ))
+    }
+
+    #[test]
+    fn parses_config() {
+        let parsed = config(&[
+            ("max_cost", ParameterValue::Int(1000)),
+            ("max_rows", ParameterValue::Float(2.5)),
+            ("seq_scan_min_rows", ParameterValue::Int(10)),
+            ("action", "throw".into()),
+        ])
+        .unwrap();
+        assert_eq!(
+            parsed,
+            CostGuardConfig {
+                max_cost: Some(1000.0),
+                max_rows: Some(2.5),
+                seq_scan_min_rows: Some(10),
+                action: CostGuardAction::Throw,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_config() {
+        assert!(config(&[]).is_err());
+        assert!(config(&[("max_cost", ParameterValue::Int(-1))]).is_err());
+        assert!(config(&[("max_cost", "high".into())]).is_err());
+        assert!(
+            config(&[
+                ("max_cost", ParameterValue::Int(1)),
+                ("action", "ignore".into())
+            ])
+            .is_err()
+        );
+        assert!(config(&[("max_time", ParameterValue::Int(1))]).is_err());
+        assert!(CostGuardConfig::parse(&ParameterValue::Bool(true)).is_err());
+    }
+
+    #[test]
+    fn reports_violations() {
+        let guard = CostGuardConfig {
+            max_cost: Some(100.0),
+            max_rows: Some(10.0),
+            seq_scan_min_rows: Some(1000),
+            action: CostGuardAction::Warn,
+        };
+        let summary = PlanSummary {
+            total_cost: Some(250.0),
+            estimated_rows: Some(5.0),
+            seq_scan_tables: vec!["big".to_string(), "small".to_string()],
+        };
+        let violations = guard.violations(&summary, |table| match table {
+            "big" => Some(5000),
+            _ => Some(3),
+        });
+        assert_eq!(violations.len(), 2);
+        assert!(violations[0].contains("max_cost"));
+        assert!(violations[1].contains("`big`"));
+    }
+
+    #[test]
+    fn zero_seq_scan_threshold_flags_every_scan() {
+        let guard = CostGuardConfig {
+            seq_scan_min_rows: Some(0),
+            ..CostGuardConfig::default()
+        };
+        let summary = PlanSummary {
+            seq_scan_tables: vec!["t".to_string()],
+            ..PlanSummary::default()
+        };
+        let violations = guard.violations(&summary, |_| panic!("size lookup not needed"));
+        assert_eq!(violations, vec!["sequential scan on `t`".to_string()]);
+    }
+
+    #[test]
+    fn caches_verdict_per_statement() {
+        let guard = CostGuard::new(
+            CostGuardConfig {
+                max_cost: Some(1.0),
+                action: CostGuardAction::Throw,
+                ..CostGuardConfig::default()
+            },
+            1,
+            16,
+        );
+        let mut explained = 0;
+        for _ in 0..3 {
+            let result = guard.check("SELECT * FROM t", || {
+                explained += 1;
+                Some(vec!["too expensive".to_string()])
+            });
+            assert!(matches!(result, Err(SqlxError::CostExceeded { .. })));
+        }
+        assert_eq!(explained, 1);
+
+        assert!(
+            guard
+                .check("CREATE TABLE t (id INT)", || unreachable!())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn remembers_failed_explains_until_forgotten() {
+        let guard = CostGuard::new(
+            CostGuardConfig {
+                max_cost: Some(1.0),
+                action: CostGuardAction::Throw,
+                ..CostGuardConfig::default()
+            },
+            1,
+            16,
+        );
+        assert!(
+            guard
+                .check("SELECT * FROM tmp", || None)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            guard
+                .check("SELECT * FROM tmp", || unreachable!())
+                .unwrap()
+                .is_empty()
+        );
+
+        guard.forget_failures();
+        let result = guard.check("SELECT * FROM tmp", || {
+            Some(vec!["too expensive".to_string()])
+        });
+        assert!(matches!(result, Err(SqlxError::CostExceeded { .. })));
+    }
+
+    #[test]
+    fn detects_explainable_statements() {
+        assert!(is_explainable("  /* c */ select 1"));
+        assert!(is_explainable("(SELECT 1) UNION (SELECT 2)"));
+        assert!(is_explainable("DELETE FROM t"));
+        assert!(!is_explainable("CREATE TABLE t (id INT)"));
+        assert!(!is_explainable("SET search_path TO app"));
+    }
+}
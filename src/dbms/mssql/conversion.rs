@@ -1,5 +1,6 @@
 use crate::conversion::Conversion;
 use crate::error::Error as SqlxError;
+use crate::time_zone::{TimeZone, render_instant};
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
 use ext_php_rs::binary::Binary;
 use ext_php_rs::convert::IntoZval;
@@ -16,6 +17,7 @@ impl Conversion for MssqlRow {
         &self,
         column: &MssqlColumn,
         _associative_arrays: bool,
+        time_zone: Option<TimeZone>,
     ) -> crate::error::Result<Zval> {
         fn try_cast_into_zval<'r, T>(
             row: &'r MssqlRow,
@@ -80,7 +82,7 @@ impl Conversion for MssqlRow {
 
             "DATETIMEOFFSET" => {
                 match self.try_get::<DateTime<Utc>, _>(column_ordinal) {
-                    Ok(value) => value.to_string().into_zval(false)
+                    Ok(value) => render_instant(&value, time_zone).into_zval(false)
                         .map_err(|err| SqlxError::Conversion { message: format!("{err:?}") })?,
                     Err(ColumnDecode { source, .. }) if source.is::<UnexpectedNullError>() => Zval::null(),
                     Err(err) => return Err(SqlxError::Conversion { message: format!("{err:?}") }),
@@ -102,6 +104,7 @@ impl Conversion for MssqlRow {
     fn column_value_into_array_key<'a, MssqlColumn: Column, Mssql>(
         &self,
         column: &MssqlColumn,
+        time_zone: Option<TimeZone>,
     ) -> crate::error::Result<ArrayKey<'a>> {
         let column_ordinal = column.name();
         Ok(match column.type_info().name() {
@@ -126,7 +129,10 @@ impl Conversion for MssqlRow {
                 ArrayKey::String(self.try_get::<NaiveDateTime, _>(column_ordinal)?.to_string())
             }
             "DATETIMEOFFSET" => {
-                ArrayKey::String(self.try_get::<DateTime<Utc>, _>(column_ordinal)?.to_string())
+                ArrayKey::String(render_instant(
+                    &self.try_get::<DateTime<Utc>, _>(column_ordinal)?,
+                    time_zone,
+                ))
             }
 
             other => {
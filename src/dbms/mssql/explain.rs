@@ -0,0 +1,107 @@
+//! MSSQL XML showplan support.
+
+use super::inner::MssqlDriverInner;
+use crate::error::Error as SqlxError;
+use crate::explain::{ExplainOptions, Explained, summarize_mssql};
+use crate::param_value::ParameterValue;
+use crate::param_value::utils::bind_values;
+use sqlx_oldapi::pool::PoolConnection;
+use sqlx_oldapi::{Mssql, MssqlConnection, Row};
+
+/// A pooled connection with showplan enabled on its session.
+///
+/// Dropped before [`Self::reset`] ran to the end, whether on an error or because
+/// a timeout dropped the future, the connection is detached from the pool and
+/// closed, so that no other query sees the showplan setting or an open
+/// transaction.
+struct ShowplanSession {
+    conn: Option<PoolConnection<Mssql>>,
+    reset: bool,
+}
+
+impl ShowplanSession {
+    fn conn(&mut self) -> &mut MssqlConnection {
+        self.conn
+            .as_mut()
+            .expect("connection is only taken on drop")
+    }
+
+    /// Runs the statements that undo the session changes, and lets the
+    /// connection go back to the pool if they all succeed.
+    async fn reset(&mut self, statements: &[&str]) -> Result<(), sqlx_oldapi::Error> {
+        for statement in statements {
+            sqlx_oldapi::query(statement).execute(self.conn()).await?;
+        }
+        self.reset = true;
+        Ok(())
+    }
+}
+
+impl Drop for ShowplanSession {
+    fn drop(&mut self) {
+        if !self.reset
+            && let Some(conn) = self.conn.take()
+        {
+            drop(conn.detach());
+        }
+    }
+}
+
+impl MssqlDriverInner {
+    /// Returns the XML showplan for an already-rendered statement.
+    ///
+    /// Uses `SET SHOWPLAN_XML ON` (estimated plan, statement not executed), or
+    /// `SET STATISTICS XML ON` with `analyze` (actual plan; the statement is
+    /// executed inside a transaction that is rolled back). `buffers` is ignored.
+    ///
+    /// Both settings are session-scoped, so the statement runs on a dedicated
+    /// pooled connection, outside the driver's transaction or pinned connection.
+    /// The connection goes back to the pool only once the settings are turned off
+    /// and the transaction rolled back; otherwise it is closed.
+    pub(crate) fn explain_rendered(
+        &self,
+        sql: &str,
+        values: &[ParameterValue],
+        options: ExplainOptions,
+    ) -> crate::error::Result<Explained> {
+        let (enable, reset): (&str, &[&str]) = if options.analyze {
+            (
+                "SET STATISTICS XML ON",
+                &["SET STATISTICS XML OFF", "ROLLBACK TRANSACTION"],
+            )
+        } else {
+            ("SET SHOWPLAN_XML ON", &["SET SHOWPLAN_XML OFF"])
+        };
+        let query = bind_values(sqlx_oldapi::query(sql), values)?;
+
+        let pool = &self.pool;
+        let rows = self
+            .run_query(async move {
+                let mut session = ShowplanSession {
+                    conn: Some(pool.acquire().await?),
+                    reset: false,
+                };
+                if options.analyze {
+                    sqlx_oldapi::query("BEGIN TRANSACTION")
+                        .execute(session.conn())
+                        .await?;
+                }
+                sqlx_oldapi::query(enable).execute(session.conn()).await?;
+                let rows = query.fetch_all(session.conn()).await;
+                session.reset(reset).await?;
+                rows
+            })
+            .and_then(|inner| inner.map_err(|err| SqlxError::query_with_source(sql, err)))?;
+
+        // The plan is the last result set: a single row with the XML in column 0.
+        let xml: String = rows
+            .last()
+            .ok_or_else(|| SqlxError::Other("The server returned no execution plan".to_string()))?
+            .try_get(0)
+            .map_err(|err| SqlxError::query_with_source(sql, err))?;
+        Ok(Explained {
+            summary: summarize_mssql(&xml),
+            plan: serde_json::Value::String(xml),
+        })
+    }
+}
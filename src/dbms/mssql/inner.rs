@@ -1,11 +1,13 @@
 #![allow(clippy::needless_pass_by_value)]
-use crate::ast::{IdentifierQuoteStyle, UpsertStyle};
+use crate::ast::{BinaryLiteralStyle, IdentifierQuoteStyle, UpsertStyle};
 use crate::php_sqlx_impl_driver_inner;
+use crate::time_zone::TimeZone;
 
 /// SQL query to set the application name in session context.
 /// Readable via `SELECT SESSION_CONTEXT(N'application_name')`.
 /// Requires SQL Server 2016 or later.
-pub const SET_APPLICATION_NAME_QUERY: &str = "EXEC sp_set_session_context @key = N'application_name', @value = $name";
+pub const SET_APPLICATION_NAME_QUERY: &str =
+    "EXEC sp_set_session_context @key = N'application_name', @value = $name";
 
 /// SQL query to describe table columns using `information_schema`.
 pub const DESCRIBE_TABLE_QUERY: &str = r"
@@ -21,6 +23,61 @@ WHERE table_name = $table
 ORDER BY ordinal_position
 ";
 
+/// SQL query estimating a table's row count, for the cost guard.
+/// Counts the rows of the heap or clustered index.
+pub const TABLE_ROWS_QUERY: &str = r"
+SELECT CAST(SUM(rows) AS BIGINT)
+FROM sys.partitions
+WHERE object_id = OBJECT_ID($table)
+  AND index_id IN (0, 1)
+";
+
+/// SQL Server has no session-level statement timeout, so the request deadline
+/// (`setDeadline()`) is only enforced client-side.
+pub const SET_STATEMENT_TIMEOUT_QUERY: Option<&str> = None;
+pub const RESET_STATEMENT_TIMEOUT_QUERY: Option<&str> = None;
+
+/// SQL Server resets a session through a flag of the TDS protocol, which the
+/// driver doesn't expose (`sp_reset_connection` can't be called from a batch), so
+/// a connection a request left pinned or inside a transaction is closed instead.
+pub const RESET_SESSION_QUERY: Option<&str> = None;
+
+/// Marks a connection to be closed when it goes back to the pool, and returns a
+/// row if the mark is set.
+pub const DISCARD_MARK_QUERY: &str = "EXEC sp_set_session_context N'sqlx_discard', 1";
+pub const DISCARD_CHECK_QUERY: &str =
+    "SELECT 1 WHERE SESSION_CONTEXT(N'sqlx_discard') IS NOT NULL";
+
+/// The driver prepares no statements ahead: `PreparedQuery` only reuses the
+/// parsed query.
+pub const STATEMENT_CACHE: bool = false;
+
+/// `openBlob()` expression returning the size in bytes of `{column}`.
+pub const BLOB_LENGTH_EXPR: &str = "DATALENGTH({column})";
+
+/// `openBlob()` expression returning one chunk of `{column}`, from the 1-based
+/// `$blob_offset` for `$blob_length` bytes.
+pub const BLOB_CHUNK_EXPR: &str = "SUBSTRING({column}, $blob_offset, $blob_length)";
+
+/// Connections have no statement cache.
+#[allow(clippy::unnecessary_wraps)]
+pub fn statement_cache_size(_conn: &sqlx_oldapi::MssqlConnection) -> Option<usize> {
+    None
+}
+
+/// Connections have no statement cache.
+#[allow(clippy::unused_async)]
+pub async fn clear_statement_cache(
+    _conn: &mut sqlx_oldapi::MssqlConnection,
+) -> Result<(), sqlx_oldapi::Error> {
+    Ok(())
+}
+
+/// SQL Server has no session time zone.
+pub fn set_time_zone_query(_time_zone: &TimeZone) -> Option<String> {
+    None
+}
+
 pub const SETTINGS: Settings = Settings {
     collapsible_in_enabled: true,
     escaping_double_single_quotes: true,
@@ -37,6 +94,10 @@ pub const SETTINGS: Settings = Settings {
     escape_backslash: false,
     upsert_style: UpsertStyle::Unsupported,
     identifier_quote_style: IdentifierQuoteStyle::Bracket,
+    binary_literal_style: BinaryLiteralStyle::HexNumber,
+    redact_secrets: false,
+    datetime_offsets: true,
+    time_zone: None,
 };
 
 php_sqlx_impl_driver_inner!(MssqlDriverInner, Mssql);
@@ -57,4 +118,28 @@ impl MssqlDriverInner {
                 .to_string(),
         ))
     }
+
+    /// Large objects are a `PostgreSQL` feature.
+    ///
+    /// Store the bytes in a `VARBINARY(MAX)` column and read it with `openBlob()`.
+    #[allow(clippy::unused_self)]
+    pub fn large_object(
+        self: &std::sync::Arc<Self>,
+        _oid: Option<i64>,
+    ) -> crate::error::Result<crate::blob::Blob> {
+        Err(crate::error::Error::Other(
+            "largeObject() is only supported on PostgreSQL; use openBlob() on MSSQL".to_string(),
+        ))
+    }
+
+    /// Only `PostgreSQL` copies compressed values before reading them; here
+    /// `openBlob()` reads the column with `SUBSTRING()` directly.
+    #[allow(clippy::unused_self, clippy::unnecessary_wraps)]
+    pub fn spooled_blob(
+        self: &std::sync::Arc<Self>,
+        _select: &dyn Fn(&str) -> String,
+        _key: &crate::param_value::ParamsMap,
+    ) -> crate::error::Result<Option<crate::blob::Blob>> {
+        Ok(None)
+    }
 }
@@ -1,5 +1,7 @@
 //! MSSQL query result iterator.
 
+use crate::dbms::mssql::inner::MssqlDriverInner;
+
 crate::php_sqlx_impl_query_result!(
     MssqlQueryResult,
     "Sqlx\\MssqlQueryResult",
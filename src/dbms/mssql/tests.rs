@@ -1,5 +1,5 @@
 use crate::ast::Ast;
-use crate::dbms::mssql::inner::{DESCRIBE_TABLE_QUERY, SETTINGS};
+use crate::dbms::mssql::inner::{DESCRIBE_TABLE_QUERY, SETTINGS, TABLE_ROWS_QUERY};
 use crate::param_value::ParamsMap;
 
 fn into_ast(sql: &str) -> Ast {
@@ -62,3 +62,17 @@ fn test_describe_table_query_with_schema() {
     assert_eq!(values[0], ParameterValue::String("users".into()));
     assert_eq!(values[1], ParameterValue::String("dbo".into()));
 }
+
+#[test]
+fn test_table_rows_query_renders() {
+    use crate::param_value::ParameterValue;
+
+    let ast = into_ast(TABLE_ROWS_QUERY);
+    let mut params = ParamsMap::new();
+    params.insert("table".into(), ParameterValue::String("users".into()));
+
+    let (sql, values) = ast.render(params, &SETTINGS).expect("Rendering failed");
+
+    assert!(sql.contains("@p1"), "Expected @p1 placeholder for table");
+    assert_eq!(values, vec![ParameterValue::String("users".into())]);
+}
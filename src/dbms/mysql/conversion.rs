@@ -5,6 +5,7 @@
 use crate::LazyRowJson;
 use crate::conversion::{Conversion, json_into_zval};
 use crate::error::Error as SqlxError;
+use crate::time_zone::{TimeZone, render_session_timestamp};
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
 use ext_php_rs::binary::Binary;
 use ext_php_rs::convert::IntoZval;
@@ -21,6 +22,7 @@ impl Conversion for MySqlRow {
         &self,
         column: &MySqlColumn,
         associative_arrays: bool,
+        time_zone: Option<TimeZone>,
     ) -> crate::error::Result<Zval> {
         fn try_cast_into_zval<'r, T>(row: &'r MySqlRow, column_ordinal: usize) -> crate::error::Result<Zval>
         where
@@ -75,7 +77,7 @@ impl Conversion for MySqlRow {
             }
             "TIMESTAMP" => {
                 match self.try_get::<DateTime<Utc>, _>(column_ordinal) {
-                    Ok(value) => value.to_string().into_zval(false)
+                    Ok(value) => render_session_timestamp(&value, time_zone).into_zval(false)
                         .map_err(|err| SqlxError::Conversion { message: format!("{err:?}") })?,
                     Err(ColumnDecode { source, .. }) if source.is::<UnexpectedNullError>() => Zval::null(),
                     Err(err) => return Err(SqlxError::Conversion { message: format!("{err:?}") }),
@@ -141,6 +143,7 @@ impl Conversion for MySqlRow {
     fn column_value_into_array_key<'a, MySqlColumn: Column, MySql>(
         &self,
         column: &MySqlColumn,
+        _time_zone: Option<TimeZone>,
     ) -> crate::error::Result<ArrayKey<'a>> {
         let column_name = column.name();
         Ok(match column.type_info().name() {
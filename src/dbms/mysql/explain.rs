@@ -0,0 +1,50 @@
+//! `MySQL` `EXPLAIN FORMAT=JSON` / `EXPLAIN ANALYZE` support.
+
+use super::inner::MySqlDriverInner;
+use crate::error::Error as SqlxError;
+use crate::explain::{ExplainOptions, Explained, summarize_mysql, summarize_mysql_tree};
+use crate::param_value::ParameterValue;
+use crate::param_value::utils::bind_values;
+use sqlx_oldapi::Row;
+
+impl MySqlDriverInner {
+    /// Runs `EXPLAIN FORMAT=JSON` for an already-rendered statement.
+    ///
+    /// `analyze` uses `EXPLAIN ANALYZE` (`MySQL` 8.0.18+), whose output is the
+    /// tree text format; the statement is then executed inside a transaction that
+    /// is rolled back. `buffers` is ignored. Runs on the driver's dedicated
+    /// connection, if any (see `fetch_explain`).
+    pub(crate) fn explain_rendered(
+        &self,
+        sql: &str,
+        values: &[ParameterValue],
+        options: ExplainOptions,
+    ) -> crate::error::Result<Explained> {
+        let statement = if options.analyze {
+            format!("EXPLAIN ANALYZE {sql}")
+        } else {
+            format!("EXPLAIN FORMAT=JSON {sql}")
+        };
+        let query = bind_values(sqlx_oldapi::query(&statement), values)?;
+
+        let row = self
+            .fetch_explain(query, options.analyze)
+            .and_then(|inner| inner.map_err(|err| SqlxError::query_with_source(&statement, err)))?;
+
+        let output: String = row
+            .try_get(0)
+            .map_err(|err| SqlxError::query_with_source(&statement, err))?;
+        if options.analyze {
+            return Ok(Explained {
+                summary: summarize_mysql_tree(&output),
+                plan: serde_json::Value::String(output),
+            });
+        }
+        let plan: serde_json::Value = serde_json::from_str(&output)
+            .map_err(|err| SqlxError::Other(format!("Cannot decode EXPLAIN output: {err}")))?;
+        Ok(Explained {
+            summary: summarize_mysql(&plan),
+            plan,
+        })
+    }
+}
@@ -1,6 +1,7 @@
 #![allow(clippy::needless_pass_by_value)]
-use crate::ast::{IdentifierQuoteStyle, UpsertStyle};
+use crate::ast::{BinaryLiteralStyle, IdentifierQuoteStyle, UpsertStyle};
 use crate::php_sqlx_impl_driver_inner;
+use crate::time_zone::TimeZone;
 
 /// SQL query to set the application name as a session variable.
 /// Can be queried via `SELECT @sqlx_application_name`.
@@ -22,6 +23,64 @@ WHERE table_name = $table
 ORDER BY ordinal_position
 ";
 
+/// SQL query estimating a table's row count, for the cost guard.
+/// `TABLE_ROWS` is an estimate for `InnoDB` tables.
+pub const TABLE_ROWS_QUERY: &str = r"
+SELECT CAST(table_rows AS SIGNED)
+FROM information_schema.tables
+WHERE table_schema = DATABASE()
+  AND table_name = $table
+";
+
+/// Statements applying the request deadline (`setDeadline()`) as a server-side
+/// timeout when a connection is handed out, and restoring the server's setting
+/// when it is returned to the pool. `{ms}` is replaced with the remaining
+/// milliseconds. `max_execution_time` only applies to `SELECT` statements.
+pub const SET_STATEMENT_TIMEOUT_QUERY: Option<&str> = Some("SET SESSION max_execution_time = {ms}");
+pub const RESET_STATEMENT_TIMEOUT_QUERY: Option<&str> =
+    Some("SET SESSION max_execution_time = DEFAULT");
+
+/// `MySQL` resets a session with the `COM_RESET_CONNECTION` protocol command,
+/// which the driver doesn't expose, so a connection a request left pinned or
+/// inside a transaction is closed instead.
+pub const RESET_SESSION_QUERY: Option<&str> = None;
+
+/// Marks a connection to be closed when it goes back to the pool, and returns a
+/// row if the mark is set.
+pub const DISCARD_MARK_QUERY: &str = "SET @sqlx_discard = 1";
+pub const DISCARD_CHECK_QUERY: &str = "SELECT 1 FROM DUAL WHERE @sqlx_discard IS NOT NULL";
+
+/// Connections keep the statements they prepare in an LRU cache, sized by
+/// `OPT_STATEMENT_CACHE_CAPACITY`.
+pub const STATEMENT_CACHE: bool = true;
+
+/// `openBlob()` expression returning the size in bytes of `{column}`.
+pub const BLOB_LENGTH_EXPR: &str = "OCTET_LENGTH({column})";
+
+/// `openBlob()` expression returning one chunk of `{column}`, from the 1-based
+/// `$blob_offset` for `$blob_length` bytes.
+pub const BLOB_CHUNK_EXPR: &str = "SUBSTRING({column}, $blob_offset, $blob_length)";
+
+/// Returns the number of statements prepared on a connection.
+#[allow(clippy::unnecessary_wraps)]
+pub fn statement_cache_size(conn: &sqlx_oldapi::MySqlConnection) -> Option<usize> {
+    Some(sqlx_oldapi::Connection::cached_statements_size(conn))
+}
+
+/// Deallocates the statements prepared on a connection.
+pub async fn clear_statement_cache(
+    conn: &mut sqlx_oldapi::MySqlConnection,
+) -> Result<(), sqlx_oldapi::Error> {
+    sqlx_oldapi::Connection::clear_cached_statements(conn).await
+}
+
+/// Sets the session time zone (`OPT_TIMEZONE`). Named time zones require the
+/// server's time zone tables to be loaded.
+#[allow(clippy::unnecessary_wraps)]
+pub fn set_time_zone_query(time_zone: &TimeZone) -> Option<String> {
+    Some(format!("SET time_zone = '{}'", time_zone.name()))
+}
+
 pub const SETTINGS: Settings = Settings {
     collapsible_in_enabled: true,
     escaping_double_single_quotes: true,
@@ -38,6 +97,10 @@ pub const SETTINGS: Settings = Settings {
     escape_backslash: true,      // MySQL interprets \ as escape
     upsert_style: UpsertStyle::OnDuplicateKey,
     identifier_quote_style: IdentifierQuoteStyle::Backtick,
+    binary_literal_style: BinaryLiteralStyle::XString,
+    redact_secrets: false,
+    datetime_offsets: false,
+    time_zone: None,
 };
 
 php_sqlx_impl_driver_inner!(MySqlDriverInner, MySql);
@@ -58,4 +121,28 @@ impl MySqlDriverInner {
                 .to_string(),
         ))
     }
+
+    /// Large objects are a `PostgreSQL` feature.
+    ///
+    /// Store the bytes in a `LONGBLOB` column and read it with `openBlob()`.
+    #[allow(clippy::unused_self)]
+    pub fn large_object(
+        self: &std::sync::Arc<Self>,
+        _oid: Option<i64>,
+    ) -> crate::error::Result<crate::blob::Blob> {
+        Err(crate::error::Error::Other(
+            "largeObject() is only supported on PostgreSQL; use openBlob() on MySQL".to_string(),
+        ))
+    }
+
+    /// Only `PostgreSQL` copies compressed values before reading them; here
+    /// `openBlob()` reads the column with `SUBSTRING()` directly.
+    #[allow(clippy::unused_self, clippy::unnecessary_wraps)]
+    pub fn spooled_blob(
+        self: &std::sync::Arc<Self>,
+        _select: &dyn Fn(&str) -> String,
+        _key: &crate::param_value::ParamsMap,
+    ) -> crate::error::Result<Option<crate::blob::Blob>> {
+        Ok(None)
+    }
 }
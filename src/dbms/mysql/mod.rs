@@ -1,3 +1,4 @@
+mod explain;
 #[cfg(test)]
 mod tests;
 
@@ -1,5 +1,7 @@
 //! `MySQL` query result iterator.
 
+use crate::dbms::mysql::inner::MySqlDriverInner;
+
 crate::php_sqlx_impl_query_result!(
     MySqlQueryResult,
     "Sqlx\\MySqlQueryResult",
@@ -6,6 +6,7 @@ use crate::conversion::{Conversion, json_into_zval};
 use crate::error::Error as SqlxError;
 #[cfg(feature = "lazy-row")]
 use crate::lazy_row::LazyRowJson;
+use crate::time_zone::{TimeZone, render_instant};
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
 use ext_php_rs::binary::Binary;
 use ext_php_rs::convert::IntoZval;
@@ -23,6 +24,7 @@ impl Conversion for PgRow {
         &self,
         column: &PgColumn,
         associative_arrays: bool,
+        time_zone: Option<TimeZone>,
     ) -> crate::error::Result<Zval> {
         fn try_cast_into_zval<'r, T>(row: &'r PgRow, column_ordinal: usize) -> crate::error::Result<Zval>
         where
@@ -156,7 +158,7 @@ impl Conversion for PgRow {
             }
             "TIMESTAMPTZ" => {
                 match self.try_get::<DateTime<Utc>, _>(column_ordinal) {
-                    Ok(value) => value.to_string().into_zval(false)
+                    Ok(value) => render_instant(&value, time_zone).into_zval(false)
                         .map_err(|err| SqlxError::Conversion { message: format!("{err:?}") })?,
                     Err(ColumnDecode { source, .. }) if source.is::<UnexpectedNullError>() => Zval::null(),
                     Err(err) => return Err(SqlxError::Conversion { message: format!("{err:?}") }),
@@ -227,7 +229,7 @@ impl Conversion for PgRow {
             }
             "_TIMESTAMPTZ" => {
                 match self.try_get::<Vec<DateTime<Utc>>, _>(column_ordinal) {
-                    Ok(values) => values.iter().map(ToString::to_string).collect::<Vec<_>>()
+                    Ok(values) => values.iter().map(|value| render_instant(value, time_zone)).collect::<Vec<_>>()
                         .into_zval(false)
                         .map_err(|err| SqlxError::Conversion { message: format!("{err:?}") })?,
                     Err(ColumnDecode { source, .. }) if source.is::<UnexpectedNullError>() => Zval::null(),
@@ -271,6 +273,7 @@ impl Conversion for PgRow {
     fn column_value_into_array_key<'a, PgColumn: Column, Postgres>(
         &self,
         column: &PgColumn,
+        _time_zone: Option<TimeZone>,
     ) -> crate::error::Result<ArrayKey<'a>> {
         let column_ordinal = column.ordinal();
         Ok(match column.type_info().name() {
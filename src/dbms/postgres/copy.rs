@@ -18,6 +18,8 @@ use super::inner::PgDriverInner;
 use crate::RUNTIME;
 use crate::error::Error as SqlxError;
 use crate::param_value::ParameterValue;
+use crate::query_hook::{Instrumentation, QueryKind};
+use crate::time_zone::OFFSET_DATETIME_FORMAT;
 use ext_php_rs::convert::FromZval;
 use ext_php_rs::types::Zval;
 use std::fmt::Write;
@@ -54,8 +56,17 @@ impl PgDriverInner {
         }
 
         let statement = format!("COPY {table} ({}) FROM STDIN", columns.join(", "));
+        let (statement, parameters, meta) = self.before_query(&statement, None, QueryKind::Copy)?;
+        if parameters.is_some() {
+            return Err(SqlxError::Other(
+                "copyIn: the onBeforeQuery hook can't return `params` for a COPY statement"
+                    .to_string(),
+            ));
+        }
+        let statement = self.query_tags.apply(statement.into_owned());
+        let timer = self.start_timer(&statement, None, meta, Instrumentation::default());
 
-        RUNTIME.block_on(async {
+        let result = RUNTIME.block_on(async {
             let mut copy = self
                 .pool
                 .copy_in_raw(&statement)
@@ -80,7 +91,15 @@ impl PgDriverInner {
             copy.finish()
                 .await
                 .map_err(|err| SqlxError::query_with_source(&statement, err))
-        })
+        });
+
+        if let Some(t) = timer {
+            match &result {
+                Ok(rows) => t.finish(Some(*rows), None),
+                Err(err) => t.finish(None, Some(&err.to_string())),
+            }
+        }
+        result
     }
 }
 
@@ -108,7 +127,8 @@ fn encode_row(row: &Zval, columns: &[String], out: &mut String) -> crate::error:
 /// Encodes a single cell `Zval` into the COPY text format.
 ///
 /// Scalars take an allocation-free fast path (`str()` borrows); anything else
-/// (JSON arrays/objects, `DateTime`, …) falls back to [`ParameterValue`] and the
+/// (JSON arrays/objects, `DateTime`, `Binary`, strings that are not valid UTF-8,
+/// …) falls back to [`ParameterValue`] and the
 /// shared [`encode_field`] encoder.
 fn encode_value(value: &Zval, out: &mut String) -> crate::error::Result<()> {
     if value.is_null() {
@@ -144,9 +164,18 @@ fn encode_field(value: &ParameterValue, out: &mut String) -> crate::error::Resul
             let _ = write!(out, "{f}");
         }
         ParameterValue::String(s) => escape_into(s, out),
+        ParameterValue::Secret(secret) => encode_field(&secret.value, out)?,
         ParameterValue::DateTime(dt) => {
             escape_into(&dt.format("%Y-%m-%d %H:%M:%S%.f").to_string(), out);
         }
+        ParameterValue::DateTimeTz(dt) => {
+            escape_into(&dt.format(OFFSET_DATETIME_FORMAT).to_string(), out);
+        }
+        // Bytes use the bytea hex format, its backslash escaped for COPY.
+        ParameterValue::Binary(bytes) => {
+            out.push_str("\\\\x");
+            out.push_str(&crate::binary::to_hex(bytes));
+        }
         // JSON-ish values are serialized to JSON text (for json/jsonb columns).
         ParameterValue::Json(_) | ParameterValue::Object(_) | ParameterValue::Array(_) => {
             let json = serde_json::to_string(value).map_err(|err| SqlxError::Conversion {
@@ -213,6 +242,14 @@ mod tests {
         assert_eq!(field(&ParameterValue::String("\\N".to_string())), "\\\\N");
     }
 
+    #[test]
+    fn binary_uses_bytea_hex_format() {
+        assert_eq!(
+            field(&ParameterValue::Binary(vec![0x00, 0x09, 0xff])),
+            "\\\\x0009ff"
+        );
+    }
+
     #[test]
     fn json_object_is_serialized() {
         let mut obj = BTreeMap::new();
@@ -0,0 +1,47 @@
+//! `PostgreSQL` `EXPLAIN (FORMAT JSON)` support.
+
+use super::inner::PgDriverInner;
+use crate::error::Error as SqlxError;
+use crate::explain::{ExplainOptions, Explained, summarize_postgres};
+use crate::param_value::ParameterValue;
+use crate::param_value::utils::bind_values;
+use sqlx_oldapi::Row;
+
+impl PgDriverInner {
+    /// Runs `EXPLAIN (FORMAT JSON)` for an already-rendered statement.
+    ///
+    /// `analyze` and `buffers` map to the `ANALYZE` and `BUFFERS` options. With
+    /// `analyze`, the statement is executed inside a transaction that is rolled
+    /// back, whatever it looks like: a `SELECT` may call a function that writes.
+    /// Runs on the driver's dedicated connection, if any (see
+    /// `fetch_explain`).
+    pub(crate) fn explain_rendered(
+        &self,
+        sql: &str,
+        values: &[ParameterValue],
+        options: ExplainOptions,
+    ) -> crate::error::Result<Explained> {
+        let mut flags = Vec::with_capacity(3);
+        if options.analyze {
+            flags.push("ANALYZE");
+        }
+        if options.buffers {
+            flags.push("BUFFERS");
+        }
+        flags.push("FORMAT JSON");
+        let statement = format!("EXPLAIN ({}) {sql}", flags.join(", "));
+        let query = bind_values(sqlx_oldapi::query(&statement), values)?;
+
+        let row = self
+            .fetch_explain(query, options.analyze)
+            .and_then(|inner| inner.map_err(|err| SqlxError::query_with_source(&statement, err)))?;
+
+        let plan: serde_json::Value = row
+            .try_get(0)
+            .map_err(|err| SqlxError::query_with_source(&statement, err))?;
+        Ok(Explained {
+            summary: summarize_postgres(&plan),
+            plan,
+        })
+    }
+}
@@ -1,6 +1,7 @@
 #![allow(clippy::needless_pass_by_value)]
-use crate::ast::{IdentifierQuoteStyle, UpsertStyle};
+use crate::ast::{BinaryLiteralStyle, IdentifierQuoteStyle, UpsertStyle};
 use crate::php_sqlx_impl_driver_inner;
+use crate::time_zone::TimeZone;
 
 /// SQL query to set the application name for connection identification.
 /// Visible in `pg_stat_activity.application_name`.
@@ -21,6 +22,73 @@ WHERE table_name = $table
 ORDER BY ordinal_position
 ";
 
+/// SQL query estimating a table's row count, for the cost guard.
+/// `reltuples` is only maintained by `ANALYZE`/`VACUUM`, so the live-tuple
+/// counter from the statistics collector is used as a fallback.
+pub const TABLE_ROWS_QUERY: &str = r"
+SELECT GREATEST(c.reltuples::bigint, COALESCE(s.n_live_tup, 0))
+FROM pg_class c
+LEFT JOIN pg_stat_user_tables s ON s.relid = c.oid
+WHERE c.oid = to_regclass($table)
+";
+
+/// Statements applying the request deadline (`setDeadline()`) as a server-side
+/// statement timeout when a connection is handed out, and restoring the
+/// server's setting when it is returned to the pool. `{ms}` is replaced with the
+/// remaining milliseconds.
+pub const SET_STATEMENT_TIMEOUT_QUERY: Option<&str> = Some("SET statement_timeout = {ms}");
+pub const RESET_STATEMENT_TIMEOUT_QUERY: Option<&str> = Some("RESET statement_timeout");
+
+/// Resets the session state of a connection a request left pinned or inside a
+/// transaction. `DISCARD ALL` also drops the prepared statements, so the
+/// connection's statement cache is cleared first. If it fails, the connection is
+/// closed instead.
+pub const RESET_SESSION_QUERY: Option<&str> = Some("DISCARD ALL");
+
+/// Marks a connection to be closed when it goes back to the pool, and returns a
+/// row if the mark is set.
+pub const DISCARD_MARK_QUERY: &str = "SELECT set_config('sqlx.discard', 'on', false)";
+pub const DISCARD_CHECK_QUERY: &str =
+    "SELECT 1 WHERE current_setting('sqlx.discard', true) = 'on'";
+
+/// Connections keep the statements they prepare in an LRU cache, sized by
+/// `OPT_STATEMENT_CACHE_CAPACITY`.
+pub const STATEMENT_CACHE: bool = true;
+
+/// `openBlob()` expression returning the size in bytes of `{column}`.
+pub const BLOB_LENGTH_EXPR: &str = "octet_length({column})";
+
+/// `openBlob()` expression returning one chunk of `{column}`, from the 1-based
+/// `$blob_offset` for `$blob_length` bytes.
+pub const BLOB_CHUNK_EXPR: &str =
+    "substring({column} FROM CAST($blob_offset AS integer) FOR CAST($blob_length AS integer))";
+
+/// Returns the number of statements prepared on a connection.
+#[allow(clippy::unnecessary_wraps)]
+pub fn statement_cache_size(conn: &sqlx_oldapi::PgConnection) -> Option<usize> {
+    Some(sqlx_oldapi::Connection::cached_statements_size(conn))
+}
+
+/// Deallocates the statements prepared on a connection.
+pub async fn clear_statement_cache(
+    conn: &mut sqlx_oldapi::PgConnection,
+) -> Result<(), sqlx_oldapi::Error> {
+    sqlx_oldapi::Connection::clear_cached_statements(conn).await
+}
+
+/// Sets the session time zone (`OPT_TIMEZONE`). Offsets are given as an interval:
+/// a bare `'+02:00'` is read as a POSIX time zone, with east and west swapped.
+#[allow(clippy::unnecessary_wraps)]
+pub fn set_time_zone_query(time_zone: &TimeZone) -> Option<String> {
+    Some(match time_zone {
+        TimeZone::Fixed(_) => format!(
+            "SET TIME ZONE INTERVAL '{}' HOUR TO MINUTE",
+            time_zone.name()
+        ),
+        TimeZone::Named(_) => format!("SET TIME ZONE '{}'", time_zone.name()),
+    })
+}
+
 pub const SETTINGS: Settings = Settings {
     collapsible_in_enabled: true,
     escaping_double_single_quotes: true,
@@ -37,5 +105,9 @@ pub const SETTINGS: Settings = Settings {
     escape_backslash: false,    // unnecessary
     upsert_style: UpsertStyle::OnConflict,
     identifier_quote_style: IdentifierQuoteStyle::DoubleQuote,
+    binary_literal_style: BinaryLiteralStyle::Bytea,
+    redact_secrets: false,
+    datetime_offsets: true,
+    time_zone: None,
 };
 php_sqlx_impl_driver_inner!(PgDriverInner, Postgres);
@@ -0,0 +1,185 @@
+//! `PostgreSQL` large objects as [`Blob`]s.
+//!
+//! A large object is stored in `pg_largeobject` and referenced by its OID, so it
+//! can be read and written at any offset without rewriting the whole value. Each
+//! chunk goes through the server-side `lo_get()` and `lo_put()` functions as a
+//! single statement, so no large-object descriptor outlives a statement and no
+//! transaction is needed.
+//!
+//! `openBlob()` also reads compressed `bytea` values through a large object:
+//! `SUBSTRING()` decompresses the whole value for every chunk, so the value is
+//! decompressed once by `lo_from_bytea()` and the copy is unlinked when the blob
+//! is closed or freed.
+
+use super::inner::PgDriverInner;
+use crate::blob::{Blob, BlobQuery, BlobSource, read_only_column, value_to_u64};
+use crate::error::Error as SqlxError;
+use crate::param_value::{ParameterValue, ParamsMap};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Creates an empty large object and returns its OID.
+const CREATE_QUERY: &str = "SELECT CAST(lo_create(0) AS bigint)";
+
+/// Returns the size of a large object by seeking to its end (`SEEK_END`) through
+/// a read-only (`INV_READ`) descriptor.
+const SIZE_QUERY: &str = "SELECT lo_lseek64(lo_open(CAST($oid AS oid), 262144), 0, 2)";
+
+const READ_QUERY: &str = "SELECT lo_get(CAST($oid AS oid), $offset, CAST($length AS integer))";
+
+const WRITE_QUERY: &str = "SELECT lo_put(CAST($oid AS oid), $offset, $bytes)";
+
+const UNLINK_QUERY: &str = "SELECT lo_unlink(CAST($oid AS oid))";
+
+/// `openBlob()` expression copying `{column}` into a new large object and
+/// returning its OID if the value is stored compressed, `NULL` otherwise.
+const SPOOL_EXPR: &str = "CASE WHEN pg_column_size({column}) < octet_length({column}) \
+     THEN CAST(lo_from_bytea(0, {column}) AS bigint) END";
+
+/// A large object, identified by its OID.
+pub struct LargeObject {
+    driver: Arc<PgDriverInner>,
+    oid: i64,
+}
+
+impl LargeObject {
+    fn parameters(&self, offset: u64) -> crate::error::Result<ParamsMap> {
+        let offset = i64::try_from(offset).map_err(|_| SqlxError::InvalidParameter {
+            name: "offset".to_string(),
+            expected: "an offset within the large object".to_string(),
+        })?;
+        Ok(ParamsMap::from([
+            ("oid".to_string(), ParameterValue::Int(self.oid)),
+            ("offset".to_string(), ParameterValue::Int(offset)),
+        ]))
+    }
+}
+
+impl BlobSource for LargeObject {
+    fn size(&self) -> crate::error::Result<u64> {
+        let parameters = ParamsMap::from([("oid".to_string(), ParameterValue::Int(self.oid))]);
+        self.driver
+            .blob_value(SIZE_QUERY, parameters)?
+            .as_ref()
+            .map_or(Ok(0), value_to_u64)
+    }
+
+    fn read_at(&self, offset: u64, length: usize) -> crate::error::Result<Vec<u8>> {
+        let mut parameters = self.parameters(offset)?;
+        parameters.insert(
+            "length".to_string(),
+            ParameterValue::Int(i64::try_from(length).unwrap_or(i64::from(i32::MAX))),
+        );
+        self.driver
+            .blob_value(READ_QUERY, parameters)?
+            .and_then(|chunk| chunk.binary::<u8>())
+            .ok_or_else(|| SqlxError::Conversion {
+                message: "lo_get() did not return bytes".to_string(),
+            })
+    }
+
+    fn write_at(&self, offset: u64, bytes: &[u8]) -> crate::error::Result<()> {
+        let mut parameters = self.parameters(offset)?;
+        parameters.insert("bytes".to_string(), ParameterValue::Binary(bytes.to_vec()));
+        self.driver.blob_execute(WRITE_QUERY, parameters)
+    }
+
+    fn oid(&self) -> Option<i64> {
+        Some(self.oid)
+    }
+}
+
+/// A compressed column copied into a large object by `openBlob()`. It is
+/// read-only, and the large object is unlinked when the blob is closed or freed.
+struct SpooledColumn {
+    object: LargeObject,
+    unlinked: AtomicBool,
+}
+
+impl BlobSource for SpooledColumn {
+    fn size(&self) -> crate::error::Result<u64> {
+        self.object.size()
+    }
+
+    fn read_at(&self, offset: u64, length: usize) -> crate::error::Result<Vec<u8>> {
+        self.object.read_at(offset, length)
+    }
+
+    fn write_at(&self, _offset: u64, _bytes: &[u8]) -> crate::error::Result<()> {
+        Err(read_only_column())
+    }
+
+    fn release(&self) {
+        if self.unlinked.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let parameters =
+            ParamsMap::from([("oid".to_string(), ParameterValue::Int(self.object.oid))]);
+        // The copy is gone anyway if the transaction it was made in rolled back.
+        let _ = self.object.driver.blob_execute(UNLINK_QUERY, parameters);
+    }
+}
+
+impl Drop for SpooledColumn {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+impl PgDriverInner {
+    /// Copies the column selected by `select` into a temporary large object if
+    /// it is stored compressed, for `openBlob()`.
+    ///
+    /// Returns `None` if the value is stored uncompressed, is `NULL` or no row
+    /// matches, and when statements outside a transaction may go to a read
+    /// replica, which can't create a large object.
+    ///
+    /// # Errors
+    /// Returns an error if a statement fails.
+    pub fn spooled_blob(
+        self: &Arc<Self>,
+        select: &dyn Fn(&str) -> String,
+        key: &ParamsMap,
+    ) -> crate::error::Result<Option<Blob>> {
+        if !self.replica_pools.is_empty()
+            && !self.has_active_transaction()
+            && !self.has_pinned_connection()
+        {
+            return Ok(None);
+        }
+        let Some(oid) = self
+            .blob_value(&select(SPOOL_EXPR), key.clone())?
+            .and_then(|oid| oid.long())
+        else {
+            return Ok(None);
+        };
+        Blob::new(SpooledColumn {
+            object: LargeObject {
+                driver: self.clone(),
+                oid,
+            },
+            unlinked: AtomicBool::new(false),
+        })
+        .map(Some)
+    }
+
+    /// Opens the large object `oid`, or creates an empty one if `oid` is `None`.
+    ///
+    /// # Errors
+    /// Returns an error if the large object does not exist or a statement fails.
+    pub fn large_object(self: &Arc<Self>, oid: Option<i64>) -> crate::error::Result<Blob> {
+        let oid = match oid {
+            Some(oid) => oid,
+            None => self
+                .blob_value(CREATE_QUERY, ParamsMap::new())?
+                .and_then(|oid| oid.long())
+                .ok_or_else(|| SqlxError::Conversion {
+                    message: "lo_create() did not return an OID".to_string(),
+                })?,
+        };
+        Blob::new(LargeObject {
+            driver: self.clone(),
+            oid,
+        })
+    }
+}
@@ -1,4 +1,6 @@
 mod copy;
+mod explain;
+mod large_object;
 #[cfg(test)]
 mod tests;
 use crate::php_sqlx_impl_driver;
@@ -1,5 +1,7 @@
 //! `PostgreSQL` query result iterator.
 
+use crate::dbms::postgres::inner::PgDriverInner;
+
 crate::php_sqlx_impl_query_result!(
     PgQueryResult,
     "Sqlx\\PgQueryResult",
@@ -1,7 +1,7 @@
 use super::*;
 use crate::ast::Ast;
 use crate::by_clause::{ByClause, ByClauseColumnDefinition};
-use crate::dbms::postgres::inner::{DESCRIBE_TABLE_QUERY, SETTINGS};
+use crate::dbms::postgres::inner::{DESCRIBE_TABLE_QUERY, SETTINGS, TABLE_ROWS_QUERY};
 use crate::paginate_clause::PaginateClause;
 use crate::param_value::ParamsMap;
 use collapse::*;
@@ -362,3 +362,15 @@ fn test_describe_table_query_with_schema() {
     assert_eq!(values[0], ParameterValue::String("users".into()));
     assert_eq!(values[1], ParameterValue::String("public".into()));
 }
+
+#[test]
+fn test_table_rows_query_renders() {
+    let ast = into_ast(TABLE_ROWS_QUERY);
+    let mut params = ParamsMap::new();
+    params.insert("table".into(), ParameterValue::String("users".into()));
+
+    let (sql, values) = ast.render(params, &SETTINGS).expect("Rendering failed");
+
+    assert!(sql.contains("$1"), "Expected $1 placeholder for table");
+    assert_eq!(values, vec![ParameterValue::String("users".into())]);
+}
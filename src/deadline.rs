@@ -0,0 +1,166 @@
+//! Request-wide deadline (`setDeadline()`).
+//!
+//! A deadline is an absolute point in time, given as Unix milliseconds, by which
+//! the current request must be done with the database. While it is set:
+//!
+//! - every statement, pool acquire and `begin()` runs with the remaining time as
+//!   its client-side timeout, or with `OPT_QUERY_TIMEOUT` if that is shorter;
+//! - connections handed out by the pool get the remaining time as a server-side
+//!   statement timeout where the database supports it (`statement_timeout` on
+//!   `PostgreSQL`, `max_execution_time` on `MySQL`), and have it reset when
+//!   they are released;
+//! - the retry policy gives up instead of sleeping past the deadline.
+//!
+//! Once the deadline has passed, calls fail immediately with a
+//! `TimeoutException` without touching the pool.
+//!
+//! # Example
+//!
+//! ```php
+//! $driver->setDeadline((int) (($_SERVER['REQUEST_TIME_FLOAT'] + 2) * 1000));
+//! $driver->queryAll('SELECT * FROM orders'); // at most what is left of the 2s
+//! $driver->setDeadline(null);
+//! ```
+
+use crate::error::{Error as SqlxError, Result};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The deadline of the current request, shared by the driver and its pools.
+#[derive(Debug, Default)]
+pub struct Deadline {
+    at: Mutex<Option<SystemTime>>,
+}
+
+impl Deadline {
+    /// Creates an unset deadline.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the deadline to `unix_millis`, or clears it with `None`.
+    pub fn set(&self, unix_millis: Option<i64>) {
+        let at = unix_millis.map(|millis| match u64::try_from(millis) {
+            Ok(millis) => UNIX_EPOCH + Duration::from_millis(millis),
+            Err(_) => UNIX_EPOCH,
+        });
+        *self.at.lock().unwrap_or_else(PoisonError::into_inner) = at;
+    }
+
+    /// Returns the deadline as Unix milliseconds, if set.
+    #[must_use]
+    pub fn get(&self) -> Option<i64> {
+        let at = (*self.at.lock().unwrap_or_else(PoisonError::into_inner))?;
+        let millis = at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        Some(i64::try_from(millis).unwrap_or(i64::MAX))
+    }
+
+    /// Returns the time left before the deadline, or `None` if no deadline is set.
+    ///
+    /// # Errors
+    /// Returns [`SqlxError::DeadlineExceeded`] if the deadline has passed.
+    pub fn remaining(&self, operation: &str) -> Result<Option<Duration>> {
+        let Some(at) = *self.at.lock().unwrap_or_else(PoisonError::into_inner) else {
+            return Ok(None);
+        };
+        match at.duration_since(SystemTime::now()) {
+            Ok(remaining) if !remaining.is_zero() => Ok(Some(remaining)),
+            _ => Err(SqlxError::DeadlineExceeded {
+                operation: operation.to_string(),
+            }),
+        }
+    }
+
+    /// Returns true if waiting `delay` still leaves time before the deadline.
+    #[must_use]
+    pub fn allows(&self, delay: Duration) -> bool {
+        self.remaining("retry")
+            .is_ok_and(|remaining| remaining.is_none_or(|remaining| delay < remaining))
+    }
+
+    /// Returns the server-side statement timeout to apply to a connection, in
+    /// milliseconds, or `None` if no deadline is set.
+    ///
+    /// Never returns `0`, which disables the timeout on most databases.
+    #[must_use]
+    pub fn statement_timeout_ms(&self) -> Option<u64> {
+        let at = (*self.at.lock().unwrap_or_else(PoisonError::into_inner))?;
+        let remaining = at.duration_since(SystemTime::now()).unwrap_or_default();
+        Some(
+            u64::try_from(remaining.as_millis())
+                .unwrap_or(u64::MAX)
+                .max(1),
+        )
+    }
+
+    /// Returns true if a deadline is set.
+    #[must_use]
+    pub fn is_set(&self) -> bool {
+        self.at
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now_millis() -> i64 {
+        i64::try_from(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn unset_deadline_never_expires() {
+        let deadline = Deadline::new();
+        assert!(!deadline.is_set());
+        assert_eq!(deadline.get(), None);
+        assert!(deadline.remaining("query").unwrap().is_none());
+        assert!(deadline.allows(Duration::from_secs(123)));
+        assert_eq!(deadline.statement_timeout_ms(), None);
+    }
+
+    #[test]
+    fn remaining_budget_bounds_waits() {
+        let deadline = Deadline::new();
+        let at = now_millis() + 10_000;
+        deadline.set(Some(at));
+        assert_eq!(deadline.get(), Some(at));
+
+        let remaining = deadline.remaining("query").unwrap().unwrap();
+        assert!(remaining <= Duration::from_secs(10));
+        assert!(remaining > Duration::from_secs(5));
+        assert!(deadline.allows(Duration::from_secs(1)));
+        assert!(!deadline.allows(Duration::from_secs(20)));
+        assert!(deadline.statement_timeout_ms().unwrap() <= 10_000);
+
+        deadline.set(None);
+        assert!(!deadline.is_set());
+    }
+
+    #[test]
+    fn passed_deadline_fails_fast() {
+        let deadline = Deadline::new();
+        deadline.set(Some(now_millis() - 1));
+        assert!(matches!(
+            deadline.remaining("begin"),
+            Err(SqlxError::DeadlineExceeded { operation }) if operation == "begin"
+        ));
+        assert!(!deadline.allows(Duration::ZERO));
+        assert_eq!(deadline.statement_timeout_ms(), Some(1));
+
+        deadline.set(Some(-5));
+        assert_eq!(deadline.get(), Some(0));
+    }
+}
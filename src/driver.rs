@@ -351,13 +351,17 @@ macro_rules! php_sqlx_impl_driver {
             /// If `persistent_name` is set in options, checks the global registry
             /// for an existing connection and reuses it. Otherwise, creates a new
             /// connection pool and optionally registers it for persistence.
+            ///
+            /// With `OPT_PERSISTENT_RECREATE`, a registered pool whose options differ
+            /// is replaced; the old pool closes once the last driver using it is gone.
             pub fn new(options: DriverInnerOptions) -> $crate::error::Result<Self> {
-                if let Some(name) = options.persistent_name.as_ref() {
-                    if let Some(driver_inner) = PERSISTENT_DRIVER_REGISTRY.get(name) {
-                        return Ok(Self {
-                            driver_inner: driver_inner.clone(),
-                        });
-                    }
+                if let Some(name) = options.persistent_name.as_ref()
+                    && let Some(driver_inner) = PERSISTENT_DRIVER_REGISTRY.get(name)
+                    && (!options.persistent_recreate || driver_inner.options == options)
+                {
+                    return Ok(Self {
+                        driver_inner: driver_inner.clone(),
+                    });
                 }
                 let persistent_name = options.persistent_name.clone();
                 let driver_inner = Arc::new(<$inner>::new(options)?);
@@ -366,6 +370,96 @@ macro_rules! php_sqlx_impl_driver {
                 }
                 Ok(Self { driver_inner })
             }
+
+            /// Describes every pool registered with `OPT_PERSISTENT_NAME`.
+            #[must_use]
+            pub fn persistent_pools() -> Vec<serde_json::Value> {
+                PERSISTENT_DRIVER_REGISTRY
+                    .iter()
+                    .map(|entry| entry.value().pool_info(entry.key()))
+                    .collect()
+            }
+
+            /// Unregisters the persistent pool `name` and closes it, even if drivers
+            /// still use it. Returns false if no such pool is registered.
+            pub fn close_persistent(name: &str) -> bool {
+                let Some((_, driver_inner)) = PERSISTENT_DRIVER_REGISTRY.remove(name) else {
+                    return false;
+                };
+                driver_inner.close();
+                true
+            }
+
+            /// Resets the per-request state the ending request left on the persistent
+            /// pools (see `reset_request_state()`). Returns a warning for each pool
+            /// that had a dangling transaction or pinned connection.
+            pub fn reset_persistent() -> Vec<String> {
+                PERSISTENT_DRIVER_REGISTRY
+                    .iter()
+                    .filter_map(|entry| {
+                        let cleaned = entry.value().reset_request_state();
+                        (!cleaned.is_empty()).then(|| {
+                            format!(
+                                "Sqlx: request ended with dangling state on persistent pool \
+                                 `{}`: {}",
+                                entry.key(),
+                                cleaned.join(", ")
+                            )
+                        })
+                    })
+                    .collect()
+            }
+
+            /// Unregisters and closes every persistent pool. Returns how many were closed.
+            pub fn close_all_persistent() -> usize {
+                let names: Vec<String> = PERSISTENT_DRIVER_REGISTRY
+                    .iter()
+                    .map(|entry| entry.key().clone())
+                    .collect();
+                names.iter().filter(|name| Self::close_persistent(name)).count()
+            }
+
+            /// Reads one entry of `parallel()`: an SQL string, an `[$sql, $params]`
+            /// pair or a query builder. Returns the SQL, its parameters and the
+            /// builder's query tags, or `None` if the entry is none of these.
+            #[allow(clippy::type_complexity)]
+            fn parallel_entry(
+                value: &Zval,
+            ) -> Option<(
+                String,
+                Option<BTreeMap<String, ParameterValue>>,
+                BTreeMap<String, String>,
+            )> {
+                use ext_php_rs::convert::FromZval;
+                use ext_php_rs::types::ZendClassObject;
+
+                if let Some(sql) = value.string() {
+                    return Some((sql, None, BTreeMap::new()));
+                }
+                if let Some(pair) = value.array() {
+                    let sql = pair.get_index(0).and_then(Zval::string)?;
+                    let parameters = match pair.get_index(1) {
+                        Some(parameters) if !parameters.is_null() => {
+                            Some(BTreeMap::<String, ParameterValue>::from_zval(parameters)?)
+                        }
+                        _ => None,
+                    };
+                    return Some((sql, parameters, BTreeMap::new()));
+                }
+                let object = value.object()?;
+                let (query, parameters, tags) =
+                    if let Some(builder) = ZendClassObject::<$read_query_builder>::from_zend_obj(object)
+                        .and_then(|x| x.obj.as_ref())
+                    {
+                        (&builder.query, &builder.parameters, &builder.query_tags)
+                    } else {
+                        let builder = ZendClassObject::<$write_query_builder>::from_zend_obj(object)
+                            .and_then(|x| x.obj.as_ref())?;
+                        (&builder.query, &builder.parameters, &builder.query_tags)
+                    };
+                let parameters = (!parameters.is_empty()).then(|| parameters.clone());
+                Some((query.clone(), parameters, tags.clone()))
+            }
         }
 
         #[php_impl]
@@ -385,6 +479,10 @@ macro_rules! php_sqlx_impl_driver {
 
             /// Creates a prepared query object with the given SQL string.
             ///
+            /// On `PostgreSQL` and `MySQL`, its statements are prepared server-side on
+            /// each connection they run on and kept in that connection's statement
+            /// cache (see `OPT_STATEMENT_CACHE_CAPACITY`).
+            ///
             /// # Arguments
             /// - `query`: SQL query string to prepare
             ///
@@ -395,6 +493,12 @@ macro_rules! php_sqlx_impl_driver {
                 $prepared_query::new(query, self.driver_inner.clone())
             }
 
+            /// Returns the statement cache hits and misses of all prepared queries of
+            /// this driver, as `['hits' => int, 'misses' => int, 'unknown' => int]`.
+            pub fn statement_cache_stats(&self) -> BTreeMap<String, u64> {
+                self.driver_inner.prepared_statements.totals.to_map()
+            }
+
             /// Creates a query builder object
             ///
             ///
@@ -456,8 +560,8 @@ macro_rules! php_sqlx_impl_driver {
             ) -> $crate::error::Result<$query_result> {
                 let batch_size = batch_size.unwrap_or($crate::query_result::DEFAULT_BATCH_SIZE);
                 let assoc = self.driver_inner.options.associative_arrays;
-                let (receiver, cancel_token) = self.driver_inner.query_stream(query, parameters, batch_size)?;
-                Ok($query_result::new(receiver, cancel_token, assoc, batch_size))
+                let (receiver, cancel_token, timer) = self.driver_inner.query_stream(query, parameters, batch_size)?;
+                Ok($query_result::new(self.driver_inner.clone(), receiver, cancel_token, timer, assoc, batch_size))
             }
 
             /// Executes an SQL query and returns a lazy `QueryResult` iterator with rows as associative arrays.
@@ -479,8 +583,8 @@ macro_rules! php_sqlx_impl_driver {
                 batch_size: Option<usize>,
             ) -> $crate::error::Result<$query_result> {
                 let batch_size = batch_size.unwrap_or($crate::query_result::DEFAULT_BATCH_SIZE);
-                let (receiver, cancel_token) = self.driver_inner.query_stream(query, parameters, batch_size)?;
-                Ok($query_result::new(receiver, cancel_token, true, batch_size))
+                let (receiver, cancel_token, timer) = self.driver_inner.query_stream(query, parameters, batch_size)?;
+                Ok($query_result::new(self.driver_inner.clone(), receiver, cancel_token, timer, true, batch_size))
             }
 
             /// Executes an SQL query and returns a lazy `QueryResult` iterator with rows as objects.
@@ -502,8 +606,8 @@ macro_rules! php_sqlx_impl_driver {
                 batch_size: Option<usize>,
             ) -> $crate::error::Result<$query_result> {
                 let batch_size = batch_size.unwrap_or($crate::query_result::DEFAULT_BATCH_SIZE);
-                let (receiver, cancel_token) = self.driver_inner.query_stream(query, parameters, batch_size)?;
-                Ok($query_result::new(receiver, cancel_token, false, batch_size))
+                let (receiver, cancel_token, timer) = self.driver_inner.query_stream(query, parameters, batch_size)?;
+                Ok($query_result::new(self.driver_inner.clone(), receiver, cancel_token, timer, false, batch_size))
             }
 
             /// Executes an SQL query and hydrates every result row into an instance of `class`.
@@ -586,6 +690,168 @@ macro_rules! php_sqlx_impl_driver {
                 self.driver_inner.query_maybe_row_into(&target, query, parameters, None)
             }
 
+            /// Starts an SQL query in the background, on a pool connection of its own,
+            /// and returns at once. Collect the rows with `Pending::wait()`.
+            ///
+            /// The before-query hook, query firewall and cost guard run before this
+            /// method returns; background queries are not retried. Inside a
+            /// transaction or `withConnection()`, the query runs at once on that
+            /// connection.
+            ///
+            /// # Example
+            /// ```php
+            /// $orders = $driver->queryAllAsync('SELECT * FROM orders WHERE user_id = ?', [42]);
+            /// $user = $driver->queryRow('SELECT * FROM users WHERE id = ?', [42]);
+            /// foreach ($orders->wait() as $order) { /* ... */ }
+            /// ```
+            ///
+            /// # Exceptions
+            /// Throws if the query can't be rendered or is refused; errors of the
+            /// query itself are thrown by `wait()`.
+            pub fn query_all_async(
+                &self,
+                query: &str,
+                parameters: Option<BTreeMap<String, ParameterValue>>,
+            ) -> $crate::error::Result<$crate::pending::Pending> {
+                let task = self.driver_inner.spawn_fetch_all(query, parameters)?;
+                let driver_inner = self.driver_inner.clone();
+                Ok($crate::pending::Pending::new(task, move |task| {
+                    driver_inner.join_all(task, None)
+                }))
+            }
+
+            /// Starts an SQL query in the background, like `queryAllAsync()`, and
+            /// returns at once. `Pending::wait()` returns its first row, or `null` if
+            /// there is none.
+            ///
+            /// # Example
+            /// ```php
+            /// $user = $driver->queryMaybeRowAsync('SELECT * FROM users WHERE id = ?', [42]);
+            /// $orders = $driver->queryAll('SELECT * FROM orders WHERE user_id = ?', [42]);
+            /// $user = $user->wait();
+            /// ```
+            ///
+            /// # Exceptions
+            /// Throws if the query can't be rendered or is refused; errors of the
+            /// query itself are thrown by `wait()`.
+            pub fn query_maybe_row_async(
+                &self,
+                query: &str,
+                parameters: Option<BTreeMap<String, ParameterValue>>,
+            ) -> $crate::error::Result<$crate::pending::Pending> {
+                let task = self.driver_inner.spawn_fetch_maybe_row(query, parameters)?;
+                let driver_inner = self.driver_inner.clone();
+                Ok($crate::pending::Pending::new(task, move |task| {
+                    driver_inner.join_maybe_row(task, None)
+                }))
+            }
+
+            /// Starts an SQL statement in the background on the primary, like
+            /// `queryAllAsync()`, and returns at once. `Pending::wait()` returns the
+            /// number of affected rows.
+            ///
+            /// Outside a transaction, the statement commits on its own whether or not
+            /// its `Pending` is waited for.
+            ///
+            /// # Example
+            /// ```php
+            /// $logged = $driver->executeAsync('INSERT INTO audit (event) VALUES (?)', ['login']);
+            /// $user = $driver->queryRow('SELECT * FROM users WHERE id = ?', [42]);
+            /// $logged->wait();
+            /// ```
+            ///
+            /// # Exceptions
+            /// Throws if the statement can't be rendered or is refused; errors of the
+            /// statement itself are thrown by `wait()`.
+            pub fn execute_async(
+                &self,
+                query: &str,
+                parameters: Option<BTreeMap<String, ParameterValue>>,
+            ) -> $crate::error::Result<$crate::pending::Pending> {
+                use ext_php_rs::convert::IntoZval;
+
+                let task = self.driver_inner.spawn_execute(query, parameters)?;
+                let driver_inner = self.driver_inner.clone();
+                Ok($crate::pending::Pending::new(task, move |task| {
+                    driver_inner
+                        .join_execute(task)?
+                        .into_zval(false)
+                        .map_err(|err| SqlxError::Conversion {
+                            message: format!("{err}"),
+                        })
+                }))
+            }
+
+            /// Runs several read queries concurrently, each on a pool connection of
+            /// its own, and returns their rows keyed like `$queries`.
+            ///
+            /// # Arguments
+            /// - `queries`: `name => query`, where a query is an SQL string, an
+            ///   `[$sql, $params]` pair or a query builder.
+            ///
+            /// # Returns
+            /// `name => rows`. A query that failed gets the exception it would have
+            /// thrown (a `Sqlx\Exceptions\SqlxException`) instead of rows.
+            ///
+            /// # Example
+            /// ```php
+            /// $stats = $driver->parallel([
+            ///     'users' => 'SELECT count(*) AS n FROM users',
+            ///     'orders' => ['SELECT count(*) AS n FROM orders WHERE created_at > ?', [$since]],
+            ///     'top' => $driver->builder()->select('*')->from('products')->limit(5),
+            /// ]);
+            /// ```
+            ///
+            /// # Exceptions
+            /// Throws if the driver is closed.
+            pub fn parallel(
+                &self,
+                queries: &ext_php_rs::types::ZendHashTable,
+            ) -> $crate::error::Result<Zval> {
+                use ext_php_rs::convert::IntoZval;
+                use ext_php_rs::types::{ArrayKey, ZendHashTable};
+                use $crate::pending::StatementTask;
+
+                self.driver_inner.ensure_open()?;
+                let tasks: Vec<_> = queries
+                    .iter()
+                    .map(|(key, value)| {
+                        let task = match Self::parallel_entry(value) {
+                            Some((sql, params, tags)) => {
+                                let _tags = self.driver_inner.query_tags.scope(tags);
+                                self.driver_inner
+                                    .spawn_fetch_all(&sql, params)
+                                    .unwrap_or_else(|err| StatementTask::Ready(Err(err)))
+                            }
+                            None => StatementTask::Ready(Err(SqlxError::InvalidParameter {
+                                name: key.to_string(),
+                                expected: "an SQL string, [$sql, $params] or a query builder"
+                                    .to_string(),
+                            })),
+                        };
+                        (key, task)
+                    })
+                    .collect();
+
+                let mut results = ZendHashTable::with_capacity(u32::try_from(tasks.len())?);
+                for (key, task) in tasks {
+                    let value = match self.driver_inner.join_all(task, None) {
+                        Ok(rows) => rows,
+                        Err(err) => err.into_exception_object().unwrap_or_else(Zval::new),
+                    };
+                    let inserted = match key {
+                        ArrayKey::Long(index) => results.insert(index, value),
+                        key => results.insert(key.to_string().as_str(), value),
+                    };
+                    inserted.map_err(|err| SqlxError::Conversion {
+                        message: format!("{err}"),
+                    })?;
+                }
+                results.into_zval(false).map_err(|err| SqlxError::Conversion {
+                    message: format!("{err}"),
+                })
+            }
+
             /// Executes an SQL query and returns a dictionary grouping rows by the first column.
             ///
             /// Each row in the result must contain at least one column. The **first column** is used as the **key**, and the
@@ -965,6 +1231,68 @@ macro_rules! php_sqlx_impl_driver {
                 self.driver_inner.copy_in(table, rows)
             }
 
+            /// Opens a binary column of one row as a `Sqlx\Blob`, read a chunk at a time.
+            ///
+            /// Each `read()` past the buffered chunk fetches the next chunk with
+            /// `SUBSTRING()`, so a large `bytea`, `LONGBLOB` or `VARBINARY(MAX)` value is
+            /// never held in memory at once. Use `copyTo()` to pipe it to a PHP stream.
+            /// The blob is read-only; bind a `Sqlx\Binary` or a stream to write.
+            ///
+            /// # Arguments
+            /// - `table`: Table name.
+            /// - `column`: The binary column.
+            /// - `key`: Column => value pairs identifying the row, usually its primary key.
+            ///
+            /// # Returns
+            /// The blob, or null if the value is `NULL`.
+            ///
+            /// # Example
+            /// ```php
+            /// $blob = $driver->openBlob('files', 'content', ['id' => 7]);
+            /// $blob->copyTo(fopen('php://output', 'wb'));
+            /// ```
+            ///
+            /// # Exceptions
+            /// Throws if a name is invalid, the key is empty, no row matches the key,
+            /// or a statement fails.
+            pub fn open_blob(
+                &self,
+                table: &str,
+                column: &str,
+                key: BTreeMap<String, ParameterValue>,
+            ) -> $crate::error::Result<Option<$crate::blob::Blob>> {
+                self.driver_inner.open_blob(table, column, key)
+            }
+
+            /// Opens a `PostgreSQL` large object as a readable and writable `Sqlx\Blob`.
+            ///
+            /// Chunks are read and written with the server-side `lo_get()` and `lo_put()`
+            /// functions, one statement each, so no transaction is needed. Store the OID
+            /// (`$blob->oid()`) to reference the object; delete it with `lo_unlink()`.
+            ///
+            /// **`MySQL`** and **`MSSQL`** have no large objects and will throw; use
+            /// `openBlob()` there.
+            ///
+            /// # Arguments
+            /// - `oid`: The large object to open, or null to create an empty one.
+            ///
+            /// # Example
+            /// ```php
+            /// $blob = $driver->largeObject();
+            /// $blob->copyFrom(fopen('upload.iso', 'rb'));
+            /// $driver->execute('INSERT INTO files (content_oid) VALUES (?)', [$blob->oid()]);
+            /// ```
+            ///
+            /// # Exceptions
+            /// Throws if the driver is not `PostgreSQL`, the large object does not exist,
+            /// or a statement fails.
+            pub fn large_object(
+                &self,
+                oid: Option<i64>,
+            ) -> $crate::error::Result<$crate::blob::Blob> {
+                self.driver_inner.large_object(oid)
+            }
+
             /// Inserts a row or updates it if a conflict occurs on the specified columns.
             ///
             /// This method generates database-specific SQL for upsert operations:
@@ -1097,6 +1425,63 @@ macro_rules! php_sqlx_impl_driver {
                 self.driver_inner.dry(query, parameters)
             }
 
+            /// Returns the execution plan of a query.
+            ///
+            /// The query is rendered through the normal AST pipeline and explained with
+            /// `EXPLAIN (FORMAT JSON)` on `PostgreSQL`, `EXPLAIN FORMAT=JSON` on `MySQL`
+            /// and `SET SHOWPLAN_XML` on MSSQL.
+            ///
+            /// # Arguments
+            /// - `query`: SQL query string
+            /// - `parameters`: Optional array of indexed/named parameters to bind.
+            /// - `options`: `['analyze' => bool, 'buffers' => bool]`. `analyze` executes the
+            ///   statement (inside a rolled-back transaction for writes); `buffers` is
+            ///   `PostgreSQL`-only.
+            ///
+            /// # Returns
+            /// `['plan' => ..., 'summary' => ['total_cost' => ?float, 'estimated_rows' => ?float,
+            /// 'seq_scan' => bool, 'seq_scan_tables' => string[]]]`. `plan` is the decoded JSON
+            /// plan, or the XML showplan string on MSSQL.
+            ///
+            /// # Exceptions
+            /// Throws an exception if the query can't be rendered, an option is invalid,
+            /// or the database rejects the `EXPLAIN`.
+            pub fn explain(
+                &self,
+                query: &str,
+                parameters: Option<BTreeMap<String, ParameterValue>>,
+                options: Option<BTreeMap<String, ParameterValue>>,
+            ) -> $crate::error::Result<Zval> {
+                let options = $crate::explain::ExplainOptions::from_params(options)?;
+                self.driver_inner
+                    .explain(query, parameters, options)?
+                    .into_zval()
+            }
+
+            /// Starts a query profile on this driver.
+            ///
+            /// Every statement executed until `$profile->stop()` is aggregated by its
+            /// normalized fingerprint: placeholders, inline literals and `IN`-list
+            /// lengths are ignored, so an N+1 loop shows up as one fingerprint with a
+            /// high count. Starting a new profile stops the previous one.
+            ///
+            /// # Arguments
+            /// - `warn_after`: Optional threshold; the first execution that takes a
+            ///   fingerprint past it adds a warning to the report and to the `$warnings`
+            ///   argument of the `onQuery()` hook.
+            ///
+            /// # Example
+            /// ```php
+            /// $profile = $driver->profile(10);
+            /// // ... handle the request ...
+            /// $report = $profile->stop();
+            /// // ['queries' => 57, 'distinct' => 3, 'duplicates' => 12, 'total_ms' => 41.2, ...]
+            /// ```
+            pub fn profile(&self, warn_after: Option<u64>) -> $crate::profiler::Profile {
+                self.driver_inner.settings_owner.claim();
+                $crate::profiler::Profile::new(self.driver_inner.profiler.start(warn_after))
+            }
+
             /// Registers a callback to be invoked after each query execution.
             ///
             /// The callback receives:
@@ -1105,6 +1490,9 @@ macro_rules! php_sqlx_impl_driver {
             /// - `float $durationMs` - Execution time in milliseconds
             /// - `?int $rows` - Rows affected (writes) or returned (reads), or `null` if unknown
             /// - `?string $error` - Error message if the query failed, or `null` on success
+            /// - `mixed $meta` - Metadata attached by the `onBeforeQuery()` hook, or `null`
+            /// - `array $warnings` - Warnings about the statement, e.g. from the cost guard
+            ///   or a running `profile()`
             ///
             /// # Example
             /// ```php
@@ -1132,9 +1520,14 @@ macro_rules! php_sqlx_impl_driver {
             ///
             /// # Notes
             /// - Exceptions thrown by the callback are silently ignored to avoid disrupting query execution
-            /// - The hook applies to all query methods: `query*`, `execute`, `insert`
-            /// - The hook is NOT inherited by prepared queries or query builders
+            /// - The hook applies to all query methods: `query*`, `execute`, `insert`, including
+            ///   prepared queries and query builders created from this driver
+            /// - Lazy `query()` results report once, when exhausted or destroyed, with the
+            ///   number of rows streamed and the total time since the query was sent
+            /// - `begin`, `commit`, `rollback` and savepoint statements are reported too,
+            ///   with `$rows` set to `null`
             pub fn on_query(&self, callback: &Zval) {
+                self.driver_inner.settings_owner.claim();
                 if callback.is_null() {
                     self.driver_inner.query_hook.clear();
                 } else {
@@ -1142,6 +1535,186 @@ macro_rules! php_sqlx_impl_driver {
                 }
             }
 
+            /// Registers a callback to be invoked before each statement is executed.
+            ///
+            /// The callback receives:
+            /// - `string $sql` - The SQL as passed to the driver (before rendering)
+            /// - `array $params` - The bound parameters
+            /// - `string $kind` - One of `execute`, `query`, `stream`, `copy`, `begin`,
+            ///   `commit`, `rollback`, `savepoint`, `rollback_to_savepoint`, `release_savepoint`
+            ///
+            /// It may return:
+            /// - `null` to run the statement unchanged;
+            /// - a `string` to replace the SQL;
+            /// - an array with any of the keys `sql`, `params` and `meta`. `meta` is passed
+            ///   as the sixth argument to the `onQuery()` hook.
+            ///
+            /// Throwing from the callback vetoes the statement: the driver throws a
+            /// `NotPermittedException` carrying the callback's message. Transaction
+            /// statements can be vetoed but not rewritten; rollbacks can never be vetoed.
+            ///
+            /// # Example
+            /// ```php
+            /// $driver->onBeforeQuery(function (string $sql, array $params, string $kind) {
+            ///     if ($kind === 'execute' && Maintenance::enabled()) {
+            ///         throw new \RuntimeException('Writes are disabled during maintenance');
+            ///     }
+            ///     return ['meta' => ['request_id' => Request::id()]];
+            /// });
+            ///
+            /// // Disable the hook
+            /// $driver->onBeforeQuery(null);
+            /// ```
+            pub fn on_before_query(&self, callback: &Zval) {
+                self.driver_inner.settings_owner.claim();
+                if callback.is_null() {
+                    self.driver_inner.before_query_hook.clear();
+                } else {
+                    self.driver_inner
+                        .before_query_hook
+                        .set(callback.shallow_clone());
+                }
+            }
+
+            /// Registers a callback to be invoked before each automatic retry.
+            ///
+            /// The callback receives:
+            /// - `int $attempt` - The retry about to run, starting at 1
+            /// - `float $delayMs` - How long the driver waits before it
+            /// - `string $error` - The message of the error that triggered the retry
+            ///
+            /// Only statements retried under the retry policy (`OPT_RETRY_MAX_ATTEMPTS`)
+            /// are reported.
+            ///
+            /// # Example
+            /// ```php
+            /// $driver->onRetry(function (int $attempt, float $delayMs, string $error) {
+            ///     Logger::warning("Retry #{$attempt} in {$delayMs}ms: {$error}");
+            /// });
+            ///
+            /// // Disable the hook
+            /// $driver->onRetry(null);
+            /// ```
+            ///
+            /// # Notes
+            /// - Exceptions thrown by the callback are silently ignored and don't stop the retry
+            pub fn on_retry(&self, callback: &Zval) {
+                self.driver_inner.settings_owner.claim();
+                if callback.is_null() {
+                    self.driver_inner.retry_hook.clear();
+                } else {
+                    self.driver_inner.retry_hook.set(callback.shallow_clone());
+                }
+            }
+
+            /// Returns the state of the circuit breaker (`OPT_CIRCUIT_BREAKER`).
+            ///
+            /// # Returns
+            /// - `"closed"`: statements run normally (always the case if the breaker
+            ///   is disabled);
+            /// - `"open"`: statements fail fast with a `CircuitOpenException`;
+            /// - `"half_open"`: the cooldown has elapsed and the next statements probe
+            ///   the database.
+            ///
+            /// Drivers sharing a persistent pool (`OPT_PERSISTENT_NAME`) share the state.
+            #[must_use]
+            pub fn circuit_state(&self) -> &'static str {
+                self.driver_inner.circuit_state().as_str()
+            }
+
+            /// Sets a deadline for the current request, as Unix milliseconds.
+            ///
+            /// Until cleared, every query, pool acquire, retry backoff and `begin()`
+            /// uses the time left as its timeout, also applied server-side on
+            /// PostgreSQL (`statement_timeout`) and MySQL (`max_execution_time`).
+            /// Once the deadline has passed, calls fail immediately with a
+            /// `TimeoutException`.
+            ///
+            /// # Arguments
+            /// - `unix_millis`: The deadline, or `null` to clear it.
+            ///
+            /// The deadline is stored with the connection pool; with `OPT_PERSISTENT_NAME`
+            /// it is cleared when the PHP request ends.
+            pub fn set_deadline(&self, unix_millis: Option<i64>) {
+                self.driver_inner.set_deadline(unix_millis);
+            }
+
+            /// Returns the deadline set with `setDeadline()` as Unix milliseconds, or
+            /// `null` if none is set.
+            #[must_use]
+            pub fn get_deadline(&self) -> Option<i64> {
+                self.driver_inner.get_deadline()
+            }
+
+            /// Sets `sqlcommenter` tags appended to every statement sent by this driver.
+            ///
+            /// Tags are rendered as a trailing comment, e.g.
+            /// `SELECT ... /*controller='users',route='%2Fusers'*/`, so that database-side
+            /// tooling can attribute queries to application code. Keys and values are
+            /// percent-encoded and cannot break out of the comment. Tags apply to prepared
+            /// queries and query builders created from this driver as well.
+            ///
+            /// Passing an empty array removes all driver-level tags.
+            ///
+            /// # Example
+            /// ```php
+            /// $driver->setQueryTags(['application' => 'billing', 'route' => '/invoices']);
+            /// ```
+            ///
+            /// # Exceptions
+            /// Throws if a tag value is not a scalar.
+            pub fn set_query_tags(
+                &self,
+                tags: BTreeMap<String, ParameterValue>,
+            ) -> $crate::error::Result<()> {
+                self.driver_inner
+                    .query_tags
+                    .set($crate::query_tags::tags_from_params(tags)?);
+                self.driver_inner.settings_owner.claim();
+                Ok(())
+            }
+
+            /// Returns the currently effective query tags.
+            pub fn get_query_tags(&self) -> BTreeMap<String, String> {
+                self.driver_inner.query_tags.effective()
+            }
+
+            /// Executes a callback with additional query tags.
+            ///
+            /// The tags are merged over the driver-level tags (see `setQueryTags()`) for
+            /// every statement executed inside the callback — including prepared queries
+            /// and query builders — and removed once the callback returns or throws.
+            ///
+            /// # Returns
+            /// The value returned by the callback.
+            ///
+            /// # Example
+            /// ```php
+            /// $users = $driver->withQueryTags(['controller' => 'UserController'], function ($driver) {
+            ///     return $driver->queryAll('SELECT * FROM users');
+            /// });
+            /// ```
+            ///
+            /// # Exceptions
+            /// Throws if a tag value is not a scalar or the callback throws.
+            pub fn with_query_tags(
+                &self,
+                tags: BTreeMap<String, ParameterValue>,
+                callable: ZendCallable,
+            ) -> PhpResult<Zval> {
+                let tags = $crate::query_tags::tags_from_params(tags)?;
+                let _tags = self.driver_inner.query_tags.scope(tags);
+                callable.try_call(vec![self]).map_err(|err| match err {
+                    ext_php_rs::error::Error::Exception(exception) => exception.properties_table[0]
+                        .string()
+                        .as_ref()
+                        .map(String::as_str)
+                        .unwrap_or("Unknown error inside callback.")
+                        .into(),
+                    _ => err.into(),
+                })
+            }
+
             /// Sets the application name for this connection.
             ///
             /// This helps identify the connection in database monitoring tools:
@@ -1250,24 +1823,21 @@ macro_rules! php_sqlx_impl_driver {
                 // Callback-based transaction with automatic commit/rollback
                 let callable = callable.unwrap();
                 let callable_ret = callable.try_call(vec![self]);
-                let tx = self.driver_inner.retrieve_ongoing_transaction().unwrap();
                 match callable_ret {
                     Ok(value) => {
                         if value.is_false() {
-                            $crate::RUNTIME
-                                .block_on(tx.rollback())
-                                .map_err(SqlxError::rollback_failed)?;
-                        } else {
-                            $crate::RUNTIME
-                                .block_on(tx.commit())
-                                .map_err(SqlxError::commit_failed)?;
+                            self.driver_inner.rollback()?;
+                        } else if let Err(err) = self.driver_inner.commit() {
+                            // A vetoed commit leaves the transaction open; roll it back.
+                            if matches!(err, SqlxError::Vetoed { .. }) {
+                                self.driver_inner.rollback()?;
+                            }
+                            return Err(err.into());
                         }
                         Ok(true)
                     }
                     Err(err) => {
-                        $crate::RUNTIME
-                            .block_on(tx.rollback())
-                            .map_err(SqlxError::rollback_failed)?;
+                        self.driver_inner.rollback()?;
                         match err {
                             ext_php_rs::error::Error::Exception(exception) => Err(exception
                                 .properties_table[0]
@@ -1421,6 +1991,49 @@ macro_rules! php_sqlx_impl_driver {
                     }
                 }
             }
+
+            /// Queues statements with a callback and runs them together on one
+            /// connection.
+            ///
+            /// The statements are sent one after another with their parameters bound.
+            /// Outside a transaction, the batch runs in one of its own and succeeds or
+            /// fails as a whole. Inside a transaction or `withConnection()`, that
+            /// connection is used.
+            ///
+            /// # Arguments
+            /// - `callable`: Receives a `Sqlx\Batch` to queue statements on with
+            ///   `execute()` and `queryAll()`.
+            ///
+            /// # Returns
+            /// One result per statement, in order: the affected rows of an `execute()`,
+            /// the rows of a `queryAll()`.
+            ///
+            /// # Example
+            /// ```php
+            /// $results = $driver->batch(function (Sqlx\Batch $b) use ($events) {
+            ///     foreach ($events as $event) {
+            ///         $b->execute('INSERT INTO audit (event) VALUES (?)', [$event]);
+            ///     }
+            ///     $b->queryAll('SELECT count(*) AS n FROM audit');
+            /// });
+            /// ```
+            ///
+            /// # Exceptions
+            /// Throws if the callback throws or a statement fails. Batches are not
+            /// retried.
+            pub fn batch(&self, callable: ZendCallable) -> PhpResult<Vec<Zval>> {
+                let batch = $crate::batch::Batch::default();
+                callable.try_call(vec![&batch]).map_err(|err| match err {
+                    ext_php_rs::error::Error::Exception(exception) => exception.properties_table[0]
+                        .string()
+                        .as_ref()
+                        .map(String::as_str)
+                        .unwrap_or("Unknown error inside callback.")
+                        .into(),
+                    _ => err.into(),
+                })?;
+                Ok(self.driver_inner.batch(&batch.take(), None)?)
+            }
         }
     };
 
@@ -1,8 +1,9 @@
+use crate::conversion::json_into_zval;
 use crate::dbms::mssql::MssqlDriver;
 use crate::dbms::mysql::MySqlDriver;
 use crate::dbms::postgres::PgDriver;
 use crate::error::Error as SqlxError;
-use crate::options::{DriverOptions, DriverOptionsArg};
+use crate::options::{DriverInnerOptions, DriverOptions, DriverOptionsArg};
 use ext_php_rs::convert::IntoZval;
 use ext_php_rs::prelude::*;
 use ext_php_rs::types::{ZendClassObject, Zval};
@@ -35,28 +36,91 @@ impl DriverFactory {
     /// # Returns
     /// Instance of `Sqlx\PgDriver`, `Sqlx\MySqlDriver`, or `Sqlx\MssqlDriver`
     pub fn make(url_or_options: DriverOptionsArg) -> crate::error::Result<Zval> {
-        let options = url_or_options.parse()?;
+        AnyDriver::new(url_or_options.parse()?)?.to_zval()
+    }
+
+    /// Lists the connection pools kept across requests with `OPT_PERSISTENT_NAME`.
+    ///
+    /// # Returns
+    /// A list of `['name' => string, 'driver' => string, 'url' => string,
+    /// 'size' => int, 'idle' => int, 'max_connections' => int, 'replicas' => int,
+    /// 'age_seconds' => float, 'closed' => bool]`. The URL's password is masked.
+    pub fn persistent_pools() -> crate::error::Result<Zval> {
+        let pools = PgDriver::persistent_pools()
+            .into_iter()
+            .chain(MySqlDriver::persistent_pools())
+            .chain(MssqlDriver::persistent_pools())
+            .collect();
+        json_into_zval(serde_json::Value::Array(pools), true)
+    }
+
+    /// Closes the persistent pool `name` and removes it from the registry.
+    ///
+    /// Drivers still using it fail with "Driver has been closed"; the next
+    /// driver created with this name opens a new pool.
+    ///
+    /// # Returns
+    /// `false` if no pool is registered under this name.
+    pub fn close_persistent(name: &str) -> bool {
+        // Evaluated eagerly: the name may be registered by several drivers
+        let closed = [
+            PgDriver::close_persistent(name),
+            MySqlDriver::close_persistent(name),
+            MssqlDriver::close_persistent(name),
+        ];
+        closed.contains(&true)
+    }
+
+    /// Closes every persistent pool and empties the registry.
+    ///
+    /// # Returns
+    /// The number of pools closed.
+    pub fn close_all_persistent() -> usize {
+        PgDriver::close_all_persistent()
+            + MySqlDriver::close_all_persistent()
+            + MssqlDriver::close_all_persistent()
+    }
+}
+
+/// A driver of the kind selected by the URL scheme.
+pub(crate) enum AnyDriver {
+    Pg(PgDriver),
+    MySql(MySqlDriver),
+    Mssql(MssqlDriver),
+}
+
+impl AnyDriver {
+    /// Creates the driver matching the scheme of `options.url`.
+    pub(crate) fn new(options: DriverInnerOptions) -> crate::error::Result<Self> {
         let url = Url::parse(options.url.as_ref().ok_or(SqlxError::UrlRequired)?)?;
         let scheme = url.scheme();
         match scheme.to_lowercase().as_str() {
-            "postgres" | "postgresql" | "pgsql" => {
-                Ok(ZendClassObject::new(PgDriver::new(options)?)
-                    .into_zval(false)
-                    .map_err(|err| SqlxError::Conversion {
-                        message: format!("{err}"),
-                    })?)
-            }
-            "mysql" => Ok(ZendClassObject::new(MySqlDriver::new(options)?)
-                .into_zval(false)
-                .map_err(|err| SqlxError::Conversion {
-                    message: format!("{err}"),
-                })?),
-            "mssql" | "sqlserver" => Ok(ZendClassObject::new(MssqlDriver::new(options)?)
-                .into_zval(false)
-                .map_err(|err| SqlxError::Conversion {
-                    message: format!("{err}"),
-                })?),
+            "postgres" | "postgresql" | "pgsql" => Ok(Self::Pg(PgDriver::new(options)?)),
+            "mysql" => Ok(Self::MySql(MySqlDriver::new(options)?)),
+            "mssql" | "sqlserver" => Ok(Self::Mssql(MssqlDriver::new(options)?)),
             _ => Err(SqlxError::Other(format!("Unsupported scheme: {scheme}"))),
         }
     }
+
+    /// Wraps the driver into a PHP object. Objects created from the same
+    /// `AnyDriver` share its pool and state.
+    pub(crate) fn to_zval(&self) -> crate::error::Result<Zval> {
+        match self {
+            Self::Pg(driver) => ZendClassObject::new(PgDriver {
+                driver_inner: driver.driver_inner.clone(),
+            })
+            .into_zval(false),
+            Self::MySql(driver) => ZendClassObject::new(MySqlDriver {
+                driver_inner: driver.driver_inner.clone(),
+            })
+            .into_zval(false),
+            Self::Mssql(driver) => ZendClassObject::new(MssqlDriver {
+                driver_inner: driver.driver_inner.clone(),
+            })
+            .into_zval(false),
+        }
+        .map_err(|err| SqlxError::Conversion {
+            message: format!("{err}"),
+        })
+    }
 }
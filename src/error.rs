@@ -3,11 +3,15 @@
 //! This module provides a structured error enum that converts to PHP exceptions
 //! with appropriate error codes and messages.
 
+use crate::param_value::{ParameterValue, ParamsMap};
+use ext_php_rs::boxed::ZBox;
 use ext_php_rs::builders::ModuleBuilder;
 use ext_php_rs::class::RegisteredClass;
+use ext_php_rs::convert::{IntoZval, IntoZvalDyn};
 use ext_php_rs::exception::PhpException;
 use ext_php_rs::prelude::*;
-use ext_php_rs::zend::ce;
+use ext_php_rs::types::{ZendClassObject, ZendObject, Zval};
+use ext_php_rs::zend::{ClassEntry, ce};
 use std::fmt;
 
 /// Base exception class for php-sqlx errors.
@@ -44,6 +48,8 @@ impl SqlxException {
     pub const TIMEOUT: i32 = ErrorCode::Timeout as i32;
     /// Pool exhausted
     pub const POOL_EXHAUSTED: i32 = ErrorCode::PoolExhausted as i32;
+    /// Circuit breaker open
+    pub const CIRCUIT_OPEN: i32 = ErrorCode::CircuitOpen as i32;
 }
 
 /// Helper function to get `SqlxException` class entry for subclass extension.
@@ -59,11 +65,88 @@ fn get_sqlx_exception_ce() -> &'static ext_php_rs::ffi::zend_class_entry {
 pub struct ConnectionException;
 
 /// Thrown when query execution fails.
+///
+/// Carries the rendered SQL and the context of the failed statement.
 #[php_class]
 #[php(name = "Sqlx\\Exceptions\\QueryException")]
 #[php(extends(ce = get_sqlx_exception_ce, stub = "\\Sqlx\\Exceptions\\SqlxException"))]
 #[derive(Default)]
-pub struct QueryException;
+pub struct QueryException {
+    sql: Option<String>,
+    context: QueryContext,
+}
+
+impl QueryException {
+    /// Creates the exception object for a query error, with the message and code
+    /// set through the `Exception` constructor.
+    fn into_object(self, message: &str) -> Option<Zval> {
+        let mut message_zv = Zval::new();
+        message_zv.set_string(message, false).ok()?;
+        let mut code_zv = Zval::new();
+        code_zv.set_long(ErrorCode::Query as i64);
+
+        let object = ZendClassObject::new(self);
+        object
+            .std
+            .try_call_method("__construct", vec![&message_zv, &code_zv])
+            .ok()?;
+        object.into_zval(false).ok()
+    }
+}
+
+#[php_impl]
+impl QueryException {
+    /// Returns the rendered SQL that failed, or `null` if unknown.
+    #[must_use]
+    pub fn get_sql(&self) -> Option<String> {
+        self.sql.clone()
+    }
+
+    /// Returns the parameters the statement was called with, or `null` if unknown.
+    ///
+    /// Values of sensitive-looking placeholders (`password`, `token`, ...), of
+    /// placeholders listed in `OPT_REDACT_PARAMS` and `Sqlx\Secret` values are
+    /// replaced with `'***'`.
+    #[must_use]
+    pub fn get_parameters(&self) -> Option<ParameterValue> {
+        self.context.parameters.clone().map(ParameterValue::Object)
+    }
+
+    /// Returns the name of the driver that ran the statement (`postgres`, `mysql`
+    /// or `mssql`), or `null` if unknown.
+    #[must_use]
+    pub fn get_driver_name(&self) -> Option<String> {
+        self.context.driver.clone()
+    }
+
+    /// Returns the attempt that failed, starting at 1 (greater than 1 if the
+    /// statement was retried), or `null` if unknown.
+    #[must_use]
+    pub fn get_attempt(&self) -> Option<u32> {
+        self.context.attempt
+    }
+
+    /// Returns the position of the statement that failed in `batch()`, or of the
+    /// parameter set that failed in `executeMany()`, starting at 0; `null` for
+    /// other statements.
+    #[must_use]
+    pub fn get_index(&self) -> Option<usize> {
+        self.context.index
+    }
+}
+
+/// Context of a failed statement, exposed by `QueryException`.
+#[derive(Debug, Clone, Default)]
+pub struct QueryContext {
+    /// Parameters the statement was called with, sensitive values masked.
+    pub parameters: Option<ParamsMap>,
+    /// Name of the driver that ran the statement.
+    pub driver: Option<String>,
+    /// Attempt that failed, starting at 1.
+    pub attempt: Option<u32>,
+    /// Position of the failed statement or parameter set, starting at 0.
+    pub index: Option<usize>,
+}
 
 /// Thrown when transaction operations fail.
 #[php_class]
@@ -121,6 +204,13 @@ pub struct TimeoutException;
 #[derive(Default)]
 pub struct PoolExhaustedException;
 
+/// Thrown without contacting the database while the circuit breaker is open.
+#[php_class]
+#[php(name = "Sqlx\\Exceptions\\CircuitOpenException")]
+#[php(extends(ce = get_sqlx_exception_ce, stub = "\\Sqlx\\Exceptions\\SqlxException"))]
+#[derive(Default)]
+pub struct CircuitOpenException;
+
 /// Error codes for categorizing errors in PHP.
 ///
 /// These codes can be used in PHP to programmatically handle specific error types.
@@ -149,6 +239,31 @@ pub enum ErrorCode {
     Timeout = 9,
     /// Pool exhausted
     PoolExhausted = 10,
+    /// Circuit breaker open
+    CircuitOpen = 11,
+}
+
+/// A PHP exception object thrown by a callback, kept to be chained as the
+/// `previous` exception of the error it caused.
+pub struct PhpThrowable(Zval);
+
+// SAFETY: PHP is single-threaded per request. A PhpThrowable is created from a
+// callback's exception and thrown back to PHP on the same thread.
+unsafe impl Send for PhpThrowable {}
+unsafe impl Sync for PhpThrowable {}
+
+impl PhpThrowable {
+    /// Wraps an exception object.
+    #[must_use]
+    pub fn new(exception: ZBox<ZendObject>) -> Option<Self> {
+        exception.into_zval(false).ok().map(Self)
+    }
+}
+
+impl fmt::Debug for PhpThrowable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PhpThrowable")
+    }
 }
 
 /// The main error type for php-sqlx operations.
@@ -169,6 +284,7 @@ pub enum Error {
         message: String,
         query: Option<String>,
         source: Option<Box<dyn std::error::Error + Send + Sync>>,
+        context: Option<Box<QueryContext>>,
     },
 
     /// No active transaction to commit/rollback.
@@ -227,12 +343,37 @@ pub enum Error {
     /// Write operation attempted on readonly connection.
     ReadonlyViolation,
 
+    /// The `onBeforeQuery` hook threw, vetoing the query.
+    Vetoed {
+        message: String,
+        /// The exception the hook threw, chained as `previous`.
+        previous: Option<PhpThrowable>,
+    },
+
+    /// The cost guard refused a statement whose plan exceeds its thresholds.
+    CostExceeded {
+        sql: String,
+        violations: Vec<String>,
+    },
+
+    /// The query allowlist refused a statement whose fingerprint isn't approved.
+    NotAllowlisted { fingerprint: String, sql: String },
+
+    /// The query allowlist refused a statement with inline literals.
+    InlineLiterals { count: usize, sql: String },
+
     /// Connection pool exhausted.
     PoolExhausted { timeout_ms: u64 },
 
     /// Operation timed out.
     Timeout { operation: String, timeout_ms: u64 },
 
+    /// The request deadline set with `setDeadline()` has passed.
+    DeadlineExceeded { operation: String },
+
+    /// The circuit breaker is open; the statement was not sent.
+    CircuitOpen { retry_in_ms: u64 },
+
     /// URL not provided in options.
     UrlRequired,
 
@@ -269,9 +410,14 @@ impl Error {
             | Self::TypeMismatch { .. } => ErrorCode::Parameter,
             Self::Configuration { .. } | Self::UrlRequired => ErrorCode::Configuration,
             Self::InvalidIdentifier { .. } | Self::InvalidSavepoint { .. } => ErrorCode::Validation,
-            Self::ReadonlyViolation => ErrorCode::NotPermitted,
+            Self::ReadonlyViolation
+            | Self::Vetoed { .. }
+            | Self::CostExceeded { .. }
+            | Self::NotAllowlisted { .. }
+            | Self::InlineLiterals { .. } => ErrorCode::NotPermitted,
             Self::PoolExhausted { .. } => ErrorCode::PoolExhausted,
-            Self::Timeout { .. } => ErrorCode::Timeout,
+            Self::Timeout { .. } | Self::DeadlineExceeded { .. } => ErrorCode::Timeout,
+            Self::CircuitOpen { .. } => ErrorCode::CircuitOpen,
             Self::Conversion { .. } | Self::Other(_) => ErrorCode::General,
         }
     }
@@ -290,6 +436,64 @@ impl Error {
         )
     }
 
+    /// Returns true if the database couldn't be reached: a connection error, a
+    /// pool or connect timeout, or an I/O, TLS or closed-pool error from the driver.
+    ///
+    /// These are the failures counted by the circuit breaker.
+    #[must_use]
+    pub fn is_connection_failure(&self) -> bool {
+        match self {
+            Self::Connection { .. } | Self::PoolExhausted { .. } => true,
+            Self::Timeout { operation, .. } => operation != "query",
+            Self::Query { source, .. }
+            | Self::CommitFailed { source, .. }
+            | Self::RollbackFailed { source, .. }
+            | Self::BeginFailed { source, .. } => source
+                .as_deref()
+                .and_then(|source| source.downcast_ref::<sqlx_oldapi::Error>())
+                .is_some_and(is_connection_failure),
+            _ => false,
+        }
+    }
+
+    /// Returns the codes the database reported for this error: the SQLSTATE and,
+    /// on `MySQL`, the vendor error number. Empty if the error didn't come from the
+    /// database.
+    #[must_use]
+    pub fn database_codes(&self) -> Vec<String> {
+        let source = match self {
+            Self::Connection { source, .. }
+            | Self::Query { source, .. }
+            | Self::CommitFailed { source, .. }
+            | Self::RollbackFailed { source, .. }
+            | Self::BeginFailed { source, .. } => source.as_deref(),
+            _ => None,
+        };
+        let Some(sqlx_oldapi::Error::Database(err)) =
+            source.and_then(|source| source.downcast_ref::<sqlx_oldapi::Error>())
+        else {
+            return Vec::new();
+        };
+
+        #[cfg_attr(not(feature = "mysql"), allow(unused_mut))]
+        let mut codes: Vec<String> = err.code().into_iter().map(Into::into).collect();
+        #[cfg(feature = "mysql")]
+        if let Some(err) = err.try_downcast_ref::<sqlx_oldapi::mysql::MySqlDatabaseError>() {
+            codes.push(err.number().to_string());
+        }
+        codes
+    }
+
+    /// Updates the context of a `Query` error, creating it if needed; other
+    /// errors are returned unchanged.
+    #[must_use]
+    pub fn with_query_context(mut self, update: impl FnOnce(&mut QueryContext)) -> Self {
+        if let Self::Query { context, .. } = &mut self {
+            update(context.get_or_insert_with(Box::default));
+        }
+        self
+    }
+
     // Convenience constructors
 
     /// Creates a connection error.
@@ -317,6 +521,7 @@ impl Error {
             message: message.into(),
             query: None,
             source: None,
+            context: None,
         }
     }
 
@@ -326,6 +531,7 @@ impl Error {
             message: message.into(),
             query: Some(sql.into()),
             source: None,
+            context: None,
         }
     }
 
@@ -339,6 +545,7 @@ impl Error {
             message: source_msg,
             query: Some(sql.into()),
             source: Some(Box::new(source)),
+            context: None,
         }
     }
 
@@ -454,6 +661,22 @@ impl fmt::Display for Error {
             }
             Self::InvalidIdentifier { value } => write!(f, "Invalid identifier: `{value}`"),
             Self::ReadonlyViolation => write!(f, "Cannot write to a readonly connection"),
+            Self::Vetoed { message, .. } => {
+                write!(f, "Query vetoed by onBeforeQuery hook: {message}")
+            }
+            Self::CostExceeded { sql, violations } => write!(
+                f,
+                "Query blocked by cost guard: {}\nQuery: {sql}",
+                violations.join("; ")
+            ),
+            Self::NotAllowlisted { fingerprint, sql } => write!(
+                f,
+                "Query not in allowlist (fingerprint `{fingerprint}`)\nQuery: {sql}"
+            ),
+            Self::InlineLiterals { count, sql } => write!(
+                f,
+                "Query contains {count} inline literal(s); bind values through placeholders\nQuery: {sql}"
+            ),
             Self::PoolExhausted { timeout_ms } => {
                 write!(f, "Connection pool exhausted (timeout: {timeout_ms}ms)")
             }
@@ -461,6 +684,14 @@ impl fmt::Display for Error {
                 operation,
                 timeout_ms,
             } => write!(f, "Operation `{operation}` timed out after {timeout_ms}ms"),
+            Self::DeadlineExceeded { operation } => {
+                write!(f, "Request deadline exceeded during `{operation}`")
+            }
+            Self::CircuitOpen { retry_in_ms } => write!(
+                f,
+                "Circuit breaker is open after repeated connection failures; \
+                 retrying in {retry_in_ms}ms"
+            ),
             Self::UrlRequired => write!(f, "Database URL is required"),
             Self::ColumnNotFound { column } => write!(f, "Column `{column}` not found"),
             Self::InvalidSavepoint { name } => write!(f, "Invalid savepoint name: `{name}`"),
@@ -490,21 +721,88 @@ impl From<Error> for PhpException {
         let code = err.code();
         let message = err.to_string();
 
-        let ce = match code {
-            ErrorCode::Connection => ConnectionException::get_metadata().ce(),
-            ErrorCode::Query => QueryException::get_metadata().ce(),
-            ErrorCode::Transaction => TransactionException::get_metadata().ce(),
-            ErrorCode::Parse => ParseException::get_metadata().ce(),
-            ErrorCode::Parameter => ParameterException::get_metadata().ce(),
-            ErrorCode::Configuration => ConfigurationException::get_metadata().ce(),
-            ErrorCode::Validation => ValidationException::get_metadata().ce(),
-            ErrorCode::NotPermitted => NotPermittedException::get_metadata().ce(),
-            ErrorCode::Timeout => TimeoutException::get_metadata().ce(),
-            ErrorCode::PoolExhausted => PoolExhaustedException::get_metadata().ce(),
-            ErrorCode::General => SqlxException::get_metadata().ce(),
+        if let Error::Query { query, context, .. } = err {
+            let exception = QueryException {
+                sql: query,
+                context: context.map(|context| *context).unwrap_or_default(),
+            };
+            let mut php_exception = PhpException::new(
+                message.clone(),
+                code as i32,
+                QueryException::get_metadata().ce(),
+            );
+            php_exception.set_object(exception.into_object(&message));
+            return php_exception;
+        }
+
+        let mut php_exception = PhpException::new(message.clone(), code as i32, exception_ce(code));
+        if let Error::Vetoed {
+            previous: Some(previous),
+            ..
+        } = err
+        {
+            php_exception.set_object(new_exception_object(code, &message, Some(&previous.0)));
+        }
+        php_exception
+    }
+}
+
+impl Error {
+    /// Creates the exception object that would be thrown for this error, for
+    /// APIs that return failures as values, such as `parallel()`.
+    pub fn into_exception_object(self) -> Option<Zval> {
+        let code = self.code();
+        let message = self.to_string();
+
+        if let Error::Query { query, context, .. } = self {
+            let exception = QueryException {
+                sql: query,
+                context: context.map(|context| *context).unwrap_or_default(),
+            };
+            return exception.into_object(&message);
+        }
+
+        let previous = match &self {
+            Error::Vetoed { previous, .. } => previous.as_ref().map(|previous| &previous.0),
+            _ => None,
         };
+        new_exception_object(code, &message, previous)
+    }
+}
+
+/// Creates an exception object of the class thrown for `code`, chaining
+/// `previous` if given.
+fn new_exception_object(code: ErrorCode, message: &str, previous: Option<&Zval>) -> Option<Zval> {
+    let mut message_zv = Zval::new();
+    message_zv.set_string(message, false).ok()?;
+    let mut code_zv = Zval::new();
+    code_zv.set_long(code as i64);
+
+    let mut args: Vec<&dyn IntoZvalDyn> = vec![&message_zv, &code_zv];
+    if let Some(previous) = previous {
+        args.push(previous);
+    }
 
-        PhpException::new(message, code as i32, ce)
+    let object = ZendObject::new(exception_ce(code));
+    object.try_call_method("__construct", args).ok()?;
+    object.into_zval(false).ok()
+}
+
+/// Returns the exception class thrown for errors with `code`.
+fn exception_ce(code: ErrorCode) -> &'static ClassEntry {
+    match code {
+        ErrorCode::Connection => ConnectionException::get_metadata().ce(),
+        ErrorCode::Query => QueryException::get_metadata().ce(),
+        ErrorCode::Transaction => TransactionException::get_metadata().ce(),
+        ErrorCode::Parse => ParseException::get_metadata().ce(),
+        ErrorCode::Parameter => ParameterException::get_metadata().ce(),
+        ErrorCode::Configuration => ConfigurationException::get_metadata().ce(),
+        ErrorCode::Validation => ValidationException::get_metadata().ce(),
+        ErrorCode::NotPermitted => NotPermittedException::get_metadata().ce(),
+        ErrorCode::Timeout => TimeoutException::get_metadata().ce(),
+        ErrorCode::PoolExhausted => PoolExhaustedException::get_metadata().ce(),
+        ErrorCode::CircuitOpen => CircuitOpenException::get_metadata().ce(),
+        ErrorCode::General => SqlxException::get_metadata().ce(),
     }
 }
 
@@ -557,6 +855,7 @@ impl From<sqlx_oldapi::Error> for Error {
                 message: "Row not found".to_string(),
                 query: None,
                 source: Some(Box::new(err)),
+                context: None,
             },
             sqlx_oldapi::Error::ColumnNotFound(name) => Self::ColumnNotFound {
                 column: name.clone(),
@@ -566,11 +865,25 @@ impl From<sqlx_oldapi::Error> for Error {
                 message: err.to_string(),
                 query: None,
                 source: Some(Box::new(err)),
+                context: None,
             },
         }
     }
 }
 
+/// Returns true if a driver error means the database couldn't be reached.
+#[must_use]
+pub fn is_connection_failure(err: &sqlx_oldapi::Error) -> bool {
+    matches!(
+        err,
+        sqlx_oldapi::Error::Io(_)
+            | sqlx_oldapi::Error::Tls(_)
+            | sqlx_oldapi::Error::PoolTimedOut
+            | sqlx_oldapi::Error::PoolClosed
+            | sqlx_oldapi::Error::WorkerCrashed
+    )
+}
+
 /// Result type alias using our Error.
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -588,6 +901,7 @@ pub fn build(module: ModuleBuilder) -> ModuleBuilder {
         .class::<NotPermittedException>()
         .class::<TimeoutException>()
         .class::<PoolExhaustedException>()
+        .class::<CircuitOpenException>()
 }
 
 #[cfg(test)]
@@ -613,6 +927,14 @@ mod tests {
             ErrorCode::Parameter
         );
         assert_eq!(Error::ReadonlyViolation.code(), ErrorCode::NotPermitted);
+        assert_eq!(
+            Error::Vetoed {
+                message: "x".to_string(),
+                previous: None,
+            }
+            .code(),
+            ErrorCode::NotPermitted
+        );
     }
 
     #[test]
@@ -622,6 +944,26 @@ mod tests {
         assert!(err.to_string().contains("SELECT foo FROM bar"));
     }
 
+    #[test]
+    fn test_query_context() {
+        let err = Error::query_with_sql("syntax error", "SELEC 1")
+            .with_query_context(|context| context.attempt = Some(2))
+            .with_query_context(|context| context.driver = Some("postgres".to_string()))
+            .with_query_context(|context| context.index = Some(3));
+        let Error::Query { context, .. } = err else {
+            panic!("expected a query error");
+        };
+        let context = context.unwrap();
+        assert_eq!(context.attempt, Some(2));
+        assert_eq!(context.driver.as_deref(), Some("postgres"));
+        assert_eq!(context.index, Some(3));
+        assert!(context.parameters.is_none());
+
+        let err =
+            Error::NoActiveTransaction.with_query_context(|context| context.attempt = Some(1));
+        assert!(matches!(err, Error::NoActiveTransaction));
+    }
+
     #[test]
     fn test_is_transient() {
         // Transient errors
@@ -646,5 +988,12 @@ mod tests {
             }
             .is_transient()
         );
+        // Retrying past the request deadline is pointless
+        assert!(
+            !Error::DeadlineExceeded {
+                operation: "query".to_string()
+            }
+            .is_transient()
+        );
     }
 }
@@ -0,0 +1,393 @@
+//! `EXPLAIN` / query plan support.
+//!
+//! `$driver->explain()` renders augmented SQL through the normal AST pipeline and
+//! asks the database for its plan in a machine-readable format:
+//!
+//! | DBMS         | Statement                                                     | Plan        |
+//! |--------------|---------------------------------------------------------------|-------------|
+//! | `PostgreSQL` | `EXPLAIN (FORMAT JSON) ...`                                   | JSON        |
+//! | `MySQL`      | `EXPLAIN FORMAT=JSON ...` (`EXPLAIN ANALYZE` for analyze)     | JSON / tree |
+//! | `MSSQL`      | `SET SHOWPLAN_XML ON` (`SET STATISTICS XML ON` for analyze)   | XML string  |
+//!
+//! Alongside the raw plan, a [`PlanSummary`] normalizes the few numbers that are
+//! comparable across databases: total estimated cost, estimated rows, and which
+//! tables are read with a full sequential/table scan.
+//!
+//! # Example
+//!
+//! ```php
+//! $plan = $driver->explain('SELECT * FROM users WHERE email = $email', ['email' => 'a@b.c']);
+//! if ($plan['summary']['seq_scan']) {
+//!     echo "Full scan on: " . implode(', ', $plan['summary']['seq_scan_tables']);
+//! }
+//! ```
+
+use crate::conversion::json_into_zval;
+use crate::error::Error as SqlxError;
+use crate::param_value::ParameterValue;
+use ext_php_rs::types::Zval;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+/// Options accepted by `explain()`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExplainOptions {
+    /// Actually execute the statement and report real timings/row counts.
+    ///
+    /// Write statements are executed inside a transaction that is rolled back.
+    pub analyze: bool,
+    /// Include buffer usage (`PostgreSQL` only; ignored elsewhere).
+    pub buffers: bool,
+}
+
+impl ExplainOptions {
+    /// Parses the `['analyze' => bool, 'buffers' => bool]` options array.
+    ///
+    /// # Errors
+    /// Returns an error for unknown keys or non-boolean values.
+    pub fn from_params(
+        options: Option<BTreeMap<String, ParameterValue>>,
+    ) -> crate::error::Result<Self> {
+        let mut parsed = Self::default();
+        for (key, value) in options.unwrap_or_default() {
+            let ParameterValue::Bool(flag) = value else {
+                return Err(SqlxError::InvalidParameter {
+                    name: key,
+                    expected: "boolean".to_string(),
+                });
+            };
+            match key.as_str() {
+                "analyze" => parsed.analyze = flag,
+                "buffers" => parsed.buffers = flag,
+                _ => {
+                    return Err(SqlxError::Other(format!(
+                        "explain: unknown option `{key}` (expected `analyze` or `buffers`)"
+                    )));
+                }
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// Database-independent summary of a query plan.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlanSummary {
+    /// Total estimated cost of the plan, in the database's own cost units.
+    pub total_cost: Option<f64>,
+    /// Estimated number of rows produced by the statement.
+    pub estimated_rows: Option<f64>,
+    /// Tables read with a sequential (`PostgreSQL`) or full table (`MySQL`, MSSQL) scan.
+    pub seq_scan_tables: Vec<String>,
+}
+
+impl PlanSummary {
+    /// Returns true if the plan contains at least one sequential/table scan.
+    #[must_use]
+    pub fn has_seq_scan(&self) -> bool {
+        !self.seq_scan_tables.is_empty()
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "total_cost": self.total_cost,
+            "estimated_rows": self.estimated_rows,
+            "seq_scan": self.has_seq_scan(),
+            "seq_scan_tables": self.seq_scan_tables,
+        })
+    }
+
+    fn push_table(&mut self, table: &str) {
+        if !self.seq_scan_tables.iter().any(|t| t == table) {
+            self.seq_scan_tables.push(table.to_string());
+        }
+    }
+}
+
+/// A decoded plan together with its summary.
+#[derive(Debug, Clone)]
+pub struct Explained {
+    /// The plan as returned by the database: decoded JSON, or a string for MSSQL
+    /// (XML) and `MySQL` `EXPLAIN ANALYZE` (tree text).
+    pub plan: Value,
+    /// Normalized summary of `plan`.
+    pub summary: PlanSummary,
+}
+
+impl Explained {
+    /// Converts to the PHP array `['plan' => ..., 'summary' => [...]]`.
+    pub fn into_zval(self) -> crate::error::Result<Zval> {
+        json_into_zval(
+            json!({ "plan": self.plan, "summary": self.summary.to_json() }),
+            true,
+        )
+    }
+}
+
+/// Summarizes a `PostgreSQL` `EXPLAIN (FORMAT JSON)` plan.
+#[must_use]
+pub fn summarize_postgres(plan: &Value) -> PlanSummary {
+    let mut summary = PlanSummary::default();
+    let Some(root) = plan.get(0).and_then(|p| p.get("Plan")) else {
+        return summary;
+    };
+    summary.total_cost = root.get("Total Cost").and_then(Value::as_f64);
+    summary.estimated_rows = root.get("Plan Rows").and_then(Value::as_f64);
+
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if node.get("Node Type").and_then(Value::as_str) == Some("Seq Scan")
+            && let Some(table) = node.get("Relation Name").and_then(Value::as_str)
+        {
+            summary.push_table(table);
+        }
+        if let Some(children) = node.get("Plans").and_then(Value::as_array) {
+            stack.extend(children.iter().rev());
+        }
+    }
+    summary
+}
+
+/// Summarizes a `MySQL` `EXPLAIN FORMAT=JSON` plan.
+///
+/// The estimated row count is that of the last table in join order, i.e. the
+/// rows produced by the whole join.
+#[must_use]
+pub fn summarize_mysql(plan: &Value) -> PlanSummary {
+    let mut summary = PlanSummary::default();
+    let Some(block) = plan.get("query_block") else {
+        return summary;
+    };
+    summary.total_cost = block
+        .get("cost_info")
+        .and_then(|c| c.get("query_cost"))
+        .and_then(number_or_string);
+
+    walk_mysql(block, &mut summary);
+    summary
+}
+
+/// Collects table scans and the final row estimate from a `MySQL` plan node.
+fn walk_mysql(value: &Value, summary: &mut PlanSummary) {
+    match value {
+        Value::Object(map) => {
+            if let Some(table) = map.get("table") {
+                let name = table.get("table_name").and_then(Value::as_str);
+                if table.get("access_type").and_then(Value::as_str) == Some("ALL")
+                    && let Some(name) = name
+                {
+                    summary.push_table(name);
+                }
+                if let Some(rows) = table
+                    .get("rows_produced_per_join")
+                    .and_then(number_or_string)
+                {
+                    summary.estimated_rows = Some(rows);
+                }
+            }
+            for (key, child) in map {
+                if key != "table" {
+                    walk_mysql(child, summary);
+                }
+            }
+            if let Some(table) = map.get("table") {
+                walk_mysql(table, summary);
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| walk_mysql(item, summary)),
+        _ => {}
+    }
+}
+
+/// Summarizes `MySQL` `EXPLAIN ANALYZE` tree output.
+///
+/// Cost and rows come from the `(cost=... rows=...)` estimate on the root line;
+/// every `Table scan on <table>` line marks a full table scan.
+#[must_use]
+pub fn summarize_mysql_tree(tree: &str) -> PlanSummary {
+    let mut summary = PlanSummary::default();
+    if let Some(root) = tree.lines().next() {
+        summary.total_cost = tree_estimate(root, "cost=");
+        summary.estimated_rows = tree_estimate(root, "rows=");
+    }
+    for line in tree.lines() {
+        if let Some((_, rest)) = line.split_once("Table scan on ")
+            && let Some(table) = rest.split_whitespace().next()
+        {
+            summary.push_table(table);
+        }
+    }
+    summary
+}
+
+/// Extracts a number following `key` inside the first `(...)` estimate of a tree line.
+fn tree_estimate(line: &str, key: &str) -> Option<f64> {
+    let estimate = line
+        .split_once("(cost=")
+        .map(|(_, rest)| format!("cost={rest}"))?;
+    let start = estimate.find(key)? + key.len();
+    estimate[start..]
+        .split(|c: char| !(c.is_ascii_digit() || c == '.' || c == 'e' || c == '+'))
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Summarizes an MSSQL XML showplan.
+///
+/// Uses a light attribute scan rather than a full XML parser: the statement's
+/// `StatementSubTreeCost`/`StatementEstRows` give cost and rows, and every
+/// `Table Scan`/`Clustered Index Scan` operator contributes the table named in
+/// its first `<Object>` element.
+#[must_use]
+pub fn summarize_mssql(xml: &str) -> PlanSummary {
+    let mut summary = PlanSummary {
+        total_cost: xml_attr(xml, "StatementSubTreeCost").and_then(|v| v.parse().ok()),
+        estimated_rows: xml_attr(xml, "StatementEstRows").and_then(|v| v.parse().ok()),
+        ..PlanSummary::default()
+    };
+    let mut rest = xml;
+    while let Some(pos) = rest.find("PhysicalOp=\"") {
+        rest = &rest[pos + "PhysicalOp=\"".len()..];
+        let op = rest.split('"').next().unwrap_or_default();
+        if (op == "Table Scan" || op == "Clustered Index Scan")
+            && let Some(table) = xml_attr(rest, "Table")
+        {
+            summary.push_table(table.trim_start_matches('[').trim_end_matches(']'));
+        }
+    }
+    summary
+}
+
+/// Returns the value of the first `name="..."` attribute in `xml`.
+fn xml_attr<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    let needle = format!(" {name}=\"");
+    let start = xml.find(&needle)? + needle.len();
+    xml[start..].split('"').next()
+}
+
+/// `MySQL` reports costs and row estimates either as numbers or as strings.
+fn number_or_string(value: &Value) -> Option<f64> {
+    value
+        .as_f64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+}
+
+/// Returns the leading keyword of `sql`, upper-cased.
+///
+/// Leading whitespace, comments and parentheses are skipped.
+#[must_use]
+pub fn leading_keyword(sql: &str) -> String {
+    let mut rest = sql;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '(');
+        if let Some(after) = rest.strip_prefix("--") {
+            rest = after.split_once('\n').map_or("", |(_, tail)| tail);
+        } else if let Some(after) = rest.strip_prefix("/*") {
+            rest = after.split_once("*/").map_or("", |(_, tail)| tail);
+        } else {
+            break;
+        }
+    }
+    rest.chars()
+        .take_while(char::is_ascii_alphabetic)
+        .collect::<String>()
+        .to_ascii_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_options() {
+        let mut options = BTreeMap::new();
+        options.insert("analyze".to_string(), ParameterValue::Bool(true));
+        assert_eq!(
+            ExplainOptions::from_params(Some(options)).unwrap(),
+            ExplainOptions {
+                analyze: true,
+                buffers: false
+            }
+        );
+
+        let mut options = BTreeMap::new();
+        options.insert("verbose".to_string(), ParameterValue::Bool(true));
+        assert!(ExplainOptions::from_params(Some(options)).is_err());
+
+        let mut options = BTreeMap::new();
+        options.insert("analyze".to_string(), ParameterValue::Int(1));
+        assert!(ExplainOptions::from_params(Some(options)).is_err());
+    }
+
+    #[test]
+    fn summarizes_postgres_plan() {
+        let plan: Value = serde_json::from_str(
+            r#"[{"Plan": {"Node Type": "Hash Join", "Total Cost": 120.5, "Plan Rows": 42,
+                "Plans": [
+                    {"Node Type": "Seq Scan", "Relation Name": "orders", "Total Cost": 80.0, "Plan Rows": 1000},
+                    {"Node Type": "Hash", "Plans": [
+                        {"Node Type": "Index Scan", "Relation Name": "users", "Total Cost": 8.3, "Plan Rows": 1}
+                    ]}
+                ]}}]"#,
+        )
+        .unwrap();
+        let summary = summarize_postgres(&plan);
+        assert_eq!(summary.total_cost, Some(120.5));
+        assert_eq!(summary.estimated_rows, Some(42.0));
+        assert_eq!(summary.seq_scan_tables, vec!["orders".to_string()]);
+        assert!(summary.has_seq_scan());
+    }
+
+    #[test]
+    fn summarizes_mysql_plan() {
+        let plan: Value = serde_json::from_str(
+            r#"{"query_block": {"select_id": 1, "cost_info": {"query_cost": "12.75"},
+                "nested_loop": [
+                    {"table": {"table_name": "u", "access_type": "ALL", "rows_produced_per_join": 10}},
+                    {"table": {"table_name": "o", "access_type": "ref", "rows_produced_per_join": 25}}
+                ]}}"#,
+        )
+        .unwrap();
+        let summary = summarize_mysql(&plan);
+        assert_eq!(summary.total_cost, Some(12.75));
+        assert_eq!(summary.estimated_rows, Some(25.0));
+        assert_eq!(summary.seq_scan_tables, vec!["u".to_string()]);
+    }
+
+    #[test]
+    fn summarizes_mysql_tree() {
+        let tree = "-> Filter: (u.name = 'a')  (cost=1.25 rows=2) (actual time=0.05..0.06 rows=1 loops=1)\n    \
+                    -> Table scan on u  (cost=1.25 rows=10) (actual time=0.04..0.05 rows=10 loops=1)\n";
+        let summary = summarize_mysql_tree(tree);
+        assert_eq!(summary.total_cost, Some(1.25));
+        assert_eq!(summary.estimated_rows, Some(2.0));
+        assert_eq!(summary.seq_scan_tables, vec!["u".to_string()]);
+    }
+
+    #[test]
+    fn summarizes_mssql_plan() {
+        let xml = r#"<ShowPlanXML><BatchSequence><Batch><Statements>
+            <StmtSimple StatementText="SELECT * FROM users" StatementSubTreeCost="0.0032831" StatementEstRows="7">
+            <QueryPlan><RelOp NodeId="0" PhysicalOp="Table Scan" LogicalOp="Table Scan" EstimateRows="7">
+            <TableScan><Object Database="[app]" Schema="[dbo]" Table="[users]" /></TableScan>
+            </RelOp></QueryPlan></StmtSimple></Statements></Batch></BatchSequence></ShowPlanXML>"#;
+        let summary = summarize_mssql(xml);
+        assert_eq!(summary.total_cost, Some(0.003_283_1));
+        assert_eq!(summary.estimated_rows, Some(7.0));
+        assert_eq!(summary.seq_scan_tables, vec!["users".to_string()]);
+    }
+
+    #[test]
+    fn reads_leading_keyword() {
+        assert_eq!(
+            leading_keyword("  -- comment\n (select * from t)"),
+            "SELECT"
+        );
+        assert_eq!(
+            leading_keyword("/* x */ WITH a AS (SELECT 1) SELECT 1"),
+            "WITH"
+        );
+        assert_eq!(leading_keyword("insert into t values (1)"), "INSERT");
+    }
+}
@@ -0,0 +1,412 @@
+//! `php.ini` directives.
+//!
+//! The `sqlx.*` directives override the compiled-in defaults that every driver
+//! starts from, so operators can tune pools per environment without touching
+//! application code. Options passed to the driver constructor still win.
+//!
+//! ```ini
+//! sqlx.default_url = "postgres://app@db.internal/app"
+//! sqlx.max_connections = 32
+//! ; durations take a unit; a bare integer is seconds where allowed
+//! sqlx.acquire_timeout = 2
+//! sqlx.query_timeout = 5s
+//! ; sub-second settings require a unit
+//! sqlx.slow_query_threshold = 500ms
+//! ```
+//!
+//! Directives are registered at `MINIT` with `PHP_INI_SYSTEM` permission and read
+//! once, on the first driver construction. An empty value keeps the compiled
+//! default; an invalid value keeps it too, and raises a warning.
+
+use crate::slow_query_log::SlowQueryLogTarget;
+use crate::{
+    DEFAULT_ASSOC_ARRAYS, DEFAULT_AST_CACHE_SHARD_COUNT, DEFAULT_AST_CACHE_SHARD_SIZE,
+    DEFAULT_MAX_CONNECTIONS, DEFAULT_MIN_CONNECTIONS, DEFAULT_RETRY_INITIAL_BACKOFF,
+    DEFAULT_RETRY_MAX_ATTEMPTS, DEFAULT_RETRY_MAX_BACKOFF, DEFAULT_RETRY_MULTIPLIER,
+};
+use ext_php_rs::error::php_error;
+use ext_php_rs::flags::{ErrorType, IniEntryPermission};
+use ext_php_rs::zend::{ExecutorGlobals, IniEntryDef};
+use std::num::NonZeroU32;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Every directive registered by the extension.
+pub const DIRECTIVES: [&str; 16] = [
+    "sqlx.default_url",
+    "sqlx.max_connections",
+    "sqlx.min_connections",
+    "sqlx.max_lifetime",
+    "sqlx.idle_timeout",
+    "sqlx.acquire_timeout",
+    "sqlx.query_timeout",
+    "sqlx.assoc_arrays",
+    "sqlx.ast_cache_shard_count",
+    "sqlx.ast_cache_shard_size",
+    "sqlx.retry_max_attempts",
+    "sqlx.retry_initial_backoff",
+    "sqlx.retry_max_backoff",
+    "sqlx.retry_multiplier",
+    "sqlx.slow_query_threshold",
+    "sqlx.slow_query_log",
+];
+
+/// Set once the directives are registered; until then (e.g. in unit tests) the
+/// compiled defaults are used.
+static REGISTERED: AtomicBool = AtomicBool::new(false);
+
+/// Defaults read from `php.ini`, resolved on first use.
+static DEFAULTS: OnceLock<IniDefaults> = OnceLock::new();
+
+/// Registers the `sqlx.*` directives. Called from `MINIT`.
+pub fn register(module_number: i32) {
+    let entries = DIRECTIVES
+        .iter()
+        .map(|name| {
+            IniEntryDef::new(
+                (*name).to_string(),
+                String::new(),
+                &IniEntryPermission::System,
+            )
+        })
+        .collect();
+    IniEntryDef::register(entries, module_number);
+    REGISTERED.store(true, Ordering::Release);
+}
+
+/// Returns the effective driver defaults: `php.ini` values over compiled defaults.
+pub fn defaults() -> IniDefaults {
+    if !REGISTERED.load(Ordering::Acquire) {
+        return IniDefaults::default();
+    }
+    DEFAULTS
+        .get_or_init(|| {
+            let values = ExecutorGlobals::get().ini_values();
+            let (defaults, warnings) =
+                IniDefaults::from_lookup(|name| values.get(name).cloned().flatten());
+            for warning in &warnings {
+                php_error(&ErrorType::Warning, warning);
+            }
+            defaults
+        })
+        .clone()
+}
+
+/// Driver defaults that can be overridden from `php.ini`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IniDefaults {
+    /// `sqlx.default_url`: URL used when the driver options don't name one.
+    pub url: Option<String>,
+    /// `sqlx.max_connections`
+    pub max_connections: NonZeroU32,
+    /// `sqlx.min_connections`
+    pub min_connections: u32,
+    /// `sqlx.max_lifetime`
+    pub max_lifetime: Option<Duration>,
+    /// `sqlx.idle_timeout`
+    pub idle_timeout: Option<Duration>,
+    /// `sqlx.acquire_timeout`
+    pub acquire_timeout: Option<Duration>,
+    /// `sqlx.query_timeout`
+    pub query_timeout: Option<Duration>,
+    /// `sqlx.assoc_arrays`
+    pub assoc_arrays: bool,
+    /// `sqlx.ast_cache_shard_count`
+    pub ast_cache_shard_count: usize,
+    /// `sqlx.ast_cache_shard_size`
+    pub ast_cache_shard_size: usize,
+    /// `sqlx.retry_max_attempts`
+    pub retry_max_attempts: u32,
+    /// `sqlx.retry_initial_backoff`
+    pub retry_initial_backoff: Duration,
+    /// `sqlx.retry_max_backoff`
+    pub retry_max_backoff: Duration,
+    /// `sqlx.retry_multiplier`
+    pub retry_multiplier: f64,
+    /// `sqlx.slow_query_threshold`
+    pub slow_query_threshold: Option<Duration>,
+    /// `sqlx.slow_query_log`: file path or `stderr`
+    pub slow_query_log: SlowQueryLogTarget,
+}
+
+impl Default for IniDefaults {
+    fn default() -> Self {
+        Self {
+            url: None,
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            min_connections: DEFAULT_MIN_CONNECTIONS,
+            max_lifetime: None,
+            idle_timeout: None,
+            acquire_timeout: None,
+            query_timeout: None,
+            assoc_arrays: DEFAULT_ASSOC_ARRAYS,
+            ast_cache_shard_count: DEFAULT_AST_CACHE_SHARD_COUNT,
+            ast_cache_shard_size: DEFAULT_AST_CACHE_SHARD_SIZE,
+            retry_max_attempts: DEFAULT_RETRY_MAX_ATTEMPTS,
+            retry_initial_backoff: DEFAULT_RETRY_INITIAL_BACKOFF,
+            retry_max_backoff: DEFAULT_RETRY_MAX_BACKOFF,
+            retry_multiplier: DEFAULT_RETRY_MULTIPLIER,
+            slow_query_threshold: None,
+            slow_query_log: SlowQueryLogTarget::Stderr,
+        }
+    }
+}
+
+impl IniDefaults {
+    /// Resolves the defaults from raw directive values. Returns the warnings for
+    /// values that couldn't be parsed (those keep their compiled default).
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> (Self, Vec<String>) {
+        let compiled = Self::default();
+        let mut reader = Reader {
+            lookup,
+            warnings: Vec::new(),
+        };
+        let defaults = Self {
+            url: reader.read("sqlx.default_url", compiled.url, |v| {
+                Some(Some(v.to_string()))
+            }),
+            max_connections: reader.read("sqlx.max_connections", compiled.max_connections, |v| {
+                v.parse().ok()
+            }),
+            min_connections: reader.read("sqlx.min_connections", compiled.min_connections, |v| {
+                v.parse().ok()
+            }),
+            max_lifetime: reader.read("sqlx.max_lifetime", compiled.max_lifetime, |v| {
+                parse_duration(v).map(Some)
+            }),
+            idle_timeout: reader.read("sqlx.idle_timeout", compiled.idle_timeout, |v| {
+                parse_duration(v).map(Some)
+            }),
+            acquire_timeout: reader.read("sqlx.acquire_timeout", compiled.acquire_timeout, |v| {
+                parse_duration(v).map(Some)
+            }),
+            query_timeout: reader.read("sqlx.query_timeout", compiled.query_timeout, |v| {
+                parse_duration(v).map(Some)
+            }),
+            assoc_arrays: reader.read("sqlx.assoc_arrays", compiled.assoc_arrays, parse_bool),
+            ast_cache_shard_count: reader.read(
+                "sqlx.ast_cache_shard_count",
+                compiled.ast_cache_shard_count,
+                |v| v.parse().ok(),
+            ),
+            ast_cache_shard_size: reader.read(
+                "sqlx.ast_cache_shard_size",
+                compiled.ast_cache_shard_size,
+                |v| v.parse().ok(),
+            ),
+            retry_max_attempts: reader.read(
+                "sqlx.retry_max_attempts",
+                compiled.retry_max_attempts,
+                |v| v.parse().ok(),
+            ),
+            retry_initial_backoff: reader.read(
+                "sqlx.retry_initial_backoff",
+                compiled.retry_initial_backoff,
+                parse_duration_with_unit,
+            ),
+            retry_max_backoff: reader.read(
+                "sqlx.retry_max_backoff",
+                compiled.retry_max_backoff,
+                parse_duration_with_unit,
+            ),
+            retry_multiplier: reader.read(
+                "sqlx.retry_multiplier",
+                compiled.retry_multiplier,
+                |v| v.parse::<f64>().ok().filter(|m| m.is_finite() && *m > 0.0),
+            ),
+            slow_query_threshold: reader.read(
+                "sqlx.slow_query_threshold",
+                compiled.slow_query_threshold,
+                |v| parse_duration_with_unit(v).map(Some),
+            ),
+            slow_query_log: reader.read("sqlx.slow_query_log", compiled.slow_query_log, |v| {
+                Some(SlowQueryLogTarget::parse(v))
+            }),
+        };
+        (defaults, reader.warnings)
+    }
+
+    /// Human-readable `(directive, effective value)` pairs, for `phpinfo()`.
+    pub fn describe(&self) -> Vec<(&'static str, String)> {
+        fn duration(value: Option<Duration>) -> String {
+            value.map_or_else(|| "none".to_string(), |d| format!("{d:?}"))
+        }
+        vec![
+            (
+                "sqlx.default_url",
+                self.url
+                    .as_deref()
+                    .map_or_else(|| "none".to_string(), crate::utils::redact_url::redact_url),
+            ),
+            ("sqlx.max_connections", self.max_connections.to_string()),
+            ("sqlx.min_connections", self.min_connections.to_string()),
+            ("sqlx.max_lifetime", duration(self.max_lifetime)),
+            ("sqlx.idle_timeout", duration(self.idle_timeout)),
+            ("sqlx.acquire_timeout", duration(self.acquire_timeout)),
+            ("sqlx.query_timeout", duration(self.query_timeout)),
+            ("sqlx.assoc_arrays", self.assoc_arrays.to_string()),
+            (
+                "sqlx.ast_cache_shard_count",
+                self.ast_cache_shard_count.to_string(),
+            ),
+            (
+                "sqlx.ast_cache_shard_size",
+                self.ast_cache_shard_size.to_string(),
+            ),
+            (
+                "sqlx.retry_max_attempts",
+                self.retry_max_attempts.to_string(),
+            ),
+            (
+                "sqlx.retry_initial_backoff",
+                duration(Some(self.retry_initial_backoff)),
+            ),
+            (
+                "sqlx.retry_max_backoff",
+                duration(Some(self.retry_max_backoff)),
+            ),
+            ("sqlx.retry_multiplier", self.retry_multiplier.to_string()),
+            (
+                "sqlx.slow_query_threshold",
+                duration(self.slow_query_threshold),
+            ),
+            (
+                "sqlx.slow_query_log",
+                match &self.slow_query_log {
+                    SlowQueryLogTarget::Stderr => "stderr".to_string(),
+                    SlowQueryLogTarget::File(path) => path.clone(),
+                },
+            ),
+        ]
+    }
+}
+
+/// Reads directives, collecting a warning for each invalid value.
+struct Reader<F> {
+    lookup: F,
+    warnings: Vec<String>,
+}
+
+impl<F: Fn(&str) -> Option<String>> Reader<F> {
+    fn read<T>(&mut self, name: &str, default: T, parse: impl FnOnce(&str) -> Option<T>) -> T {
+        let Some(raw) = (self.lookup)(name) else {
+            return default;
+        };
+        let raw = raw.trim();
+        if raw.is_empty() {
+            return default;
+        }
+        parse(raw).unwrap_or_else(|| {
+            self.warnings.push(format!(
+                "Sqlx: invalid php.ini value `{raw}` for `{name}`, using the compiled default"
+            ));
+            default
+        })
+    }
+}
+
+/// Parses a duration string (`"500ms"`, `"2s"`), or a bare integer of seconds.
+fn parse_duration(value: &str) -> Option<Duration> {
+    match value.parse::<u64>() {
+        Ok(n) => Some(Duration::from_secs(n)),
+        Err(_) => parse_duration::parse(value).ok(),
+    }
+}
+
+/// Parses a duration string that spells out its unit, for the directives whose
+/// values are usually below a second: a bare integer is refused rather than read
+/// as seconds, since `100` is almost certainly meant as milliseconds.
+fn parse_duration_with_unit(value: &str) -> Option<Duration> {
+    if value.parse::<u64>().is_ok() {
+        return None;
+    }
+    parse_duration::parse(value).ok()
+}
+
+/// Parses a `php.ini` boolean (`1`/`0`, `on`/`off`, `yes`/`no`, `true`/`false`).
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "on" | "yes" | "true" => Some(true),
+        "0" | "off" | "no" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn resolve(values: &[(&str, &str)]) -> (IniDefaults, Vec<String>) {
+        let values: HashMap<String, String> = values
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect();
+        IniDefaults::from_lookup(|name| values.get(name).cloned())
+    }
+
+    #[test]
+    fn empty_values_keep_compiled_defaults() {
+        let (defaults, warnings) =
+            resolve(&[("sqlx.max_connections", ""), ("sqlx.assoc_arrays", "")]);
+        assert_eq!(defaults, IniDefaults::default());
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn values_override_compiled_defaults() {
+        let (defaults, warnings) = resolve(&[
+            ("sqlx.default_url", "postgres://localhost/app"),
+            ("sqlx.max_connections", "32"),
+            ("sqlx.assoc_arrays", "On"),
+            ("sqlx.acquire_timeout", "3"),
+            ("sqlx.query_timeout", "250ms"),
+            ("sqlx.slow_query_threshold", "2s"),
+            ("sqlx.retry_initial_backoff", "50ms"),
+            ("sqlx.slow_query_log", "/var/log/sqlx-slow.log"),
+        ]);
+        assert!(warnings.is_empty());
+        assert_eq!(defaults.url.as_deref(), Some("postgres://localhost/app"));
+        assert_eq!(defaults.max_connections.get(), 32);
+        assert!(defaults.assoc_arrays);
+        assert_eq!(defaults.acquire_timeout, Some(Duration::from_secs(3)));
+        assert_eq!(defaults.query_timeout, Some(Duration::from_millis(250)));
+        assert_eq!(defaults.slow_query_threshold, Some(Duration::from_secs(2)));
+        assert_eq!(defaults.retry_initial_backoff, Duration::from_millis(50));
+        assert_eq!(
+            defaults.slow_query_log,
+            SlowQueryLogTarget::File("/var/log/sqlx-slow.log".to_string())
+        );
+    }
+
+    #[test]
+    fn sub_second_directives_require_a_unit() {
+        let (defaults, warnings) = resolve(&[
+            ("sqlx.retry_initial_backoff", "100"),
+            ("sqlx.retry_max_backoff", "2"),
+            ("sqlx.slow_query_threshold", "500"),
+        ]);
+        assert_eq!(
+            defaults.retry_initial_backoff,
+            DEFAULT_RETRY_INITIAL_BACKOFF
+        );
+        assert_eq!(defaults.retry_max_backoff, DEFAULT_RETRY_MAX_BACKOFF);
+        assert_eq!(defaults.slow_query_threshold, None);
+        assert_eq!(warnings.len(), 3);
+    }
+
+    #[test]
+    fn invalid_values_fall_back_with_warning() {
+        let (defaults, warnings) = resolve(&[
+            ("sqlx.max_connections", "0"),
+            ("sqlx.retry_multiplier", "fast"),
+            ("sqlx.min_connections", "2"),
+        ]);
+        assert_eq!(defaults.max_connections, DEFAULT_MAX_CONNECTIONS);
+        assert!(defaults.retry_multiplier.eq(&DEFAULT_RETRY_MULTIPLIER));
+        assert_eq!(defaults.min_connections, 2);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("sqlx.max_connections"));
+    }
+}
@@ -16,13 +16,47 @@
 //! and query builders.
 
 use crate::error::Error as SqlxError;
-use std::time::Duration;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How retry delays are randomized (`OPT_RETRY_JITTER`).
+///
+/// Without jitter, workers that failed together retry together and hit a
+/// recovering database in waves.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RetryJitter {
+    /// Plain exponential backoff.
+    #[default]
+    None,
+    /// A random delay between zero and the exponential backoff.
+    Full,
+    /// A random delay between the initial backoff and three times the previous
+    /// delay, capped at the maximum backoff. Ignores the multiplier.
+    Decorrelated,
+}
+
+impl RetryJitter {
+    /// Parses an `OPT_RETRY_JITTER` value: `"none"`, `"full"` or `"decorrelated"`.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        if value.eq_ignore_ascii_case("none") {
+            Some(Self::None)
+        } else if value.eq_ignore_ascii_case("full") {
+            Some(Self::Full)
+        } else if value.eq_ignore_ascii_case("decorrelated") {
+            Some(Self::Decorrelated)
+        } else {
+            None
+        }
+    }
+}
 
 /// Retry policy configuration for transient database failures.
 ///
 /// When enabled (`max_attempts` > 0), transient errors like connection drops,
-/// pool exhaustion, and timeouts will be automatically retried with exponential
-/// backoff.
+/// pool exhaustion, and timeouts, and database errors whose code is listed in
+/// `retryable_codes`, will be automatically retried with exponential backoff.
 #[derive(Debug, Clone)]
 pub struct RetryPolicy {
     /// Maximum number of retry attempts (0 = disabled).
@@ -33,17 +67,54 @@ pub struct RetryPolicy {
     pub max_backoff: Duration,
     /// Multiplier for exponential backoff (e.g., 2.0 doubles each time).
     pub multiplier: f64,
+    /// How delays are randomized.
+    pub jitter: RetryJitter,
+    /// SQLSTATEs and vendor error codes that are retried in addition to
+    /// transient errors (e.g. `40001`, `40P01`, `1213`).
+    pub retryable_codes: Vec<String>,
+    /// Total time the retries of one statement may take, attempts included;
+    /// `None` means unlimited.
+    pub budget: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 0,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: RetryJitter::None,
+            retryable_codes: Vec::new(),
+            budget: None,
+        }
+    }
 }
 
 impl RetryPolicy {
+    /// Returns true if `error` is transient or carries a code listed in
+    /// `retryable_codes`.
+    #[must_use]
+    pub fn is_retryable(&self, error: &SqlxError) -> bool {
+        error.is_transient()
+            || error.database_codes().iter().any(|code| {
+                self.retryable_codes
+                    .iter()
+                    .any(|retryable| retryable.eq_ignore_ascii_case(code))
+            })
+    }
+
     /// Returns the backoff duration for the given attempt, or None if no retry should occur.
     ///
+    /// The duration is the plain exponential backoff; see [`Self::next_delay`]
+    /// for jitter and the time budget.
+    ///
     /// Returns `None` if:
     /// - `attempt >= max_attempts`
-    /// - The error is not transient
+    /// - The error is not retryable
     #[must_use]
     pub fn should_retry(&self, attempt: u32, error: &SqlxError) -> Option<Duration> {
-        if attempt >= self.max_attempts || !error.is_transient() {
+        if attempt >= self.max_attempts || !self.is_retryable(error) {
             return None;
         }
         // Use saturating conversion to avoid wrap-around for very high attempt counts
@@ -52,6 +123,39 @@ impl RetryPolicy {
         Some(backoff.min(self.max_backoff))
     }
 
+    /// Returns the delay before the next attempt, or None if no retry should occur.
+    ///
+    /// - `previous`: the delay before the failed attempt (zero after the first one).
+    /// - `elapsed`: time spent on the statement so far, attempts and delays included.
+    /// - `random`: a sample from `[0, 1)` used for jitter.
+    ///
+    /// Besides the cases of [`Self::should_retry`], returns `None` if waiting
+    /// would exceed the time budget.
+    #[must_use]
+    pub fn next_delay(
+        &self,
+        attempt: u32,
+        error: &SqlxError,
+        previous: Duration,
+        elapsed: Duration,
+        random: f64,
+    ) -> Option<Duration> {
+        let backoff = self.should_retry(attempt, error)?;
+        let delay = match self.jitter {
+            RetryJitter::None => backoff,
+            RetryJitter::Full => backoff.mul_f64(random),
+            RetryJitter::Decorrelated => {
+                let low = self.initial_backoff;
+                let high = previous.max(low).saturating_mul(3);
+                (low + high.saturating_sub(low).mul_f64(random)).min(self.max_backoff)
+            }
+        };
+        match self.budget {
+            Some(budget) if elapsed.saturating_add(delay) > budget => None,
+            _ => Some(delay),
+        }
+    }
+
     /// Returns true if retry is disabled.
     #[must_use]
     #[inline]
@@ -60,6 +164,25 @@ impl RetryPolicy {
     }
 }
 
+/// Returns a random sample from `[0, 1)` for retry jitter.
+///
+/// `RandomState` is seeded randomly per thread, which is enough to spread
+/// workers apart; it is not meant to be unpredictable.
+#[must_use]
+pub fn jitter_sample() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    // The top 53 bits fill an f64 mantissa exactly.
+    #[allow(clippy::cast_precision_loss)]
+    let sample = (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64;
+    sample
+}
+
 /// Generates the inner database driver implementation.
 ///
 /// This macro creates the core driver struct with connection pooling, query execution,
@@ -88,22 +211,40 @@ macro_rules! php_sqlx_impl_driver_inner {
             pool::{Pool, PoolConnection, PoolOptions},
             $database,
         };
+        use std::borrow::Cow;
         use std::collections::BTreeMap;
-        use std::sync::RwLock;
         use std::sync::atomic::AtomicUsize;
+        use std::sync::{Arc, RwLock};
         use threadsafe_lru::LruCache;
         use $crate::{
             RUNTIME,
             ast::{Ast, Settings},
+            batch::{BatchKind, BatchOutcome, BatchStatement},
             conversion::Conversion,
+            circuit_breaker::{CircuitBreaker, CircuitState},
+            cost_guard::CostGuard,
+            deadline::Deadline,
             error::Error as SqlxError,
-            inner_driver::RetryPolicy,
+            explain::{ExplainOptions, Explained},
+            inner_driver::{RetryPolicy, jitter_sample},
             options::DriverInnerOptions,
-            param_value::{ParameterValue, utils::bind_values},
-            query_hook::{QueryHook, QueryTimer},
+            param_value::{ParameterValue, quote::quote_identifier, utils::bind_values},
+            pending::{Completion, StatementTask, within},
+            profiler::Profiler,
+            query_allowlist::QueryAllowlist,
+            query_hook::{
+                BeforeQueryHook, DetachedQueryTimer, Instrumentation, QueryHook, QueryKind,
+                QueryTimer, RetryHook,
+            },
+            query_tags::QueryTags,
+            request_owner::RequestOwner,
+            secret::mask_parameters,
+            slow_query_log::SlowQueryLog,
+            statement_cache::{self, CacheOutcome, PreparedStatements},
             utils::{
                 hashmap_fold::{fold_into_zend_hashmap, fold_into_zend_hashmap_grouped},
                 ident::is_valid_ident,
+                redact_url::redact_url,
                 types::ColumnArgument,
             },
         };
@@ -130,12 +271,56 @@ macro_rules! php_sqlx_impl_driver_inner {
             pub tx_stack: RwLock<Vec<Transaction<'static, $database>>>,
             /// Pinned connection for session-scoped operations (`LAST_INSERT_ID`, temp tables, etc.).
             pub pinned_conn: RwLock<Option<PoolConnection<$database>>>,
+            /// The request that began the transactions on `tx_stack`.
+            pub transaction_owner: RequestOwner,
+            /// The request that pinned `pinned_conn`.
+            pub pinned_owner: RequestOwner,
             /// AST rendering settings (placeholder style, collapsible IN, etc.).
             pub settings: Settings,
             /// Retry policy for transient failures.
             pub retry_policy: RetryPolicy,
             /// Query profiling hook for logging and monitoring.
             pub query_hook: QueryHook,
+            /// Hook run before every statement; may rewrite, veto or annotate it.
+            pub before_query_hook: BeforeQueryHook,
+            /// Hook called before each retry of a failed statement.
+            pub retry_hook: RetryHook,
+            /// `sqlcommenter` tags appended to every rendered statement.
+            pub query_tags: QueryTags,
+            /// Slow query log sink, if `OPT_SLOW_QUERY_THRESHOLD` is set.
+            pub slow_query_log: Option<SlowQueryLog>,
+            /// Plan-based cost guard, if `OPT_COST_GUARD` is set.
+            pub cost_guard: Option<CostGuard>,
+            /// Circuit breaker, if `OPT_CIRCUIT_BREAKER` is set.
+            pub circuit_breaker: Option<Arc<CircuitBreaker>>,
+            /// Request deadline set by `setDeadline()`, shared with the pools' hooks.
+            pub deadline: Arc<Deadline>,
+            /// Number of connections marked (`DISCARD_MARK_QUERY`) to be closed when
+            /// they go back to the pool, shared with the pools' hooks.
+            pub pending_discards: Arc<AtomicUsize>,
+            /// When the pools were created, for `DriverFactory::persistentPools()`.
+            pub created_at: std::time::Instant,
+            /// Per-request query profiler started by `profile()`.
+            pub profiler: Profiler,
+            /// The request that last set a hook, the deadline or a profile.
+            pub settings_owner: RequestOwner,
+            /// Query firewall, if `OPT_QUERY_ALLOWLIST` is set.
+            pub query_allowlist: Option<QueryAllowlist>,
+            /// Statement cache hits and misses of the prepared queries.
+            pub prepared_statements: PreparedStatements,
+            /// Statements a connection's cache holds, the smallest of the pools'.
+            statement_cache_capacity: usize,
+        }
+
+        /// A statement of a batch, rendered: its kind, SQL and bound values, and what
+        /// its query hook timer is started with once the statement is sent.
+        struct BatchEntry {
+            kind: BatchKind,
+            sql: String,
+            values: Vec<ParameterValue>,
+            sql_inline: Option<String>,
+            meta: Option<Zval>,
+            instrumentation: Instrumentation,
         }
 
         /// Type alias for the row stream used in lazy iteration.
@@ -144,8 +329,13 @@ macro_rules! php_sqlx_impl_driver_inner {
             Result<<$database as sqlx_oldapi::Database>::Row, $crate::error::Error>,
         >;
 
-        /// Result type for `query_stream` containing the receiver and cancellation token.
-        pub type StreamResult = (RowReceiver, tokio_util::sync::CancellationToken);
+        /// Result type for `query_stream` containing the receiver, cancellation token
+        /// and the timer the `QueryResult` reports through once it is exhausted or dropped.
+        pub type StreamResult = (
+            RowReceiver,
+            tokio_util::sync::CancellationToken,
+            Option<DetachedQueryTimer>,
+        );
 
         impl $struct {
             /// Creates a new inner driver with the given configuration.
@@ -153,16 +343,28 @@ macro_rules! php_sqlx_impl_driver_inner {
             /// This establishes the connection pool and initializes the AST cache.
             /// The URL must be set in options or an error is returned.
             pub fn new(options: DriverInnerOptions) -> $crate::error::Result<Self> {
+                let deadline = Arc::new(Deadline::new());
+                let pending_discards = Arc::new(AtomicUsize::new(0));
                 let mut pool_options = PoolOptions::<$database>::new()
                     .max_connections(options.max_connections.into())
                     .min_connections(options.min_connections)
                     .max_lifetime(options.max_lifetime)
                     .idle_timeout(options.idle_timeout)
                     .test_before_acquire(options.test_before_acquire);
+                let time_zone_query = options.time_zone.as_ref().and_then(set_time_zone_query);
+                pool_options = Self::with_session_hooks(
+                    pool_options,
+                    &deadline,
+                    &pending_discards,
+                    time_zone_query.clone(),
+                );
                 if let Some(acquire_timeout) = options.acquire_timeout {
                     pool_options = pool_options.acquire_timeout(acquire_timeout);
                 }
                 let url = options.url.clone().ok_or(SqlxError::UrlRequired)?;
+                let url = Self::with_statement_cache(&url, &options);
+                let mut statement_cache_capacity =
+                    statement_cache::capacity(&url).unwrap_or(options.statement_cache_capacity);
                 let pool = RUNTIME
                     .block_on(pool_options.connect(url.as_str()))
                     .map_err(|e| SqlxError::connection_with_source("Failed to connect", e))?;
@@ -181,8 +383,18 @@ macro_rules! php_sqlx_impl_driver_inner {
                         replica_pool_options =
                             replica_pool_options.acquire_timeout(acquire_timeout);
                     }
+                    replica_pool_options = Self::with_session_hooks(
+                        replica_pool_options,
+                        &deadline,
+                        &pending_discards,
+                        time_zone_query.clone(),
+                    );
+                    let replica_url = Self::with_statement_cache(&replica_config.url, &options);
+                    if let Some(capacity) = statement_cache::capacity(&replica_url) {
+                        statement_cache_capacity = statement_cache_capacity.min(capacity);
+                    }
                     let replica_pool = RUNTIME
-                        .block_on(replica_pool_options.connect(replica_config.url.as_str()))
+                        .block_on(replica_pool_options.connect(replica_url.as_str()))
                         .map_err(|e| {
                             SqlxError::connection_with_source(
                                 format!("Failed to connect to replica: {}", replica_config.url),
@@ -197,15 +409,57 @@ macro_rules! php_sqlx_impl_driver_inner {
                 let mut settings = SETTINGS.clone();
                 settings.collapsible_in_enabled = options.collapsible_in_enabled;
                 settings.strict_placeholders = options.strict_placeholders;
+                settings.time_zone = options.time_zone;
+                // A log file that can't be opened disables the log with a warning
+                // rather than failing the driver.
+                let slow_query_log = options.slow_query_threshold.and_then(|threshold| {
+                    SlowQueryLog::new(
+                        &options.slow_query_log,
+                        threshold,
+                        options.slow_query_log_inline,
+                        stringify!($database).to_ascii_lowercase(),
+                        options.persistent_name.clone(),
+                    )
+                    .map_err(|err| {
+                        ext_php_rs::error::php_error(
+                            &ext_php_rs::flags::ErrorType::Warning,
+                            &format!("Sqlx: {err}"),
+                        );
+                    })
+                    .ok()
+                });
+                let cost_guard = options.cost_guard.clone().map(|config| {
+                    CostGuard::new(
+                        config,
+                        options.ast_cache_shard_count,
+                        options.ast_cache_shard_size,
+                    )
+                });
+                let query_allowlist = options
+                    .query_allowlist
+                    .as_deref()
+                    .map(|path| {
+                        QueryAllowlist::load(
+                            path,
+                            options.query_allowlist_mode,
+                            options.query_allowlist_literals,
+                        )
+                    })
+                    .transpose()?;
                 let retry_policy = RetryPolicy {
                     max_attempts: options.retry_max_attempts,
                     initial_backoff: options.retry_initial_backoff,
                     max_backoff: options.retry_max_backoff,
                     multiplier: options.retry_multiplier,
+                    jitter: options.retry_jitter,
+                    retryable_codes: options.retry_on.clone(),
+                    budget: options.retry_budget,
                 };
                 Ok(Self {
                     tx_stack: RwLock::new(Vec::new()),
                     pinned_conn: RwLock::new(None),
+                    transaction_owner: RequestOwner::new(),
+                    pinned_owner: RequestOwner::new(),
                     pool,
                     replica_pools,
                     replica_weights,
@@ -218,10 +472,150 @@ macro_rules! php_sqlx_impl_driver_inner {
                     settings,
                     retry_policy,
                     query_hook: QueryHook::new(),
+                    before_query_hook: BeforeQueryHook::new(),
+                    retry_hook: RetryHook::new(),
+                    query_tags: QueryTags::new(),
+                    slow_query_log,
+                    cost_guard,
+                    circuit_breaker: options
+                        .circuit_breaker
+                        .map(|config| Arc::new(CircuitBreaker::new(config))),
+                    deadline,
+                    pending_discards,
+                    created_at: std::time::Instant::now(),
+                    profiler: Profiler::default(),
+                    settings_owner: RequestOwner::new(),
+                    query_allowlist,
+                    prepared_statements: PreparedStatements::default(),
+                    statement_cache_capacity,
                     options,
                 })
             }
 
+            /// Applies `OPT_STATEMENT_CACHE_CAPACITY` to a connection URL, unless the
+            /// database has no statement cache.
+            fn with_statement_cache(url: &str, options: &DriverInnerOptions) -> String {
+                if STATEMENT_CACHE {
+                    statement_cache::with_capacity(url, options.statement_cache_capacity)
+                } else {
+                    url.to_string()
+                }
+            }
+
+            /// Installs pool hooks setting the session time zone (`time_zone_query`)
+            /// on every new connection, applying the request deadline as a server-side
+            /// statement timeout to every connection handed out while it is set,
+            /// restoring the server's setting when the connection is returned, and
+            /// closing returned connections marked by `discard_on_release()`.
+            ///
+            /// Connections can't be told apart, so once a timeout has been applied to
+            /// any of them, every returned connection is reset: the deadline may have
+            /// been cleared while one carrying a timeout was still out.
+            ///
+            /// A connection whose time zone can't be set fails to open. The timeout
+            /// statements are best effort: if the server rejects them, the client-side
+            /// timeout still applies. Databases without a session-level statement
+            /// timeout only get the time zone.
+            fn with_session_hooks(
+                pool_options: PoolOptions<$database>,
+                deadline: &Arc<Deadline>,
+                pending_discards: &Arc<AtomicUsize>,
+                time_zone_query: Option<String>,
+            ) -> PoolOptions<$database> {
+                let timeout_applied = Arc::new(std::sync::atomic::AtomicBool::new(false));
+                let on_release = Arc::clone(&timeout_applied);
+                let pending_discards = Arc::clone(pending_discards);
+                let pool_options = pool_options.after_release(move |conn, _meta| {
+                    let reset_query = RESET_STATEMENT_TIMEOUT_QUERY
+                        .filter(|_| on_release.load(std::sync::atomic::Ordering::Acquire));
+                    let pending_discards = Arc::clone(&pending_discards);
+                    Box::pin(async move {
+                        // Only checked while a marked connection is out
+                        if pending_discards.load(std::sync::atomic::Ordering::Acquire) > 0 {
+                            let marked = sqlx_oldapi::query::<$database>(DISCARD_CHECK_QUERY)
+                                .fetch_optional(&mut *conn)
+                                .await;
+                            if !matches!(marked, Ok(None)) {
+                                let _ = pending_discards.fetch_update(
+                                    std::sync::atomic::Ordering::AcqRel,
+                                    std::sync::atomic::Ordering::Acquire,
+                                    |count| count.checked_sub(1),
+                                );
+                                return Ok(false);
+                            }
+                        }
+                        if let Some(reset_query) = reset_query {
+                            let _ = sqlx_oldapi::Executor::execute(conn, reset_query).await;
+                        }
+                        Ok(true)
+                    })
+                });
+                let Some(set_query) = SET_STATEMENT_TIMEOUT_QUERY else {
+                    let Some(time_zone_query) = time_zone_query else {
+                        return pool_options;
+                    };
+                    return pool_options.after_connect(move |conn, _meta| {
+                        let time_zone_query = time_zone_query.clone();
+                        Box::pin(async move {
+                            sqlx_oldapi::Executor::execute(conn, time_zone_query.as_str())
+                                .await
+                                .map(|_| ())
+                        })
+                    });
+                };
+                let apply = move |timeout_ms: Option<u64>| {
+                    let sql = timeout_ms.map(|ms| set_query.replace("{ms}", &ms.to_string()));
+                    if sql.is_some() {
+                        timeout_applied.store(true, std::sync::atomic::Ordering::Release);
+                    }
+                    sql
+                };
+                let on_connect = Arc::clone(deadline);
+                let on_acquire = Arc::clone(deadline);
+                pool_options
+                    .after_connect(move |conn, _meta| {
+                        let time_zone_query = time_zone_query.clone();
+                        let sql = apply(on_connect.statement_timeout_ms());
+                        Box::pin(async move {
+                            if let Some(time_zone_query) = time_zone_query {
+                                sqlx_oldapi::Executor::execute(&mut *conn, time_zone_query.as_str())
+                                    .await?;
+                            }
+                            if let Some(sql) = sql {
+                                let _ = sqlx_oldapi::Executor::execute(conn, sql.as_str()).await;
+                            }
+                            Ok(())
+                        })
+                    })
+                    .before_acquire(move |conn, _meta| {
+                        let sql = apply(on_acquire.statement_timeout_ms());
+                        Box::pin(async move {
+                            if let Some(sql) = sql {
+                                let _ = sqlx_oldapi::Executor::execute(conn, sql.as_str()).await;
+                            }
+                            Ok(true)
+                        })
+                    })
+            }
+
+            /// Describes the pool registered as `name`, for
+            /// `DriverFactory::persistentPools()`. The URL's credentials are masked.
+            #[must_use]
+            pub fn pool_info(&self, name: &str) -> serde_json::Value {
+                serde_json::json!({
+                    "name": name,
+                    "driver": stringify!($database).to_ascii_lowercase(),
+                    "url": self.options.url.as_deref().map(redact_url),
+                    "size": self.pool.size(),
+                    "idle": self.pool.num_idle(),
+                    "max_connections": self.options.max_connections.get(),
+                    "replicas": self.replica_pools.len(),
+                    "age_seconds": self.created_at.elapsed().as_secs_f64(),
+                    "closed": self.is_closed(),
+                    "statement_cache": self.prepared_statements.totals.to_json(),
+                })
+            }
+
             /// Returns whether this driver is configured for read-only mode.
             ///
             /// Read-only mode is useful for replica connections where writes should be prevented.
@@ -332,54 +726,182 @@ macro_rules! php_sqlx_impl_driver_inner {
             /// - There is an active transaction (to prevent partial commits)
             /// - The error is not transient
             ///
-            /// Uses exponential backoff between retries.
+            /// Uses exponential backoff between retries, randomized by the policy's
+            /// jitter mode and bounded by its time budget and the request deadline, and
+            /// reports each retry to the `onRetry()` hook. A query error records the
+            /// attempt that failed, for `QueryException::getAttempt()`.
+            ///
+            /// Every attempt goes through the circuit breaker, which fails it fast while
+            /// open; that error is not retried.
             fn with_retry<F, T>(&self, operation: F) -> $crate::error::Result<T>
             where
                 F: Fn() -> $crate::error::Result<T>,
             {
                 // Ensure driver hasn't been closed
                 self.ensure_open()?;
+                let attempt_once = || self.through_circuit(&operation);
 
                 // Skip retry if disabled or in transaction
                 if self.retry_policy.is_disabled() || self.has_active_transaction() {
-                    return operation();
+                    return attempt_once()
+                        .map_err(|e| e.with_query_context(|context| context.attempt = Some(1)));
                 }
 
+                let started = std::time::Instant::now();
                 let mut attempt = 0u32;
+                let mut previous = std::time::Duration::ZERO;
                 loop {
-                    match operation() {
+                    match attempt_once() {
                         Ok(result) => return Ok(result),
                         Err(e) => {
-                            if let Some(backoff) = self.retry_policy.should_retry(attempt, &e) {
+                            if let Some(delay) = self.retry_policy.next_delay(
+                                attempt,
+                                &e,
+                                previous,
+                                started.elapsed(),
+                                jitter_sample(),
+                            ) && self.deadline.allows(delay)
+                            {
                                 attempt += 1;
-                                std::thread::sleep(backoff);
+                                previous = delay;
+                                self.retry_hook.call(attempt, delay, &e);
+                                std::thread::sleep(delay);
                                 continue;
                             }
-                            return Err(e);
+                            return Err(e.with_query_context(|context| {
+                                context.attempt = Some(attempt + 1);
+                            }));
                         }
                     }
                 }
             }
 
+            /// Runs `operation` if the circuit breaker lets it through, and reports
+            /// whether it failed to reach the database. Running out of request deadline
+            /// says nothing about the database and is not reported, nor is an operation
+            /// that unwinds: its permit gives the probe back when dropped.
+            fn through_circuit<T>(
+                &self,
+                operation: impl FnOnce() -> $crate::error::Result<T>,
+            ) -> $crate::error::Result<T> {
+                let Some(breaker) = &self.circuit_breaker else {
+                    return operation();
+                };
+                let permit = breaker.permit()?;
+                let result = operation();
+                permit.settle(&result);
+                result
+            }
+
+            /// Returns the state of the circuit breaker; always `Closed` if the breaker
+            /// is disabled.
+            #[must_use]
+            pub fn circuit_state(&self) -> CircuitState {
+                self.circuit_breaker
+                    .as_ref()
+                    .map_or(CircuitState::Closed, |breaker| breaker.state())
+            }
+
+            /// Attaches the driver name and the statement's parameters, with sensitive
+            /// values masked, to a query error for `QueryException`.
+            fn with_error_context(
+                &self,
+                err: SqlxError,
+                parameters: Option<&BTreeMap<String, ParameterValue>>,
+            ) -> SqlxError {
+                err.with_query_context(|context| {
+                    context.driver = Some(stringify!($database).to_ascii_lowercase());
+                    context.parameters = parameters
+                        .map(|parameters| mask_parameters(parameters, &self.options.redact_params));
+                })
+            }
+
             /// Drives a query future on the runtime, applying the configured
-            /// per-query timeout (`OPT_QUERY_TIMEOUT`) when set.
+            /// per-query timeout (`OPT_QUERY_TIMEOUT`) and the request deadline.
             ///
             /// Returns the inner driver `Result` unchanged on success, or a
-            /// `Timeout` error if the future did not complete in time (the future
-            /// is dropped, cancelling the query client-side). Callers map the inner
-            /// `sqlx` error as they see fit (e.g. `RowNotFound` handling).
-            fn run_query<Fut, T>(
+            /// `Timeout`/`DeadlineExceeded` error if the future did not complete in
+            /// time (the future is dropped, cancelling the query client-side). Callers
+            /// map the inner `sqlx` error as they see fit (e.g. `RowNotFound` handling).
+            pub(crate) fn run_query<Fut, T>(
                 &self,
                 fut: Fut,
             ) -> $crate::error::Result<Result<T, sqlx_oldapi::Error>>
             where
                 Fut: std::future::Future<Output = Result<T, sqlx_oldapi::Error>>,
             {
-                match self.options.query_timeout {
+                self.block_on_within("query", self.options.query_timeout, fut)
+            }
+
+            /// Runs a statement of the active prepared query, if any, on `conn` and
+            /// records whether it had to be prepared there, from the size of the
+            /// connection's statement cache before and after (see `CacheOutcome`).
+            fn observed<T>(
+                &self,
+                conn: &mut <sqlx_oldapi::$database as sqlx_oldapi::Database>::Connection,
+                run: impl FnOnce(
+                    &mut <sqlx_oldapi::$database as sqlx_oldapi::Database>::Connection,
+                ) -> $crate::error::Result<T>,
+            ) -> $crate::error::Result<T> {
+                if !self.prepared_statements.is_active() {
+                    return run(conn);
+                }
+                let before = statement_cache_size(conn);
+                let result = run(conn);
+                if result.is_ok()
+                    && let (Some(before), Some(after)) = (before, statement_cache_size(conn))
+                {
+                    self.prepared_statements.record(CacheOutcome::observe(
+                        before,
+                        after,
+                        self.statement_cache_capacity,
+                    ));
+                }
+                result
+            }
+
+            /// Acquires a connection from `pool` for a statement of the active prepared
+            /// query, so that it can be observed by [`Self::observed`]. Returns `None`
+            /// outside prepared queries and on databases without a statement cache,
+            /// where statements run on the pool directly.
+            fn acquire_prepared(
+                &self,
+                pool: &Pool<$database>,
+            ) -> $crate::error::Result<Option<PoolConnection<$database>>> {
+                if !STATEMENT_CACHE || !self.prepared_statements.is_active() {
+                    return Ok(None);
+                }
+                self.block_on_within("acquire", None, pool.acquire())?
+                    .map(Some)
+                    .map_err(|err| {
+                        SqlxError::connection_with_source("Failed to acquire connection", err)
+                    })
+            }
+
+            /// Drives a future on the runtime until `timeout` or the request deadline,
+            /// whichever comes first. Fails without polling it if the deadline has
+            /// already passed.
+            fn block_on_within<Fut: std::future::Future>(
+                &self,
+                operation: &str,
+                timeout: Option<std::time::Duration>,
+                fut: Fut,
+            ) -> $crate::error::Result<Fut::Output> {
+                let remaining = self.deadline.remaining(operation)?;
+                if let Some(remaining) =
+                    remaining.filter(|remaining| timeout.is_none_or(|timeout| *remaining < timeout))
+                {
+                    return RUNTIME.block_on(tokio::time::timeout(remaining, fut)).map_err(|_| {
+                        SqlxError::DeadlineExceeded {
+                            operation: operation.to_string(),
+                        }
+                    });
+                }
+                match timeout {
                     Some(timeout) => {
                         RUNTIME.block_on(tokio::time::timeout(timeout, fut)).map_err(|_| {
                             SqlxError::Timeout {
-                                operation: "query".to_string(),
+                                operation: operation.to_string(),
                                 timeout_ms: u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX),
                             }
                         })
@@ -388,6 +910,18 @@ macro_rules! php_sqlx_impl_driver_inner {
                 }
             }
 
+            /// Sets the request deadline as Unix milliseconds, or clears it with `None`.
+            pub fn set_deadline(&self, unix_millis: Option<i64>) {
+                self.settings_owner.claim();
+                self.deadline.set(unix_millis);
+            }
+
+            /// Returns the request deadline as Unix milliseconds, if set.
+            #[must_use]
+            pub fn get_deadline(&self) -> Option<i64> {
+                self.deadline.get()
+            }
+
             /// Executes an INSERT/UPDATE/DELETE query and returns affected row count.
             ///
             /// # Arguments
@@ -407,31 +941,41 @@ macro_rules! php_sqlx_impl_driver_inner {
                 query: &str,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<u64> {
+                let (query, parameters, meta) =
+                    self.before_query(query, parameters, QueryKind::Execute)?;
+
                 // Render inline query for logging only if hook is active
-                let sql_inline = if self.query_hook.is_set() {
-                    self.render_query_inline(query, parameters.clone()).ok()
-                } else {
-                    None
-                };
+                let sql_inline = self.instrumentation_inline(&query, parameters.as_ref());
 
-                let (query, values) = self.render_query(query, parameters)?;
+                let (query, values, instrumentation) =
+                    self.render_guarded(&query, parameters.clone())?;
 
-                // Start timing if hook is active
-                let timer = QueryTimer::new(&self.query_hook, query.clone(), sql_inline);
+                // Start timing if hook, slow query log or profile is active
+                let timer = self.start_timer(&query, sql_inline, meta, instrumentation);
 
                 let result = self.with_retry(|| {
                     if let Some(mut tx) = self.retrieve_ongoing_transaction() {
-                        let val = self.run_query(
-                            bind_values(sqlx_oldapi::query(&query), &values)?.execute(&mut *tx),
-                        );
+                        let val = self.observed(&mut *tx, |conn| {
+                            self.run_query(
+                                bind_values(sqlx_oldapi::query(&query), &values)?.execute(conn),
+                            )
+                        });
                         self.place_ongoing_transaction(tx);
                         val
                     } else if let Some(mut conn) = self.retrieve_pinned_connection() {
-                        let val = self.run_query(
-                            bind_values(sqlx_oldapi::query(&query), &values)?.execute(&mut *conn),
-                        );
+                        let val = self.observed(&mut *conn, |conn| {
+                            self.run_query(
+                                bind_values(sqlx_oldapi::query(&query), &values)?.execute(conn),
+                            )
+                        });
                         self.return_pinned_connection(conn);
                         val
+                    } else if let Some(mut conn) = self.acquire_prepared(&self.pool)? {
+                        self.observed(&mut *conn, |conn| {
+                            self.run_query(
+                                bind_values(sqlx_oldapi::query(&query), &values)?.execute(conn),
+                            )
+                        })
                     } else {
                         self.run_query(
                             bind_values(sqlx_oldapi::query(&query), &values)?.execute(&self.pool),
@@ -441,7 +985,8 @@ macro_rules! php_sqlx_impl_driver_inner {
                         inner.map_err(|err| SqlxError::query_with_source(&query, err))
                     })
                     .map(|done| done.rows_affected())
-                });
+                })
+                .map_err(|err| self.with_error_context(err, parameters.as_ref()));
 
                 // Call hook with timing info
                 if let Some(t) = timer {
@@ -454,14 +999,474 @@ macro_rules! php_sqlx_impl_driver_inner {
                 result
             }
 
+            /// Runs the statements queued by `batch()` on one connection and returns
+            /// one result per statement: the affected rows of an `execute()`, the rows
+            /// of a `queryAll()`.
+            ///
+            /// The statements are sent one after another with their parameters bound,
+            /// in the ongoing transaction if there is one, on the pinned connection if
+            /// there is one, and otherwise on a connection from the pool. No
+            /// transaction is started for the batch: outside of one, each statement
+            /// commits on its own and a failing statement leaves the previous ones
+            /// applied. The query timeout applies to each statement. Batches are not
+            /// retried, since part of them may have run.
+            pub fn batch(
+                &self,
+                statements: &[BatchStatement],
+                associative_arrays: Option<bool>,
+            ) -> $crate::error::Result<Vec<Zval>> {
+                self.ensure_open()?;
+                if statements.is_empty() {
+                    return Ok(Vec::new());
+                }
+
+                let mut rendered = Vec::with_capacity(statements.len());
+                for (index, statement) in statements.iter().enumerate() {
+                    rendered.push(
+                        self.render_statement(
+                            statement.kind,
+                            &statement.query,
+                            statement.parameters.clone(),
+                        )
+                        .map_err(|err| {
+                            err.with_query_context(|context| context.index = Some(index))
+                        })?,
+                    );
+                }
+
+                let mut outcomes = Vec::with_capacity(rendered.len());
+                let mut run =
+                    |conn: &mut <sqlx_oldapi::$database as sqlx_oldapi::Database>::Connection| {
+                        self.run_batch(conn, &mut rendered, &mut outcomes)
+                    };
+                let result = self
+                    .through_circuit(|| {
+                        if let Some(mut tx) = self.retrieve_ongoing_transaction() {
+                            let val = run(&mut *tx);
+                            self.place_ongoing_transaction(tx);
+                            val
+                        } else if let Some(mut conn) = self.retrieve_pinned_connection() {
+                            let val = run(&mut *conn);
+                            self.return_pinned_connection(conn);
+                            val
+                        } else {
+                            let mut conn = self
+                                .block_on_within("acquire", None, self.pool.acquire())?
+                                .map_err(|err| {
+                                    SqlxError::connection_with_source(
+                                        "Failed to acquire connection",
+                                        err,
+                                    )
+                                })?;
+                            run(&mut *conn)
+                        }
+                    })
+                    .map_err(|err| self.with_batch_error_context(err, outcomes.len(), None));
+                result?;
+
+                let assoc = associative_arrays.unwrap_or(self.options.associative_arrays);
+                outcomes
+                    .into_iter()
+                    .map(|outcome| match outcome {
+                        BatchOutcome::Affected(rows) => rows.into_zval(false).map_err(|err| {
+                            SqlxError::Conversion {
+                                message: format!("{err}"),
+                            }
+                        }),
+                        BatchOutcome::Rows(rows) => {
+                            let rows: Vec<Zval> = self
+                                .check_max_rows(rows)?
+                                .into_iter()
+                                .map(|row| row.into_zval(assoc, self.options.time_zone))
+                                .try_collect()?;
+                            rows.into_zval(false).map_err(|err| SqlxError::Conversion {
+                                message: format!("{err}"),
+                            })
+                        }
+                    })
+                    .try_collect()
+            }
+
+            /// Runs `query` once per parameter set on one connection, in the ongoing
+            /// transaction or a new one, and returns the total of affected rows.
+            ///
+            /// The query is parsed once, through the AST cache, and rendered per set.
+            /// Each rendered shape is prepared on the connection by its first set and
+            /// reused from the statement cache by the others, which only bind their
+            /// values and execute. The query timeout applies to each set.
+            ///
+            /// Stops at the first failing set, whose position is reported by
+            /// `QueryException::getIndex()`; a transaction started here is rolled
+            /// back. A failing commit, after every set ran, is reported as such, with
+            /// no position. Not retried, since part of the sets may have run.
+            pub fn execute_many(
+                &self,
+                query: &str,
+                parameter_sets: Vec<Option<BTreeMap<String, ParameterValue>>>,
+            ) -> $crate::error::Result<u64> {
+                self.ensure_open()?;
+                if parameter_sets.is_empty() {
+                    return Ok(0);
+                }
+
+                let mut rendered = Vec::with_capacity(parameter_sets.len());
+                for (index, parameters) in parameter_sets.iter().enumerate() {
+                    rendered.push(
+                        self.render_statement(BatchKind::Execute, query, parameters.clone())
+                            .map_err(|err| {
+                                self.with_error_context(
+                                    err.with_query_context(|context| context.index = Some(index)),
+                                    parameters.as_ref(),
+                                )
+                            })?,
+                    );
+                }
+
+                let mut outcomes = Vec::with_capacity(rendered.len());
+                let mut run =
+                    |conn: &mut <sqlx_oldapi::$database as sqlx_oldapi::Database>::Connection| {
+                        self.run_batch(conn, &mut rendered, &mut outcomes)
+                    };
+                let result = self.through_circuit(|| {
+                    if let Some(mut tx) = self.retrieve_ongoing_transaction() {
+                        let val = run(&mut *tx);
+                        self.place_ongoing_transaction(tx);
+                        val
+                    } else if let Some(mut conn) = self.retrieve_pinned_connection() {
+                        let val = self.in_transaction(&mut *conn, &mut run);
+                        self.return_pinned_connection(conn);
+                        val
+                    } else {
+                        let mut conn = self
+                            .block_on_within("acquire", None, self.pool.acquire())?
+                            .map_err(|err| {
+                                SqlxError::connection_with_source(
+                                    "Failed to acquire connection",
+                                    err,
+                                )
+                            })?;
+                        self.in_transaction(&mut *conn, &mut run)
+                    }
+                });
+                let result = result.map_err(|err| {
+                    let index = outcomes.len();
+                    if index == parameter_sets.len() {
+                        // Every set ran: committing the transaction failed
+                        return self.with_error_context(err, None);
+                    }
+                    self.with_batch_error_context(
+                        err,
+                        index,
+                        parameter_sets.get(index).and_then(Option::as_ref),
+                    )
+                });
+                result?;
+                Ok(outcomes.iter().map(BatchOutcome::count).sum())
+            }
+
+            /// Runs `operation` in a new transaction on `conn`, committing it if the
+            /// operation succeeds and rolling it back otherwise.
+            fn in_transaction<T>(
+                &self,
+                conn: &mut <sqlx_oldapi::$database as sqlx_oldapi::Database>::Connection,
+                operation: impl FnOnce(
+                    &mut <sqlx_oldapi::$database as sqlx_oldapi::Database>::Connection,
+                ) -> $crate::error::Result<T>,
+            ) -> $crate::error::Result<T> {
+                let mut tx = self
+                    .block_on_within("begin", None, sqlx_oldapi::Connection::begin(conn))?
+                    .map_err(SqlxError::begin_failed)?;
+                match operation(&mut *tx) {
+                    Ok(value) => {
+                        self.block_on_within("commit", None, tx.commit())?
+                            .map_err(SqlxError::commit_failed)?;
+                        Ok(value)
+                    }
+                    Err(err) => {
+                        // Past the deadline, the dropped transaction queues its rollback
+                        let _ = self.block_on_within("rollback", None, tx.rollback());
+                        Err(err)
+                    }
+                }
+            }
+
+            /// Runs the before-query hook on a batched statement, then renders it for
+            /// execution.
+            fn render_statement(
+                &self,
+                kind: BatchKind,
+                query: &str,
+                parameters: Option<BTreeMap<String, ParameterValue>>,
+            ) -> $crate::error::Result<BatchEntry> {
+                let (query, parameters, meta) =
+                    self.before_query(query, parameters, kind.query_kind())?;
+                let sql_inline = self.instrumentation_inline(&query, parameters.as_ref());
+                let (sql, values, instrumentation) = self.render_guarded(&query, parameters)?;
+                Ok(BatchEntry {
+                    kind,
+                    sql,
+                    values,
+                    sql_inline,
+                    meta,
+                    instrumentation,
+                })
+            }
+
+            /// Attaches the position of the failed statement, and the parameters it
+            /// was called with, to a batch error.
+            fn with_batch_error_context(
+                &self,
+                err: SqlxError,
+                index: usize,
+                parameters: Option<&BTreeMap<String, ParameterValue>>,
+            ) -> SqlxError {
+                self.with_error_context(
+                    err.with_query_context(|context| {
+                        context.attempt = Some(1);
+                        context.index = Some(index);
+                    }),
+                    parameters,
+                )
+            }
+
+            /// Sends statements of a batch on `conn` one after another with their
+            /// parameters bound, pushing one outcome per statement that ran. Each
+            /// statement's query hook timer starts when it is sent, and the query
+            /// timeout applies to it alone. On error, `outcomes` tells how many ran.
+            ///
+            /// A rendered statement is prepared on its first run and kept in the
+            /// connection's statement cache, so the following statements of the same
+            /// shape only bind and execute; under a prepared query, each run is
+            /// recorded as a hit or miss.
+            fn run_batch(
+                &self,
+                conn: &mut <sqlx_oldapi::$database as sqlx_oldapi::Database>::Connection,
+                statements: &mut [BatchEntry],
+                outcomes: &mut Vec<
+                    BatchOutcome<<sqlx_oldapi::$database as sqlx_oldapi::Database>::Row>,
+                >,
+            ) -> $crate::error::Result<()> {
+                for entry in statements {
+                    let timer = self.start_timer(
+                        &entry.sql,
+                        entry.sql_inline.take(),
+                        entry.meta.take(),
+                        std::mem::take(&mut entry.instrumentation),
+                    );
+                    let outcome = self.observed(conn, |conn| {
+                        self.block_on_within(
+                            "query",
+                            self.options.query_timeout,
+                            Self::run_statement(conn, entry.kind, &entry.sql, &entry.values),
+                        )?
+                    });
+                    if let Some(t) = timer {
+                        match &outcome {
+                            Ok(outcome) => t.finish(Some(outcome.count()), None),
+                            Err(err) => t.finish(None, Some(&err.to_string())),
+                        }
+                    }
+                    outcomes.push(outcome?);
+                }
+                Ok(())
+            }
+
+            /// Runs one statement of a batch with its values bound.
+            async fn run_statement(
+                conn: &mut <sqlx_oldapi::$database as sqlx_oldapi::Database>::Connection,
+                kind: BatchKind,
+                sql: &str,
+                values: &[ParameterValue],
+            ) -> $crate::error::Result<
+                BatchOutcome<<sqlx_oldapi::$database as sqlx_oldapi::Database>::Row>,
+            > {
+                let query = bind_values(sqlx_oldapi::query(sql), values)?;
+                let outcome = match kind {
+                    BatchKind::Execute => query
+                        .execute(&mut *conn)
+                        .await
+                        .map(|done| BatchOutcome::Affected(done.rows_affected())),
+                    BatchKind::QueryAll => query.fetch_all(&mut *conn).await.map(BatchOutcome::Rows),
+                };
+                outcome.map_err(|err| SqlxError::query_with_source(sql, err))
+            }
+
+            /// Renders the inline SQL reported to the query hook and slow query log.
+            ///
+            /// Returns `None` (and does no work) unless a hook is registered or the
+            /// slow query log includes inline SQL.
+            fn instrumentation_inline(
+                &self,
+                query: &str,
+                parameters: Option<&BTreeMap<String, ParameterValue>>,
+            ) -> Option<String> {
+                if self.query_hook.is_set()
+                    || self.slow_query_log.as_ref().is_some_and(|log| log.include_inline)
+                {
+                    self.render_query_inline(query, parameters.cloned()).ok()
+                } else {
+                    None
+                }
+            }
+
+            /// Starts timing a query for the query hook, slow query log and profiler.
+            ///
+            /// `meta` is the metadata attached by the before-query hook, if any.
+            /// Returns `None` if none of them is active.
+            pub(crate) fn start_timer(
+                &self,
+                sql: &str,
+                sql_inline: Option<String>,
+                meta: Option<Zval>,
+                instrumentation: Instrumentation,
+            ) -> Option<QueryTimer<'_>> {
+                QueryTimer::new(
+                    &self.query_hook,
+                    self.slow_query_log.as_ref(),
+                    self.settings.escape_backslash,
+                    sql.to_owned(),
+                    sql_inline,
+                    meta,
+                    instrumentation,
+                )
+            }
+
+            /// Runs the before-query hook, if one is registered.
+            ///
+            /// Returns the SQL and parameters to execute (rewritten by the hook, or the
+            /// originals) and the metadata to hand to the after-query hook.
+            ///
+            /// # Errors
+            /// Returns [`SqlxError::Vetoed`] if the hook throws.
+            pub fn before_query<'q>(
+                &self,
+                query: &'q str,
+                parameters: Option<BTreeMap<String, ParameterValue>>,
+                kind: QueryKind,
+            ) -> $crate::error::Result<(
+                Cow<'q, str>,
+                Option<BTreeMap<String, ParameterValue>>,
+                Option<Zval>,
+            )> {
+                if !self.before_query_hook.is_set() {
+                    return Ok((Cow::Borrowed(query), parameters, None));
+                }
+                let outcome = self
+                    .before_query_hook
+                    .call(query, parameters.as_ref(), kind)?;
+                Ok((
+                    outcome.sql.map_or(Cow::Borrowed(query), Cow::Owned),
+                    outcome.params.or(parameters),
+                    outcome.meta,
+                ))
+            }
+
+            /// Reports a transaction control statement to the before-query hook and
+            /// returns the metadata it attached.
+            ///
+            /// Transaction statements can be vetoed but not rewritten. Rollbacks can
+            /// be neither: an exception from the hook is ignored so that a failing
+            /// guard can never leave a transaction open.
+            fn before_transaction(
+                &self,
+                sql: &str,
+                kind: QueryKind,
+            ) -> $crate::error::Result<Option<Zval>> {
+                if !self.before_query_hook.is_set() {
+                    return Ok(None);
+                }
+                let result = self.before_query_hook.call(sql, None, kind);
+                match kind {
+                    QueryKind::Rollback | QueryKind::RollbackToSavepoint => {
+                        Ok(result.ok().and_then(|outcome| outcome.meta))
+                    }
+                    _ => result.map(|outcome| outcome.meta),
+                }
+            }
+
+            /// Reports a finished transaction control statement to the query hook.
+            fn finish_transaction_timer<T>(
+                timer: Option<QueryTimer<'_>>,
+                result: &$crate::error::Result<T>,
+            ) {
+                if let Some(t) = timer {
+                    match result {
+                        Ok(_) => t.finish(None, None),
+                        Err(err) => t.finish(None, Some(&err.to_string())),
+                    }
+                }
+            }
+
             /// Renders the final SQL query and parameters using the AST cache.
             ///
             /// Looks up the query in the cache; if not found, parses it and caches the AST.
             /// Returns the rendered SQL string with positional placeholders and the parameter values.
+            ///
+            /// Active query tags are appended to the rendered SQL as a `sqlcommenter`
+            /// comment; the cache is keyed by the untagged query text.
             fn render_query(
                 &self,
                 query: &str,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
+            ) -> $crate::error::Result<(String, Vec<ParameterValue>)> {
+                let (sql, values) = self.render_untagged(query, parameters)?;
+                Ok((self.query_tags.apply(sql), values))
+            }
+
+            /// Renders a query like [`Self::render_query`], checking the template
+            /// against the query allowlist and running the cost guard on the rendered
+            /// shape first.
+            ///
+            /// The guard sees the SQL before query tags are appended, so per-request
+            /// tags don't defeat its verdict cache. Returns the allowlist and guard
+            /// warnings and, while a profile is running, the statement's profile sample.
+            ///
+            /// # Errors
+            /// Returns [`SqlxError::NotAllowlisted`] or [`SqlxError::InlineLiterals`]
+            /// if the allowlist refuses the statement, and [`SqlxError::CostExceeded`]
+            /// if the guard is in `throw` mode and the plan exceeds a threshold.
+            fn render_guarded(
+                &self,
+                query: &str,
+                parameters: Option<BTreeMap<String, ParameterValue>>,
+            ) -> $crate::error::Result<(String, Vec<ParameterValue>, Instrumentation)> {
+                let session = self.profiler.active();
+                let fingerprint = if self.query_allowlist.is_some() || session.is_some() {
+                    Some(self.parse_query(query)?.fingerprint(&self.settings))
+                } else {
+                    None
+                };
+                let mut warnings = match (&self.query_allowlist, &fingerprint) {
+                    (Some(allowlist), Some(fingerprint)) => allowlist.check(fingerprint)?,
+                    _ => Vec::new(),
+                };
+
+                let (sql, values) = self.render_untagged(query, parameters)?;
+                if let Some(guard) = &self.cost_guard {
+                    warnings.extend(guard.check(&sql, || {
+                        let explained = self
+                            .explain_rendered(&sql, &values, ExplainOptions::default())
+                            .ok()?;
+                        Some(guard.config.violations(&explained.summary, |table| {
+                            self.table_row_estimate(table)
+                        }))
+                    })?);
+                }
+                let profile = session
+                    .zip(fingerprint)
+                    .map(|(session, fingerprint)| session.sample(fingerprint, &sql, &values));
+                Ok((
+                    self.query_tags.apply(sql),
+                    values,
+                    Instrumentation { warnings, profile },
+                ))
+            }
+
+            /// Renders a query through the AST cache, without query tags.
+            fn render_untagged(
+                &self,
+                query: &str,
+                parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<(String, Vec<ParameterValue>)> {
                 let parameters = parameters.unwrap_or_default();
                 if let Some(ast) = self.ast_cache.get(query) {
@@ -474,19 +1479,54 @@ macro_rules! php_sqlx_impl_driver_inner {
                 }
             }
 
+            /// Returns the database's row-count estimate for `table`, if known.
+            ///
+            /// Used by the cost guard to size sequentially scanned tables; any
+            /// failure yields `None`.
+            fn table_row_estimate(&self, table: &str) -> Option<u64> {
+                let mut params = BTreeMap::new();
+                params.insert(
+                    "table".to_string(),
+                    ParameterValue::String(table.to_string()),
+                );
+                let (sql, values) = self.render_untagged(TABLE_ROWS_QUERY, Some(params)).ok()?;
+                let row = self
+                    .run_query(
+                        bind_values(sqlx_oldapi::query(&sql), &values)
+                            .ok()?
+                            .fetch_optional(self.get_read_pool()),
+                    )
+                    .ok()?
+                    .ok()??;
+                let rows: Option<i64> = row.try_get(0).ok()?;
+                u64::try_from(rows?).ok()
+            }
+
             /// Renders a query with all parameters inlined (no placeholders).
             ///
             /// This is used for debugging or logging purposes. The rendered query
-            /// contains literal values instead of positional placeholders.
+            /// contains literal values instead of positional placeholders; secrets
+            /// and placeholders named in `OPT_REDACT_PARAMS` render as `'***'`.
             fn render_query_inline(
                 &self,
                 query: &str,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<String> {
-                let parameters = parameters.unwrap_or_default();
+                let mut parameters = parameters.unwrap_or_default();
+                for (name, value) in &mut parameters {
+                    if self
+                        .options
+                        .redact_params
+                        .iter()
+                        .any(|redacted| redacted.eq_ignore_ascii_case(name))
+                    {
+                        *value = std::mem::replace(value, ParameterValue::Null).into_secret();
+                    }
+                }
 
                 let mut settings = self.settings.clone();
                 settings.max_placeholders = 0;
+                settings.redact_secrets = true;
 
                 if let Some(ast) = self.ast_cache.get(query) {
                     let (query, _) = ast.render(parameters, &settings)?;
@@ -533,28 +1573,7 @@ macro_rules! php_sqlx_impl_driver_inner {
                 #[allow(clippy::needless_pass_by_value)] column: Option<ColumnArgument>,
                 associative_arrays: Option<bool>,
             ) -> $crate::error::Result<Zval> {
-                let (query, values) = self.render_query(query, parameters)?;
-                let row = self.with_retry(|| {
-                    if let Some(mut tx) = self.retrieve_ongoing_transaction() {
-                        let val = RUNTIME.block_on(
-                            bind_values(sqlx_oldapi::query(&query), &values)?.fetch_one(&mut *tx),
-                        );
-                        self.place_ongoing_transaction(tx);
-                        val
-                    } else if let Some(mut conn) = self.retrieve_pinned_connection() {
-                        let val = RUNTIME.block_on(
-                            bind_values(sqlx_oldapi::query(&query), &values)?.fetch_one(&mut *conn),
-                        );
-                        self.return_pinned_connection(conn);
-                        val
-                    } else {
-                        RUNTIME.block_on(
-                            bind_values(sqlx_oldapi::query(&query), &values)?
-                                .fetch_one(self.get_read_pool()),
-                        )
-                    }
-                    .map_err(|err| SqlxError::query_with_source(&query, err))
-                })?;
+                let row = self.fetch_one_row(query, parameters)?;
                 let column_idx: usize = match column {
                     Some(ColumnArgument::Index(i)) => i,
                     Some(ColumnArgument::Name(column_name)) => {
@@ -576,6 +1595,7 @@ macro_rules! php_sqlx_impl_driver_inner {
                 row.column_value_into_zval(
                     row.try_column(column_idx)?,
                     associative_arrays.unwrap_or(self.options.associative_arrays),
+                    self.options.time_zone,
                 )
             }
 
@@ -599,31 +1619,8 @@ macro_rules! php_sqlx_impl_driver_inner {
                 #[allow(clippy::needless_pass_by_value)] column: Option<ColumnArgument>,
                 associative_arrays: Option<bool>,
             ) -> $crate::error::Result<Vec<Zval>> {
-                let (query, values) = self.render_query(query, parameters)?;
                 let mut it = self
-                    .with_retry(|| {
-                        if let Some(mut tx) = self.retrieve_ongoing_transaction() {
-                            let val = RUNTIME.block_on(
-                                bind_values(sqlx_oldapi::query(&query), &values)?
-                                    .fetch_all(&mut *tx),
-                            );
-                            self.place_ongoing_transaction(tx);
-                            val
-                        } else if let Some(mut conn) = self.retrieve_pinned_connection() {
-                            let val = RUNTIME.block_on(
-                                bind_values(sqlx_oldapi::query(&query), &values)?
-                                    .fetch_all(&mut *conn),
-                            );
-                            self.return_pinned_connection(conn);
-                            val
-                        } else {
-                            RUNTIME.block_on(
-                                bind_values(sqlx_oldapi::query(&query), &values)?
-                                    .fetch_all(self.get_read_pool()),
-                            )
-                        }
-                        .map_err(|err| SqlxError::query_with_source(&query, err))
-                    })?
+                    .fetch_all_rows(query, parameters)?
                     .into_iter()
                     .peekable();
                 let Some(row) = it.peek() else {
@@ -658,6 +1655,7 @@ macro_rules! php_sqlx_impl_driver_inner {
                     row.column_value_into_zval(
                         row.column(column_idx),
                         associative_arrays.unwrap_or(self.options.associative_arrays),
+                        self.options.time_zone,
                     )
                 })
                 .try_collect()
@@ -683,35 +1681,8 @@ macro_rules! php_sqlx_impl_driver_inner {
                 #[allow(clippy::needless_pass_by_value)] column: Option<ColumnArgument>,
                 associative_arrays: Option<bool>,
             ) -> $crate::error::Result<Zval> {
-                let (query, values) = self.render_query(query, parameters)?;
                 Ok(self
-                    .with_retry(|| {
-                        if let Some(mut tx) = self.retrieve_ongoing_transaction() {
-                            let val = RUNTIME.block_on(
-                                bind_values(sqlx_oldapi::query(&query), &values)?
-                                    .fetch_one(&mut *tx),
-                            );
-                            self.place_ongoing_transaction(tx);
-                            val
-                        } else if let Some(mut conn) = self.retrieve_pinned_connection() {
-                            let val = RUNTIME.block_on(
-                                bind_values(sqlx_oldapi::query(&query), &values)?
-                                    .fetch_one(&mut *conn),
-                            );
-                            self.return_pinned_connection(conn);
-                            val
-                        } else {
-                            RUNTIME.block_on(
-                                bind_values(sqlx_oldapi::query(&query), &values)?
-                                    .fetch_one(self.get_read_pool()),
-                            )
-                        }
-                        .map(Some)
-                        .or_else(|err: sqlx_oldapi::Error| match err {
-                            sqlx_oldapi::Error::RowNotFound => Ok(None),
-                            _ => Err(SqlxError::query_with_source(&query, err)),
-                        })
-                    })?
+                    .fetch_maybe_row(query, parameters)?
                     .map(|row| {
                         let column_idx: usize = match column {
                             Some(ColumnArgument::Index(i)) => i,
@@ -734,6 +1705,7 @@ macro_rules! php_sqlx_impl_driver_inner {
                         row.column_value_into_zval(
                             row.try_column(column_idx)?,
                             associative_arrays.unwrap_or(self.options.associative_arrays),
+                            self.options.time_zone,
                         )
                     })
                     .transpose()?
@@ -742,8 +1714,9 @@ macro_rules! php_sqlx_impl_driver_inner {
 
             /// Fetches all matching rows, applying retry, connection routing, and the query hook.
             ///
-            /// Shared by `query_all` and `query_all_into`; the only thing that differs
-            /// between those is how the resulting rows are converted to PHP values.
+            /// Shared by `query_all`, `query_all_into`, `query_column` and the dictionary
+            /// methods; the only thing that differs between those is how the resulting
+            /// rows are converted to PHP values.
             fn fetch_all_rows(
                 &self,
                 query: &str,
@@ -751,28 +1724,37 @@ macro_rules! php_sqlx_impl_driver_inner {
             ) -> $crate::error::Result<
                 Vec<<sqlx_oldapi::$database as sqlx_oldapi::Database>::Row>,
             > {
-                let sql_inline = if self.query_hook.is_set() {
-                    self.render_query_inline(query, parameters.clone()).ok()
-                } else {
-                    None
-                };
+                let (query, parameters, meta) =
+                    self.before_query(query, parameters, QueryKind::Query)?;
+                let sql_inline = self.instrumentation_inline(&query, parameters.as_ref());
 
-                let (query, values) = self.render_query(query, parameters)?;
-                let timer = QueryTimer::new(&self.query_hook, query.clone(), sql_inline);
+                let (query, values, instrumentation) =
+                    self.render_guarded(&query, parameters.clone())?;
+                let timer = self.start_timer(&query, sql_inline, meta, instrumentation);
 
                 let result = self.with_retry(|| {
                     if let Some(mut tx) = self.retrieve_ongoing_transaction() {
-                        let val = self.run_query(
-                            bind_values(sqlx_oldapi::query(&query), &values)?.fetch_all(&mut *tx),
-                        );
+                        let val = self.observed(&mut *tx, |conn| {
+                            self.run_query(
+                                bind_values(sqlx_oldapi::query(&query), &values)?.fetch_all(conn),
+                            )
+                        });
                         self.place_ongoing_transaction(tx);
                         val
                     } else if let Some(mut conn) = self.retrieve_pinned_connection() {
-                        let val = self.run_query(
-                            bind_values(sqlx_oldapi::query(&query), &values)?.fetch_all(&mut *conn),
-                        );
+                        let val = self.observed(&mut *conn, |conn| {
+                            self.run_query(
+                                bind_values(sqlx_oldapi::query(&query), &values)?.fetch_all(conn),
+                            )
+                        });
                         self.return_pinned_connection(conn);
                         val
+                    } else if let Some(mut conn) = self.acquire_prepared(self.get_read_pool())? {
+                        self.observed(&mut *conn, |conn| {
+                            self.run_query(
+                                bind_values(sqlx_oldapi::query(&query), &values)?.fetch_all(conn),
+                            )
+                        })
                     } else {
                         self.run_query(
                             bind_values(sqlx_oldapi::query(&query), &values)?
@@ -782,7 +1764,8 @@ macro_rules! php_sqlx_impl_driver_inner {
                     .and_then(|inner| {
                         inner.map_err(|err| SqlxError::query_with_source(&query, err))
                     })
-                });
+                })
+                .map_err(|err| self.with_error_context(err, parameters.as_ref()));
 
                 if let Some(t) = timer {
                     match &result {
@@ -791,50 +1774,289 @@ macro_rules! php_sqlx_impl_driver_inner {
                     }
                 }
 
-                result.and_then(|rows| {
-                    if self.options.max_rows > 0
-                        && u64::try_from(rows.len()).is_ok_and(|n| n > self.options.max_rows)
-                    {
-                        Err(SqlxError::TooManyRows {
-                            max: self.options.max_rows,
-                        })
-                    } else {
-                        Ok(rows)
+                result.and_then(|rows| self.check_max_rows(rows))
+            }
+
+            /// Enforces `OPT_MAX_ROWS` on a buffered result set.
+            fn check_max_rows<R>(&self, rows: Vec<R>) -> $crate::error::Result<Vec<R>> {
+                if self.options.max_rows > 0
+                    && u64::try_from(rows.len()).is_ok_and(|n| n > self.options.max_rows)
+                {
+                    Err(SqlxError::TooManyRows {
+                        max: self.options.max_rows,
+                    })
+                } else {
+                    Ok(rows)
+                }
+            }
+
+            /// Starts fetching all rows of a query in the background, on a pool
+            /// connection of its own; collect them with [`Self::join_rows`].
+            ///
+            /// The statement goes through the before-query hook, the query firewall,
+            /// the cost guard and the circuit breaker here, on the request thread.
+            /// Inside a transaction or on a pinned connection it runs at once, on
+            /// that connection. It is not retried.
+            pub fn spawn_fetch_all(
+                &self,
+                query: &str,
+                parameters: Option<BTreeMap<String, ParameterValue>>,
+            ) -> $crate::error::Result<
+                StatementTask<Vec<<sqlx_oldapi::$database as sqlx_oldapi::Database>::Row>>,
+            > {
+                self.spawn_statement(
+                    query,
+                    parameters,
+                    QueryKind::Query,
+                    self.get_read_pool().clone(),
+                    Self::fetch_all_rows,
+                    |sql, values, pool| async move {
+                        bind_values(sqlx_oldapi::query(&sql), &values)?
+                            .fetch_all(&pool)
+                            .await
+                            .map_err(|err| SqlxError::query_with_source(&sql, err))
+                    },
+                )
+            }
+
+            /// Starts fetching at most one row of a query in the background, like
+            /// [`Self::spawn_fetch_all`]; collect it with [`Self::join_maybe_row`].
+            pub fn spawn_fetch_maybe_row(
+                &self,
+                query: &str,
+                parameters: Option<BTreeMap<String, ParameterValue>>,
+            ) -> $crate::error::Result<
+                StatementTask<Option<<sqlx_oldapi::$database as sqlx_oldapi::Database>::Row>>,
+            > {
+                self.spawn_statement(
+                    query,
+                    parameters,
+                    QueryKind::Query,
+                    self.get_read_pool().clone(),
+                    Self::fetch_maybe_row,
+                    |sql, values, pool| async move {
+                        bind_values(sqlx_oldapi::query(&sql), &values)?
+                            .fetch_optional(&pool)
+                            .await
+                            .map_err(|err| SqlxError::query_with_source(&sql, err))
+                    },
+                )
+            }
+
+            /// Starts executing a statement in the background, like
+            /// [`Self::spawn_fetch_all`] but on the primary; collect the number of
+            /// affected rows with [`Self::join_execute`].
+            pub fn spawn_execute(
+                &self,
+                query: &str,
+                parameters: Option<BTreeMap<String, ParameterValue>>,
+            ) -> $crate::error::Result<StatementTask<u64>> {
+                self.spawn_statement(
+                    query,
+                    parameters,
+                    QueryKind::Execute,
+                    self.pool.clone(),
+                    Self::execute,
+                    |sql, values, pool| async move {
+                        bind_values(sqlx_oldapi::query(&sql), &values)?
+                            .execute(&pool)
+                            .await
+                            .map(|done| done.rows_affected())
+                            .map_err(|err| SqlxError::query_with_source(&sql, err))
+                    },
+                )
+            }
+
+            /// Spawns `run` on `pool` with the rendered statement, or runs it at once
+            /// with `run_here` inside a transaction or on a pinned connection.
+            fn spawn_statement<T, F>(
+                &self,
+                query: &str,
+                parameters: Option<BTreeMap<String, ParameterValue>>,
+                kind: QueryKind,
+                pool: Pool<$database>,
+                run_here: impl FnOnce(
+                    &Self,
+                    &str,
+                    Option<BTreeMap<String, ParameterValue>>,
+                ) -> $crate::error::Result<T>,
+                run: impl FnOnce(String, Vec<ParameterValue>, Pool<$database>) -> F,
+            ) -> $crate::error::Result<StatementTask<T>>
+            where
+                T: Send + 'static,
+                F: std::future::Future<Output = $crate::error::Result<T>> + Send + 'static,
+            {
+                self.ensure_open()?;
+                if self.has_active_transaction() || self.has_pinned_connection() {
+                    return Ok(StatementTask::Ready(run_here(self, query, parameters)));
+                }
+                let remaining = self.deadline.remaining("query")?;
+                let (query, parameters, meta) = self.before_query(query, parameters, kind)?;
+                let sql_inline = self.instrumentation_inline(&query, parameters.as_ref());
+                let (sql, values, instrumentation) =
+                    self.render_guarded(&query, parameters.clone())?;
+                let permit = self
+                    .circuit_breaker
+                    .as_ref()
+                    .map(CircuitBreaker::permit)
+                    .transpose()?;
+                let timer = self
+                    .start_timer(&sql, sql_inline, meta, instrumentation)
+                    .map(QueryTimer::detach);
+
+                let timeout = self.options.query_timeout;
+                let completion = Arc::new(Completion::default());
+                let signal = completion.clone();
+                let statement = run(sql, values, pool);
+                let handle = RUNTIME.spawn(async move {
+                    let started = std::time::Instant::now();
+                    let result = within(statement, "query", timeout, remaining).await;
+                    if let Some(permit) = permit {
+                        permit.settle(&result);
                     }
+                    signal.complete();
+                    (result, started.elapsed())
+                });
+                Ok(StatementTask::Running {
+                    handle,
+                    timer,
+                    parameters,
+                    completion,
+                })
+            }
+
+            /// Waits for a statement started by [`Self::spawn_statement`] and returns
+            /// its result, reporting it to the query hook, slow query log and profiler
+            /// with the row count given by `count`.
+            fn join_statement<T>(
+                &self,
+                task: StatementTask<T>,
+                count: impl FnOnce(&T) -> Option<u64>,
+            ) -> $crate::error::Result<T> {
+                let (handle, timer, parameters) = match task {
+                    StatementTask::Ready(result) => return result,
+                    StatementTask::Running {
+                        handle,
+                        timer,
+                        parameters,
+                        ..
+                    } => (handle, timer, parameters),
+                };
+                let (result, duration) = RUNTIME.block_on(handle).unwrap_or_else(|err| {
+                    (
+                        Err(SqlxError::Other(format!("Background query failed: {err}"))),
+                        std::time::Duration::ZERO,
+                    )
+                });
+                let result = result.map_err(|err| {
+                    self.with_error_context(
+                        err.with_query_context(|context| context.attempt = Some(1)),
+                        parameters.as_ref(),
+                    )
+                });
+
+                if let Some(t) = timer {
+                    let t = t.attach(&self.query_hook, self.slow_query_log.as_ref());
+                    match &result {
+                        Ok(value) => t.finish_after(duration, count(value), None),
+                        Err(err) => t.finish_after(duration, None, Some(&err.to_string())),
+                    }
+                }
+
+                result
+            }
+
+            /// Waits for a statement started by [`Self::spawn_fetch_all`] and returns
+            /// its rows, reporting it to the query hook, slow query log and profiler.
+            pub fn join_rows(
+                &self,
+                task: StatementTask<Vec<<sqlx_oldapi::$database as sqlx_oldapi::Database>::Row>>,
+            ) -> $crate::error::Result<
+                Vec<<sqlx_oldapi::$database as sqlx_oldapi::Database>::Row>,
+            > {
+                self.join_statement(task, |rows| u64::try_from(rows.len()).ok())
+                    .and_then(|rows| self.check_max_rows(rows))
+            }
+
+            /// Collects a statement started by [`Self::spawn_fetch_all`] and converts
+            /// its rows like [`Self::query_all`].
+            pub fn join_all(
+                &self,
+                task: StatementTask<Vec<<sqlx_oldapi::$database as sqlx_oldapi::Database>::Row>>,
+                associative_arrays: Option<bool>,
+            ) -> $crate::error::Result<Zval> {
+                let assoc = associative_arrays.unwrap_or(self.options.associative_arrays);
+                let rows: Vec<Zval> = self
+                    .join_rows(task)?
+                    .into_iter()
+                    .map(|row| row.into_zval(assoc, self.options.time_zone))
+                    .try_collect()?;
+                rows.into_zval(false).map_err(|err| SqlxError::Conversion {
+                    message: format!("{err}"),
                 })
             }
 
+            /// Collects a statement started by [`Self::spawn_fetch_maybe_row`] and
+            /// converts its row like [`Self::query_maybe_row`].
+            pub fn join_maybe_row(
+                &self,
+                task: StatementTask<Option<<sqlx_oldapi::$database as sqlx_oldapi::Database>::Row>>,
+                associative_arrays: Option<bool>,
+            ) -> $crate::error::Result<Zval> {
+                let assoc = associative_arrays.unwrap_or(self.options.associative_arrays);
+                Ok(self
+                    .join_statement(task, |row| Some(u64::from(row.is_some())))?
+                    .map(|row| row.into_zval(assoc, self.options.time_zone))
+                    .transpose()?
+                    .unwrap_or_else(Zval::null))
+            }
+
+            /// Collects a statement started by [`Self::spawn_execute`] and returns
+            /// the number of affected rows.
+            pub fn join_execute(&self, task: StatementTask<u64>) -> $crate::error::Result<u64> {
+                self.join_statement(task, |rows| Some(*rows))
+            }
+
             /// Fetches exactly one row, applying retry, connection routing, and the query hook.
             ///
-            /// Shared by `query_row` and `query_row_into`.
+            /// Shared by `query_row`, `query_row_into` and `query_value`.
             fn fetch_one_row(
                 &self,
                 query: &str,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<<sqlx_oldapi::$database as sqlx_oldapi::Database>::Row>
             {
-                let sql_inline = if self.query_hook.is_set() {
-                    self.render_query_inline(query, parameters.clone()).ok()
-                } else {
-                    None
-                };
+                let (query, parameters, meta) =
+                    self.before_query(query, parameters, QueryKind::Query)?;
+                let sql_inline = self.instrumentation_inline(&query, parameters.as_ref());
 
-                let (query, values) = self.render_query(query, parameters)?;
-                let timer = QueryTimer::new(&self.query_hook, query.clone(), sql_inline);
+                let (query, values, instrumentation) =
+                    self.render_guarded(&query, parameters.clone())?;
+                let timer = self.start_timer(&query, sql_inline, meta, instrumentation);
 
                 let result = self.with_retry(|| {
                     if let Some(mut tx) = self.retrieve_ongoing_transaction() {
-                        let val = self.run_query(
-                            bind_values(sqlx_oldapi::query(&query), &values)?.fetch_one(&mut *tx),
-                        );
+                        let val = self.observed(&mut *tx, |conn| {
+                            self.run_query(
+                                bind_values(sqlx_oldapi::query(&query), &values)?.fetch_one(conn),
+                            )
+                        });
                         self.place_ongoing_transaction(tx);
                         val
                     } else if let Some(mut conn) = self.retrieve_pinned_connection() {
-                        let val = self.run_query(
-                            bind_values(sqlx_oldapi::query(&query), &values)?.fetch_one(&mut *conn),
-                        );
+                        let val = self.observed(&mut *conn, |conn| {
+                            self.run_query(
+                                bind_values(sqlx_oldapi::query(&query), &values)?.fetch_one(conn),
+                            )
+                        });
                         self.return_pinned_connection(conn);
                         val
+                    } else if let Some(mut conn) = self.acquire_prepared(self.get_read_pool())? {
+                        self.observed(&mut *conn, |conn| {
+                            self.run_query(
+                                bind_values(sqlx_oldapi::query(&query), &values)?.fetch_one(conn),
+                            )
+                        })
                     } else {
                         self.run_query(
                             bind_values(sqlx_oldapi::query(&query), &values)?
@@ -844,7 +2066,8 @@ macro_rules! php_sqlx_impl_driver_inner {
                     .and_then(|inner| {
                         inner.map_err(|err| SqlxError::query_with_source(&query, err))
                     })
-                });
+                })
+                .map_err(|err| self.with_error_context(err, parameters.as_ref()));
 
                 if let Some(t) = timer {
                     match &result {
@@ -859,7 +2082,7 @@ macro_rules! php_sqlx_impl_driver_inner {
             /// Fetches at most one row (returning `None` when no row matched), applying
             /// retry, connection routing, and the query hook.
             ///
-            /// Shared by `query_maybe_row` and `query_maybe_row_into`.
+            /// Shared by `query_maybe_row`, `query_maybe_row_into` and `query_maybe_value`.
             fn fetch_maybe_row(
                 &self,
                 query: &str,
@@ -867,28 +2090,37 @@ macro_rules! php_sqlx_impl_driver_inner {
             ) -> $crate::error::Result<
                 Option<<sqlx_oldapi::$database as sqlx_oldapi::Database>::Row>,
             > {
-                let sql_inline = if self.query_hook.is_set() {
-                    self.render_query_inline(query, parameters.clone()).ok()
-                } else {
-                    None
-                };
+                let (query, parameters, meta) =
+                    self.before_query(query, parameters, QueryKind::Query)?;
+                let sql_inline = self.instrumentation_inline(&query, parameters.as_ref());
 
-                let (query, values) = self.render_query(query, parameters)?;
-                let timer = QueryTimer::new(&self.query_hook, query.clone(), sql_inline);
+                let (query, values, instrumentation) =
+                    self.render_guarded(&query, parameters.clone())?;
+                let timer = self.start_timer(&query, sql_inline, meta, instrumentation);
 
                 let result = self.with_retry(|| {
                     if let Some(mut tx) = self.retrieve_ongoing_transaction() {
-                        let val = self.run_query(
-                            bind_values(sqlx_oldapi::query(&query), &values)?.fetch_one(&mut *tx),
-                        );
+                        let val = self.observed(&mut *tx, |conn| {
+                            self.run_query(
+                                bind_values(sqlx_oldapi::query(&query), &values)?.fetch_one(conn),
+                            )
+                        });
                         self.place_ongoing_transaction(tx);
                         val
                     } else if let Some(mut conn) = self.retrieve_pinned_connection() {
-                        let val = self.run_query(
-                            bind_values(sqlx_oldapi::query(&query), &values)?.fetch_one(&mut *conn),
-                        );
+                        let val = self.observed(&mut *conn, |conn| {
+                            self.run_query(
+                                bind_values(sqlx_oldapi::query(&query), &values)?.fetch_one(conn),
+                            )
+                        });
                         self.return_pinned_connection(conn);
                         val
+                    } else if let Some(mut conn) = self.acquire_prepared(self.get_read_pool())? {
+                        self.observed(&mut *conn, |conn| {
+                            self.run_query(
+                                bind_values(sqlx_oldapi::query(&query), &values)?.fetch_one(conn),
+                            )
+                        })
                     } else {
                         self.run_query(
                             bind_values(sqlx_oldapi::query(&query), &values)?
@@ -901,7 +2133,8 @@ macro_rules! php_sqlx_impl_driver_inner {
                             _ => Err(SqlxError::query_with_source(&query, err)),
                         })
                     })
-                });
+                })
+                .map_err(|err| self.with_error_context(err, parameters.as_ref()));
 
                 if let Some(t) = timer {
                     match &result {
@@ -935,7 +2168,10 @@ macro_rules! php_sqlx_impl_driver_inner {
                 associative_arrays: Option<bool>,
             ) -> $crate::error::Result<Zval> {
                 self.fetch_one_row(query, parameters)?
-                    .into_zval(associative_arrays.unwrap_or(self.options.associative_arrays))
+                    .into_zval(
+                        associative_arrays.unwrap_or(self.options.associative_arrays),
+                        self.options.time_zone,
+                    )
             }
 
             /// Executes an SQL query and hydrates the single result row into an instance
@@ -957,7 +2193,7 @@ macro_rules! php_sqlx_impl_driver_inner {
                 let target = $crate::conversion::resolve_target(target)?;
                 let parameters = $crate::conversion::with_target_select(&target, query, parameters);
                 self.fetch_one_row(query, parameters)?
-                    .into_target_zval(&target, assoc)
+                    .into_target_zval(&target, assoc, self.options.time_zone)
             }
 
             /// Executes an SQL query and returns a single row if available, or `null` if no rows are returned.
@@ -985,7 +2221,10 @@ macro_rules! php_sqlx_impl_driver_inner {
                 Ok(self
                     .fetch_maybe_row(query, parameters)?
                     .map(|row| {
-                        row.into_zval(associative_arrays.unwrap_or(self.options.associative_arrays))
+                        row.into_zval(
+                            associative_arrays.unwrap_or(self.options.associative_arrays),
+                            self.options.time_zone,
+                        )
                     })
                     .transpose()?
                     .unwrap_or_else(Zval::null))
@@ -1011,7 +2250,7 @@ macro_rules! php_sqlx_impl_driver_inner {
                 let parameters = $crate::conversion::with_target_select(&target, query, parameters);
                 Ok(self
                     .fetch_maybe_row(query, parameters)?
-                    .map(|row| row.into_target_zval(&target, assoc))
+                    .map(|row| row.into_target_zval(&target, assoc, self.options.time_zone))
                     .transpose()?
                     .unwrap_or_else(Zval::null))
             }
@@ -1042,7 +2281,16 @@ macro_rules! php_sqlx_impl_driver_inner {
                 use tokio_util::sync::CancellationToken;
 
                 self.ensure_open()?;
-                let (rendered_query, values) = self.render_query(query, parameters)?;
+                // The stream itself is bounded by the server-side statement timeout only
+                self.deadline.remaining("query")?;
+                let (query, parameters, meta) =
+                    self.before_query(query, parameters, QueryKind::Stream)?;
+                let sql_inline = self.instrumentation_inline(&query, parameters.as_ref());
+                let (rendered_query, values, instrumentation) =
+                    self.render_guarded(&query, parameters.clone())?;
+                let timer = self
+                    .start_timer(&rendered_query, sql_inline, meta, instrumentation)
+                    .map(QueryTimer::detach);
 
                 // Create a bounded channel - the buffer provides backpressure
                 let (tx, rx) = tokio::sync::mpsc::channel(batch_size);
@@ -1084,19 +2332,24 @@ macro_rules! php_sqlx_impl_driver_inner {
                             }
                         }
                         Err(err) => {
-                            let _ = tx.blocking_send(Err(SqlxError::query_with_source(
-                                &rendered_query,
-                                err,
+                            let _ = tx.blocking_send(Err(self.with_error_context(
+                                SqlxError::query_with_source(&rendered_query, err)
+                                    .with_query_context(|context| context.attempt = Some(1)),
+                                parameters.as_ref(),
                             )));
                         }
                     }
 
-                    return Ok((rx, cancel_token));
+                    return Ok((rx, cancel_token, timer));
                 }
 
                 // For normal queries (no transaction/pinned connection), use true streaming
                 let pool = self.pool.clone();
                 let query_for_error = rendered_query.clone();
+                let driver_name = stringify!($database).to_ascii_lowercase();
+                let masked = parameters
+                    .as_ref()
+                    .map(|parameters| mask_parameters(parameters, &self.options.redact_params));
                 let task_cancel_token = cancel_token.clone();
 
                 // Spawn a background task that streams rows and sends through channel
@@ -1128,7 +2381,14 @@ macro_rules! php_sqlx_impl_driver_inner {
                                     Some(row_result) => {
                                         let result = match row_result {
                                             Ok(row) => Ok(row),
-                                            Err(err) => Err(SqlxError::query_with_source(&query_for_error, err)),
+                                            Err(err) => Err(
+                                                SqlxError::query_with_source(&query_for_error, err)
+                                                    .with_query_context(|context| {
+                                                        context.driver = Some(driver_name.clone());
+                                                        context.parameters = masked.clone();
+                                                        context.attempt = Some(1);
+                                                    }),
+                                            ),
                                         };
 
                                         // Send the result; if receiver is dropped, stop streaming
@@ -1147,7 +2407,7 @@ macro_rules! php_sqlx_impl_driver_inner {
                     // Channel is automatically closed when tx is dropped
                 });
 
-                Ok((rx, cancel_token))
+                Ok((rx, cancel_token, timer))
             }
 
             /// Executes an SQL query and returns all results.
@@ -1174,7 +2434,7 @@ macro_rules! php_sqlx_impl_driver_inner {
                 let assoc = associative_arrays.unwrap_or(self.options.associative_arrays);
                 self.fetch_all_rows(query, parameters)?
                     .into_iter()
-                    .map(|row| row.into_zval(assoc))
+                    .map(|row| row.into_zval(assoc, self.options.time_zone))
                     .try_collect()
             }
 
@@ -1194,11 +2454,12 @@ macro_rules! php_sqlx_impl_driver_inner {
                 associative_arrays: Option<bool>,
             ) -> $crate::error::Result<Vec<Zval>> {
                 let assoc = associative_arrays.unwrap_or(self.options.associative_arrays);
+                let time_zone = self.options.time_zone;
                 let target = $crate::conversion::resolve_target(target)?;
                 let parameters = $crate::conversion::with_target_select(&target, query, parameters);
                 self.fetch_all_rows(query, parameters)?
                     .into_iter()
-                    .map(|row| row.into_target_zval(&target, assoc))
+                    .map(|row| row.into_target_zval(&target, assoc, time_zone))
                     .try_collect()
             }
 
@@ -1239,14 +2500,94 @@ macro_rules! php_sqlx_impl_driver_inner {
 
             /// Returns the query with all parameters inlined for debugging.
             ///
-            /// Unlike `dry()` which returns placeholders and values separately,
-            /// this method produces a single SQL string with literal values embedded.
-            pub fn dry_inline(
+            /// Unlike `dry()` which returns placeholders and values separately,
+            /// this method produces a single SQL string with literal values embedded.
+            pub fn dry_inline(
+                &self,
+                query: &str,
+                parameters: Option<BTreeMap<String, ParameterValue>>,
+            ) -> $crate::error::Result<String> {
+                self.render_query_inline(query, parameters)
+            }
+
+            /// Returns the execution plan of a query.
+            ///
+            /// The query is rendered through the normal AST pipeline (placeholders,
+            /// conditional blocks, query tags) and explained in the active transaction
+            /// or on the pinned connection if there is one, so that it sees their
+            /// temporary tables and uncommitted changes, and on a pooled connection
+            /// otherwise. With `analyze`, the statement is executed inside a
+            /// transaction that is rolled back. The query allowlist applies as for any
+            /// other statement.
+            ///
+            /// # Errors
+            /// Returns an error if the allowlist refuses the statement, rendering fails
+            /// or the database rejects the `EXPLAIN`.
+            pub fn explain(
                 &self,
                 query: &str,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
-            ) -> $crate::error::Result<String> {
-                self.render_query_inline(query, parameters)
+                options: ExplainOptions,
+            ) -> $crate::error::Result<Explained> {
+                self.ensure_open()?;
+                if let Some(allowlist) = &self.query_allowlist {
+                    allowlist.check(&self.parse_query(query)?.fingerprint(&self.settings))?;
+                }
+                let (query, values) = self.render_query(query, parameters)?;
+                self.explain_rendered(&query, &values, options)
+            }
+
+            /// Fetches the single row returned by an `EXPLAIN` statement.
+            ///
+            /// In the active transaction, it runs in a savepoint that is rolled back,
+            /// so a failing `EXPLAIN` doesn't abort the transaction. On the pinned
+            /// connection, it runs there. Otherwise it runs on a read pool, or in a
+            /// transaction on the primary with `rolled_back`. With `rolled_back`, the
+            /// changes of an executed statement are always rolled back.
+            pub(crate) fn fetch_explain<'q>(
+                &self,
+                query: sqlx_oldapi::query::Query<
+                    'q,
+                    $database,
+                    <$database as sqlx_oldapi::database::HasArguments<'q>>::Arguments,
+                >,
+                rolled_back: bool,
+            ) -> $crate::error::Result<
+                Result<<$database as sqlx_oldapi::Database>::Row, sqlx_oldapi::Error>,
+            > {
+                if let Some(mut tx) = self.retrieve_ongoing_transaction() {
+                    let row = self.run_query(async {
+                        let mut savepoint = sqlx_oldapi::Connection::begin(&mut *tx).await?;
+                        let row = query.fetch_one(&mut *savepoint).await;
+                        savepoint.rollback().await?;
+                        row
+                    });
+                    self.place_ongoing_transaction(tx);
+                    row
+                } else if let Some(mut conn) = self.retrieve_pinned_connection() {
+                    let row = if rolled_back {
+                        self.run_query(async {
+                            let mut tx = sqlx_oldapi::Connection::begin(&mut *conn).await?;
+                            let row = query.fetch_one(&mut *tx).await;
+                            tx.rollback().await?;
+                            row
+                        })
+                    } else {
+                        self.run_query(query.fetch_one(&mut *conn))
+                    };
+                    self.return_pinned_connection(conn);
+                    row
+                } else if rolled_back {
+                    let pool = &self.pool;
+                    self.run_query(async move {
+                        let mut tx = pool.begin().await?;
+                        let row = query.fetch_one(&mut *tx).await;
+                        tx.rollback().await?;
+                        row
+                    })
+                } else {
+                    self.run_query(query.fetch_one(self.get_read_pool()))
+                }
             }
 
             /// Executes an SQL query and returns a dictionary (map) indexed by the first column of each row.
@@ -1280,41 +2621,21 @@ macro_rules! php_sqlx_impl_driver_inner {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
                 associative_arrays: Option<bool>,
             ) -> $crate::error::Result<Zval> {
-                let (query, values) = self.render_query(query, parameters)?;
                 let assoc = associative_arrays.unwrap_or(self.options.associative_arrays);
-                self.with_retry(|| {
-                    if let Some(mut tx) = self.retrieve_ongoing_transaction() {
-                        let val = RUNTIME.block_on(
-                            bind_values(sqlx_oldapi::query(&query), &values)?.fetch_all(&mut *tx),
-                        );
-                        self.place_ongoing_transaction(tx);
-                        val
-                    } else if let Some(mut conn) = self.retrieve_pinned_connection() {
-                        let val = RUNTIME.block_on(
-                            bind_values(sqlx_oldapi::query(&query), &values)?.fetch_all(&mut *conn),
-                        );
-                        self.return_pinned_connection(conn);
-                        val
-                    } else {
-                        RUNTIME.block_on(
-                            bind_values(sqlx_oldapi::query(&query), &values)?
-                                .fetch_all(self.get_read_pool()),
-                        )
-                    }
-                    .map_err(|err| SqlxError::query_with_source(&query, err))
-                })?
-                .into_iter()
-                .map(|row| {
-                    Ok((
-                        row.column_value_into_array_key(row.column(0))?,
-                        row.into_zval(assoc)?,
-                    ))
-                })
-                .try_fold(zend_array::new(), fold_into_zend_hashmap)?
-                .into_zval(false)
-                .map_err(|err| SqlxError::Conversion {
-                    message: format!("{err:?}"),
-                })
+                let time_zone = self.options.time_zone;
+                self.fetch_all_rows(query, parameters)?
+                    .into_iter()
+                    .map(|row| {
+                        Ok((
+                            row.column_value_into_array_key(row.column(0), time_zone)?,
+                            row.into_zval(assoc, time_zone)?,
+                        ))
+                    })
+                    .try_fold(zend_array::new(), fold_into_zend_hashmap)?
+                    .into_zval(false)
+                    .map_err(|err| SqlxError::Conversion {
+                        message: format!("{err:?}"),
+                    })
             }
 
             /// Executes an SQL query and returns a dictionary grouping rows by the first column.
@@ -1360,42 +2681,22 @@ macro_rules! php_sqlx_impl_driver_inner {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
                 associative_arrays: Option<bool>,
             ) -> $crate::error::Result<Zval> {
-                let (query, values) = self.render_query(query, parameters)?;
                 let assoc = associative_arrays.unwrap_or(self.options.associative_arrays);
+                let time_zone = self.options.time_zone;
 
-                self.with_retry(|| {
-                    if let Some(mut tx) = self.retrieve_ongoing_transaction() {
-                        let val = RUNTIME.block_on(
-                            bind_values(sqlx_oldapi::query(&query), &values)?.fetch_all(&mut *tx),
-                        );
-                        self.place_ongoing_transaction(tx);
-                        val
-                    } else if let Some(mut conn) = self.retrieve_pinned_connection() {
-                        let val = RUNTIME.block_on(
-                            bind_values(sqlx_oldapi::query(&query), &values)?.fetch_all(&mut *conn),
-                        );
-                        self.return_pinned_connection(conn);
-                        val
-                    } else {
-                        RUNTIME.block_on(
-                            bind_values(sqlx_oldapi::query(&query), &values)?
-                                .fetch_all(self.get_read_pool()),
-                        )
-                    }
-                    .map_err(|err| SqlxError::query_with_source(&query, err))
-                })?
-                .into_iter()
-                .map(|row| {
-                    Ok((
-                        row.column_value_into_array_key(row.column(0))?,
-                        row.into_zval(assoc)?,
-                    ))
-                })
-                .try_fold(zend_array::new(), fold_into_zend_hashmap_grouped)?
-                .into_zval(false)
-                .map_err(|err| SqlxError::Conversion {
-                    message: format!("{err:?}"),
-                })
+                self.fetch_all_rows(query, parameters)?
+                    .into_iter()
+                    .map(|row| {
+                        Ok((
+                            row.column_value_into_array_key(row.column(0), time_zone)?,
+                            row.into_zval(assoc, time_zone)?,
+                        ))
+                    })
+                    .try_fold(zend_array::new(), fold_into_zend_hashmap_grouped)?
+                    .into_zval(false)
+                    .map_err(|err| SqlxError::Conversion {
+                        message: format!("{err:?}"),
+                    })
             }
 
             /// Executes the given SQL query and returns a grouped dictionary where:
@@ -1439,41 +2740,21 @@ macro_rules! php_sqlx_impl_driver_inner {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
                 associative_arrays: Option<bool>,
             ) -> $crate::error::Result<Zval> {
-                let (query, values) = self.render_query(query, parameters)?;
                 let assoc = associative_arrays.unwrap_or(self.options.associative_arrays);
-                self.with_retry(|| {
-                    if let Some(mut tx) = self.retrieve_ongoing_transaction() {
-                        let val = RUNTIME.block_on(
-                            bind_values(sqlx_oldapi::query(&query), &values)?.fetch_all(&mut *tx),
-                        );
-                        self.place_ongoing_transaction(tx);
-                        val
-                    } else if let Some(mut conn) = self.retrieve_pinned_connection() {
-                        let val = RUNTIME.block_on(
-                            bind_values(sqlx_oldapi::query(&query), &values)?.fetch_all(&mut *conn),
-                        );
-                        self.return_pinned_connection(conn);
-                        val
-                    } else {
-                        RUNTIME.block_on(
-                            bind_values(sqlx_oldapi::query(&query), &values)?
-                                .fetch_all(self.get_read_pool()),
-                        )
-                    }
-                    .map_err(|err| SqlxError::query_with_source(&query, err))
-                })?
-                .into_iter()
-                .map(|row| {
-                    Ok((
-                        row.column_value_into_array_key(row.column(0))?,
-                        row.column_value_into_zval(row.column(1), assoc)?,
-                    ))
-                })
-                .try_fold(zend_array::new(), fold_into_zend_hashmap_grouped)?
-                .into_zval(false)
-                .map_err(|err| SqlxError::Conversion {
-                    message: format!("{err:?}"),
-                })
+                let time_zone = self.options.time_zone;
+                self.fetch_all_rows(query, parameters)?
+                    .into_iter()
+                    .map(|row| {
+                        Ok((
+                            row.column_value_into_array_key(row.column(0), time_zone)?,
+                            row.column_value_into_zval(row.column(1), assoc, time_zone)?,
+                        ))
+                    })
+                    .try_fold(zend_array::new(), fold_into_zend_hashmap_grouped)?
+                    .into_zval(false)
+                    .map_err(|err| SqlxError::Conversion {
+                        message: format!("{err:?}"),
+                    })
             }
 
             /// Executes an SQL query and returns a dictionary mapping the first column to the second column.
@@ -1509,41 +2790,21 @@ macro_rules! php_sqlx_impl_driver_inner {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
                 associative_arrays: Option<bool>,
             ) -> $crate::error::Result<Zval> {
-                let (query, values) = self.render_query(query, parameters)?;
                 let assoc = associative_arrays.unwrap_or(self.options.associative_arrays);
-                self.with_retry(|| {
-                    if let Some(mut tx) = self.retrieve_ongoing_transaction() {
-                        let val = RUNTIME.block_on(
-                            bind_values(sqlx_oldapi::query(&query), &values)?.fetch_all(&mut *tx),
-                        );
-                        self.place_ongoing_transaction(tx);
-                        val
-                    } else if let Some(mut conn) = self.retrieve_pinned_connection() {
-                        let val = RUNTIME.block_on(
-                            bind_values(sqlx_oldapi::query(&query), &values)?.fetch_all(&mut *conn),
-                        );
-                        self.return_pinned_connection(conn);
-                        val
-                    } else {
-                        RUNTIME.block_on(
-                            bind_values(sqlx_oldapi::query(&query), &values)?
-                                .fetch_all(self.get_read_pool()),
-                        )
-                    }
-                    .map_err(|err| SqlxError::query_with_source(&query, err))
-                })?
-                .into_iter()
-                .map(|row| {
-                    Ok((
-                        row.column_value_into_array_key(row.column(0))?,
-                        row.column_value_into_zval(row.column(1), assoc)?,
-                    ))
-                })
-                .try_fold(zend_array::new(), fold_into_zend_hashmap)?
-                .into_zval(false)
-                .map_err(|err| SqlxError::Conversion {
-                    message: format!("{err:?}"),
-                })
+                let time_zone = self.options.time_zone;
+                self.fetch_all_rows(query, parameters)?
+                    .into_iter()
+                    .map(|row| {
+                        Ok((
+                            row.column_value_into_array_key(row.column(0), time_zone)?,
+                            row.column_value_into_zval(row.column(1), assoc, time_zone)?,
+                        ))
+                    })
+                    .try_fold(zend_array::new(), fold_into_zend_hashmap)?
+                    .into_zval(false)
+                    .map_err(|err| SqlxError::Conversion {
+                        message: format!("{err:?}"),
+                    })
             }
 
             /// Describes table columns with their types and metadata.
@@ -1602,6 +2863,70 @@ macro_rules! php_sqlx_impl_driver_inner {
                 self.query_all(DESCRIBE_TABLE_QUERY, Some(params), Some(true))
             }
 
+            /// Opens the binary column `column` of the row of `table` matching `key`
+            /// as a read-only [`Blob`]($crate::blob::Blob), read a chunk at a time.
+            /// On `PostgreSQL`, a compressed value is first copied into a temporary
+            /// large object, so that each chunk doesn't decompress all of it again.
+            ///
+            /// Returns `None` if the value is `NULL`.
+            ///
+            /// # Errors
+            /// Returns an error if:
+            /// - a table, column or key name is invalid, or `key` is empty;
+            /// - no row matches `key`;
+            /// - the size query or the copy fails to execute.
+            pub fn open_blob(
+                self: &Arc<Self>,
+                table: &str,
+                column: &str,
+                key: BTreeMap<String, ParameterValue>,
+            ) -> $crate::error::Result<Option<$crate::blob::Blob>> {
+                for name in [table, column].into_iter().chain(key.keys().map(String::as_str)) {
+                    if !is_valid_ident(name) {
+                        return Err(SqlxError::InvalidIdentifier {
+                            value: name.to_string(),
+                        });
+                    }
+                }
+                if key.is_empty() {
+                    return Err(SqlxError::InvalidParameter {
+                        name: "key".to_string(),
+                        expected: "a non-empty array of column => value".to_string(),
+                    });
+                }
+
+                let quote = |name: &str| quote_identifier(name, &self.settings);
+                let condition = key
+                    .keys()
+                    .enumerate()
+                    .map(|(i, name)| format!("{} = $blob_key_{i}", quote(name)))
+                    .join(" AND ");
+                let select = |expr: &str| {
+                    format!(
+                        "SELECT {} FROM {} WHERE {condition}",
+                        expr.replace("{column}", &quote(column)),
+                        quote(table),
+                    )
+                };
+                let key = key
+                    .into_values()
+                    .enumerate()
+                    .map(|(i, value)| (format!("blob_key_{i}"), value))
+                    .collect();
+
+                if let Some(blob) = self.spooled_blob(&select, &key)? {
+                    return Ok(Some(blob));
+                }
+                $crate::blob::ColumnBlob::open(
+                    self.clone(),
+                    &select(BLOB_LENGTH_EXPR),
+                    select(BLOB_CHUNK_EXPR),
+                    key,
+                )?
+                .map($crate::blob::Blob::new)
+                .transpose()
+            }
+
             /// Sets the application name for this connection.
             ///
             /// This helps identify the connection in database monitoring tools:
@@ -1666,11 +2991,15 @@ macro_rules! php_sqlx_impl_driver_inner {
             /// such as savepoints or commit/rollback. If a transaction is already ongoing,
             /// the behavior depends on the SQL backend (may error or allow nesting).
             pub fn begin(&self) -> $crate::error::Result<()> {
-                self.place_ongoing_transaction(
-                    RUNTIME
-                        .block_on(self.pool.begin())
-                        .map_err(|err| SqlxError::Other(err.to_string()))?,
-                );
+                let meta = self.before_transaction("BEGIN", QueryKind::Begin)?;
+                let timer = self.start_timer("BEGIN", None, meta, Instrumentation::default());
+                let result = self.through_circuit(|| {
+                    self.block_on_within("begin", None, self.pool.begin())?
+                        .map_err(SqlxError::begin_failed)
+                });
+                Self::finish_transaction_timer(timer, &result);
+                self.place_ongoing_transaction(result?);
+                self.transaction_owner.claim();
                 Ok(())
             }
 
@@ -1685,13 +3014,18 @@ macro_rules! php_sqlx_impl_driver_inner {
                         name: savepoint.to_string(),
                     });
                 }
+                if !self.has_active_transaction() {
+                    return Err(SqlxError::NoActiveTransaction);
+                }
+                let sql = format!("SAVEPOINT {savepoint}");
+                let meta = self.before_transaction(&sql, QueryKind::Savepoint)?;
                 if let Some(mut tx) = self.retrieve_ongoing_transaction() {
+                    let timer = self.start_timer(&sql, None, meta, Instrumentation::default());
                     let val = RUNTIME
-                        .block_on(
-                            sqlx_oldapi::query(&format!("SAVEPOINT {savepoint}")).execute(&mut *tx),
-                        )
+                        .block_on(sqlx_oldapi::query(&sql).execute(&mut *tx))
                         .map_err(|err| SqlxError::Other(err.to_string()));
                     self.place_ongoing_transaction(tx);
+                    Self::finish_transaction_timer(timer, &val);
                     val?;
                     Ok(())
                 } else {
@@ -1712,14 +3046,18 @@ macro_rules! php_sqlx_impl_driver_inner {
                         name: savepoint.to_string(),
                     });
                 }
+                if !self.has_active_transaction() {
+                    return Err(SqlxError::NoActiveTransaction);
+                }
+                let sql = format!("ROLLBACK TO SAVEPOINT {savepoint}");
+                let meta = self.before_transaction(&sql, QueryKind::RollbackToSavepoint)?;
                 if let Some(mut tx) = self.retrieve_ongoing_transaction() {
+                    let timer = self.start_timer(&sql, None, meta, Instrumentation::default());
                     let val = RUNTIME
-                        .block_on(
-                            sqlx_oldapi::query(&format!("ROLLBACK TO SAVEPOINT {savepoint}"))
-                                .execute(&mut *tx),
-                        )
+                        .block_on(sqlx_oldapi::query(&sql).execute(&mut *tx))
                         .map_err(|err| SqlxError::Other(err.to_string()));
                     self.place_ongoing_transaction(tx);
+                    Self::finish_transaction_timer(timer, &val);
                     val?;
                     Ok(())
                 } else {
@@ -1740,14 +3078,18 @@ macro_rules! php_sqlx_impl_driver_inner {
                         name: savepoint.to_string(),
                     });
                 }
+                if !self.has_active_transaction() {
+                    return Err(SqlxError::NoActiveTransaction);
+                }
+                let sql = format!("RELEASE SAVEPOINT {savepoint}");
+                let meta = self.before_transaction(&sql, QueryKind::ReleaseSavepoint)?;
                 if let Some(mut tx) = self.retrieve_ongoing_transaction() {
+                    let timer = self.start_timer(&sql, None, meta, Instrumentation::default());
                     let val = RUNTIME
-                        .block_on(
-                            sqlx_oldapi::query(&format!("RELEASE SAVEPOINT {savepoint}"))
-                                .execute(&mut *tx),
-                        )
+                        .block_on(sqlx_oldapi::query(&sql).execute(&mut *tx))
                         .map_err(|err| SqlxError::Other(err.to_string()));
                     self.place_ongoing_transaction(tx);
+                    Self::finish_transaction_timer(timer, &val);
                     val?;
                     Ok(())
                 } else {
@@ -1763,11 +3105,19 @@ macro_rules! php_sqlx_impl_driver_inner {
             /// # Errors
             /// Returns an error if no transaction is active or the commit fails.
             pub fn commit(&self) -> $crate::error::Result<()> {
+                if !self.has_active_transaction() {
+                    return Err(SqlxError::NoActiveTransaction);
+                }
+                // Checked before taking the transaction, so that it can still be rolled back
+                self.deadline.remaining("commit")?;
+                let meta = self.before_transaction("COMMIT", QueryKind::Commit)?;
                 if let Some(tx) = self.retrieve_ongoing_transaction() {
-                    RUNTIME
+                    let timer = self.start_timer("COMMIT", None, meta, Instrumentation::default());
+                    let result = RUNTIME
                         .block_on(tx.commit())
-                        .map_err(SqlxError::commit_failed)?;
-                    Ok(())
+                        .map_err(SqlxError::commit_failed);
+                    Self::finish_transaction_timer(timer, &result);
+                    result
                 } else {
                     Err(SqlxError::NoActiveTransaction)
                 }
@@ -1781,11 +3131,19 @@ macro_rules! php_sqlx_impl_driver_inner {
             /// # Errors
             /// Returns an error if no transaction is active or the rollback fails.
             pub fn rollback(&self) -> $crate::error::Result<()> {
+                let meta = if self.has_active_transaction() {
+                    self.before_transaction("ROLLBACK", QueryKind::Rollback)?
+                } else {
+                    None
+                };
                 if let Some(tx) = self.retrieve_ongoing_transaction() {
-                    RUNTIME
+                    let timer =
+                        self.start_timer("ROLLBACK", None, meta, Instrumentation::default());
+                    let result = RUNTIME
                         .block_on(tx.rollback())
-                        .map_err(SqlxError::rollback_failed)?;
-                    Ok(())
+                        .map_err(SqlxError::rollback_failed);
+                    Self::finish_transaction_timer(timer, &result);
+                    result
                 } else {
                     Err(SqlxError::NoActiveTransaction)
                 }
@@ -1809,10 +3167,13 @@ macro_rules! php_sqlx_impl_driver_inner {
                 if self.has_pinned_connection() {
                     return Err(SqlxError::Other("Connection already pinned".to_string()));
                 }
-                let conn = RUNTIME.block_on(self.pool.acquire()).map_err(|err| {
-                    SqlxError::connection_with_source("Failed to acquire connection", err)
+                let conn = self.through_circuit(|| {
+                    self.block_on_within("acquire", None, self.pool.acquire())?.map_err(|err| {
+                        SqlxError::connection_with_source("Failed to acquire connection", err)
+                    })
                 })?;
                 *self.pinned_conn.write().expect("Poisoned pinned_conn") = Some(conn);
+                self.pinned_owner.claim();
                 Ok(())
             }
 
@@ -1833,6 +3194,131 @@ macro_rules! php_sqlx_impl_driver_inner {
                 }
             }
 
+            /// Resets what a request left behind on a persistent driver: rolls back
+            /// dangling transactions and resets the session of their connections and
+            /// of the pinned connection, which is unpinned (`RESET_SESSION_QUERY`), and
+            /// clears the per-request hooks, query tags, profile and deadline.
+            /// Connections whose session can't be reset are closed instead.
+            ///
+            /// Only the state the current thread's request set up is reset: under ZTS
+            /// the driver may be in use by requests on other threads.
+            ///
+            /// Returns a description of each dangling transaction or connection found,
+            /// for the warning raised at the end of the request.
+            pub fn reset_request_state(&self) -> Vec<String> {
+                type Manager = <$database as sqlx_oldapi::Database>::TransactionManager;
+                let mut cleaned = Vec::new();
+                let time_zone_query =
+                    self.options.time_zone.as_ref().and_then(set_time_zone_query);
+
+                let transactions = if self.transaction_owner.release_current() {
+                    std::mem::take(&mut *self.tx_stack.write().expect("Poisoned tx_stack"))
+                } else {
+                    Vec::new()
+                };
+                let count = transactions.len();
+                let mut closed = 0;
+                for mut tx in transactions.into_iter().rev() {
+                    // Rolled back through its connection, which stays out of the pool
+                    // until its session is reset
+                    let reset = RUNTIME.block_on(async {
+                        <Manager as sqlx_oldapi::TransactionManager>::rollback(&mut *tx).await?;
+                        Self::reset_session(&mut *tx, time_zone_query.as_deref()).await
+                    });
+                    if reset.is_err() {
+                        self.discard_on_release(&mut *tx);
+                        closed += 1;
+                    }
+                }
+                if count > 0 {
+                    cleaned.push(if closed > 0 {
+                        format!(
+                            "rolled back {count} dangling transaction(s) and closed {closed} \
+                             connection(s) whose session could not be reset"
+                        )
+                    } else {
+                        format!("rolled back {count} dangling transaction(s)")
+                    });
+                }
+
+                let pinned = if self.pinned_owner.release_current() {
+                    self.pinned_conn
+                        .write()
+                        .expect("Poisoned pinned_conn")
+                        .take()
+                } else {
+                    None
+                };
+                if let Some(mut conn) = pinned {
+                    let reset = RUNTIME
+                        .block_on(Self::reset_session(&mut *conn, time_zone_query.as_deref()));
+                    if reset.is_ok() {
+                        cleaned.push("reset and unpinned the pinned connection".to_string());
+                    } else {
+                        // Never hand a connection with leftover session state to the pool
+                        let _ = RUNTIME.block_on(sqlx_oldapi::Connection::close(conn.detach()));
+                        cleaned.push(
+                            "closed the pinned connection, whose session could not be reset"
+                                .to_string(),
+                        );
+                    }
+                }
+
+                if self.settings_owner.release_current() {
+                    self.query_hook.clear();
+                    self.before_query_hook.clear();
+                    self.retry_hook.clear();
+                    self.query_tags.clear();
+                    self.profiler.stop();
+                    self.deadline.set(None);
+                }
+                if let Some(guard) = &self.cost_guard {
+                    guard.forget_failures();
+                }
+                cleaned
+            }
+
+            /// Resets the session of a connection with `RESET_SESSION_QUERY` and sets
+            /// its time zone again. The statement cache is cleared first, as the reset
+            /// may drop the statements it refers to.
+            ///
+            /// # Errors
+            /// Fails on databases without a session reset.
+            async fn reset_session(
+                conn: &mut <$database as sqlx_oldapi::Database>::Connection,
+                time_zone_query: Option<&str>,
+            ) -> Result<(), sqlx_oldapi::Error> {
+                let Some(reset_query) = RESET_SESSION_QUERY else {
+                    return Err(sqlx_oldapi::Error::Protocol(
+                        "session reset is not supported".to_string(),
+                    ));
+                };
+                if STATEMENT_CACHE {
+                    clear_statement_cache(conn).await?;
+                }
+                sqlx_oldapi::Executor::execute(&mut *conn, reset_query).await?;
+                if let Some(time_zone_query) = time_zone_query {
+                    sqlx_oldapi::Executor::execute(&mut *conn, time_zone_query).await?;
+                }
+                Ok(())
+            }
+
+            /// Marks a connection (`DISCARD_MARK_QUERY`) so that the pool closes it
+            /// when it is returned instead of handing it out again. A connection that
+            /// can't be marked is broken, and is closed by the pool's release test.
+            fn discard_on_release(
+                &self,
+                conn: &mut <$database as sqlx_oldapi::Database>::Connection,
+            ) {
+                if RUNTIME
+                    .block_on(sqlx_oldapi::Executor::execute(conn, DISCARD_MARK_QUERY))
+                    .is_ok()
+                {
+                    self.pending_discards
+                        .fetch_add(1, std::sync::atomic::Ordering::AcqRel);
+                }
+            }
+
             /// Retrieves the pinned connection for use in a query.
             ///
             /// Returns the connection temporarily, caller must return it via `return_pinned_connection`.
@@ -1884,6 +3370,32 @@ macro_rules! php_sqlx_impl_driver_inner {
             }
         }
 
+        impl $crate::blob::BlobQuery for $struct {
+            fn blob_value(
+                &self,
+                query: &str,
+                parameters: BTreeMap<String, ParameterValue>,
+            ) -> $crate::error::Result<Option<Zval>> {
+                self.fetch_maybe_row(query, Some(parameters))?
+                    .map(|row| {
+                        row.column_value_into_zval(
+                            row.try_column(0)?,
+                            false,
+                            self.options.time_zone,
+                        )
+                    })
+                    .transpose()
+            }
+
+            fn blob_execute(
+                &self,
+                query: &str,
+                parameters: BTreeMap<String, ParameterValue>,
+            ) -> $crate::error::Result<()> {
+                self.execute(query, Some(parameters)).map(|_| ())
+            }
+        }
+
         impl Drop for $struct {
             fn drop(&mut self) {
                 // Gracefully close all connection pools when the driver is dropped.
@@ -1915,6 +3427,7 @@ mod tests {
             initial_backoff: Duration::from_millis(100),
             max_backoff: Duration::from_secs(5),
             multiplier: 2.0,
+            ..RetryPolicy::default()
         }
     }
 
@@ -1925,6 +3438,7 @@ mod tests {
             initial_backoff: Duration::from_millis(100),
             max_backoff: Duration::from_secs(5),
             multiplier: 2.0,
+            ..RetryPolicy::default()
         };
         assert!(disabled.is_disabled());
 
@@ -1974,6 +3488,7 @@ mod tests {
             initial_backoff: Duration::from_secs(1),
             max_backoff: Duration::from_secs(5),
             multiplier: 10.0,
+            ..RetryPolicy::default()
         };
         let error = Error::connection("connection reset");
 
@@ -1994,6 +3509,7 @@ mod tests {
             initial_backoff: Duration::from_millis(100),
             max_backoff: Duration::from_secs(5),
             multiplier: 2.0,
+            ..RetryPolicy::default()
         };
         let transient_error = Error::PoolExhausted { timeout_ms: 1000 };
 
@@ -2032,4 +3548,152 @@ mod tests {
                 .is_some()
         );
     }
+
+    #[test]
+    fn test_retry_jitter_parse() {
+        assert_eq!(RetryJitter::parse("Full"), Some(RetryJitter::Full));
+        assert_eq!(
+            RetryJitter::parse("decorrelated"),
+            Some(RetryJitter::Decorrelated)
+        );
+        assert_eq!(RetryJitter::parse("none"), Some(RetryJitter::None));
+        assert_eq!(RetryJitter::parse("equal"), None);
+    }
+
+    #[test]
+    fn test_retry_policy_full_jitter() {
+        let policy = RetryPolicy {
+            jitter: RetryJitter::Full,
+            ..test_policy()
+        };
+        let error = Error::connection("connection reset");
+        let next = |random| policy.next_delay(2, &error, Duration::ZERO, Duration::ZERO, random);
+
+        // Anywhere between zero and the exponential backoff of 400ms
+        assert_eq!(next(0.0), Some(Duration::ZERO));
+        assert_eq!(next(0.5), Some(Duration::from_millis(200)));
+        assert!(next(0.999).unwrap() < Duration::from_millis(400));
+    }
+
+    #[test]
+    fn test_retry_policy_decorrelated_jitter() {
+        let policy = RetryPolicy {
+            jitter: RetryJitter::Decorrelated,
+            max_attempts: 10,
+            ..test_policy()
+        };
+        let error = Error::connection("connection reset");
+        let next =
+            |previous, random| policy.next_delay(1, &error, previous, Duration::ZERO, random);
+
+        // Between the initial backoff and three times the previous delay
+        assert_eq!(next(Duration::ZERO, 0.0), Some(Duration::from_millis(100)));
+        assert_eq!(
+            next(Duration::from_millis(100), 0.5),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(
+            next(Duration::from_secs(1), 0.0),
+            Some(Duration::from_millis(100))
+        );
+
+        // Capped at max_backoff
+        assert_eq!(
+            next(Duration::from_secs(4), 0.9),
+            Some(Duration::from_secs(5))
+        );
+    }
+
+    #[test]
+    fn test_retry_policy_budget() {
+        let policy = RetryPolicy {
+            budget: Some(Duration::from_millis(500)),
+            ..test_policy()
+        };
+        let error = Error::connection("connection reset");
+
+        assert_eq!(
+            policy.next_delay(1, &error, Duration::ZERO, Duration::from_millis(250), 0.0),
+            Some(Duration::from_millis(200))
+        );
+        // 350ms spent + 200ms delay would exceed the 500ms budget
+        assert_eq!(
+            policy.next_delay(1, &error, Duration::ZERO, Duration::from_millis(350), 0.0),
+            None
+        );
+    }
+
+    /// A database error carrying a SQLSTATE, as the drivers report it.
+    #[derive(Debug)]
+    struct CodedError(&'static str);
+
+    impl std::fmt::Display for CodedError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "error {}", self.0)
+        }
+    }
+
+    impl std::error::Error for CodedError {}
+
+    impl sqlx_oldapi::error::DatabaseError for CodedError {
+        fn message(&self) -> &str {
+            "database error"
+        }
+
+        fn code(&self) -> Option<std::borrow::Cow<'_, str>> {
+            Some(self.0.into())
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+    }
+
+    fn database_error(code: &'static str) -> Error {
+        Error::Query {
+            message: "database error".to_string(),
+            query: None,
+            source: Some(Box::new(sqlx_oldapi::Error::Database(Box::new(
+                CodedError(code),
+            )))),
+            context: None,
+        }
+    }
+
+    #[test]
+    fn test_retry_policy_retryable_codes() {
+        let policy = RetryPolicy {
+            retryable_codes: vec!["40001".to_string(), "40p01".to_string()],
+            ..test_policy()
+        };
+        // Listed codes are retried, case-insensitively
+        assert!(policy.is_retryable(&database_error("40001")));
+        assert!(policy.is_retryable(&database_error("40P01")));
+        assert_eq!(
+            policy.should_retry(0, &database_error("40001")),
+            Some(Duration::from_millis(100))
+        );
+        // Unlisted codes are not
+        assert!(!policy.is_retryable(&database_error("23505")));
+        assert_eq!(policy.should_retry(0, &database_error("23505")), None);
+        // Errors without a database code are only retried if transient
+        assert!(!policy.is_retryable(&Error::query("serialization failure")));
+        assert!(policy.is_retryable(&Error::PoolExhausted { timeout_ms: 1000 }));
+    }
+
+    #[test]
+    fn test_jitter_sample_range() {
+        for _ in 0..100 {
+            let sample = jitter_sample();
+            assert!((0.0..1.0).contains(&sample));
+        }
+    }
 }
@@ -38,11 +38,20 @@
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
 pub mod ast;
+pub mod batch;
+pub mod binary;
+pub mod blob;
 pub mod by_clause;
+pub mod circuit_breaker;
+pub mod connection_manager;
 pub mod conversion;
+pub mod cost_guard;
+pub mod deadline;
 mod driver;
 pub mod error;
+pub mod explain;
 pub mod identifier;
+pub mod ini;
 pub mod inner_driver;
 pub mod interfaces;
 #[cfg(feature = "lazy-row")]
@@ -50,22 +59,36 @@ mod lazy_row;
 pub mod options;
 pub mod paginate_clause;
 pub mod param_value;
+pub mod pending;
 mod prepared_query;
+pub mod profiler;
+pub mod query_allowlist;
 pub mod query_builder;
 pub mod query_result;
+pub mod secret;
 pub mod select_clause;
 
 mod dbms;
 pub mod driver_factory;
 pub mod query_hook;
+pub mod query_tags;
+pub mod request_owner;
+pub mod slow_query_log;
+pub mod statement_cache;
+pub mod time_zone;
 #[cfg(test)]
 mod tests;
 mod types;
 pub mod utils;
 
 use dbms::{mssql, mysql, postgres};
+use ext_php_rs::error::php_error;
+use ext_php_rs::ffi::display_ini_entries;
+use ext_php_rs::flags::ErrorType;
 use ext_php_rs::prelude::*;
-pub use inner_driver::RetryPolicy;
+use ext_php_rs::zend::ModuleEntry;
+use ext_php_rs::{info_table_end, info_table_header, info_table_row, info_table_start};
+pub use inner_driver::{RetryJitter, RetryPolicy};
 #[cfg(feature = "lazy-row")]
 pub use lazy_row::{LazyRow, LazyRowJson};
 use std::num::NonZeroU32;
@@ -104,6 +127,10 @@ const DEFAULT_COLLAPSIBLE_IN: bool = true;
 /// When true, validates connection health before use (adds latency).
 const DEFAULT_TEST_BEFORE_ACQUIRE: bool = false;
 
+/// Default number of server-side prepared statements cached per connection
+/// (the `sqlx` default).
+const DEFAULT_STATEMENT_CACHE_CAPACITY: usize = 100;
+
 /// Default maximum retry attempts for transient failures (0 = disabled).
 const DEFAULT_RETRY_MAX_ATTEMPTS: u32 = 0;
 
@@ -126,7 +153,12 @@ pub use dbms::mysql::{MySqlDriver, MySqlPreparedQuery};
 /// utility classes with the PHP module.
 #[php_module]
 pub fn module(mut module: ModuleBuilder) -> ModuleBuilder {
-    module = module.name("sqlx").version(env!("CARGO_PKG_VERSION"));
+    module = module
+        .name("sqlx")
+        .version(env!("CARGO_PKG_VERSION"))
+        .startup_function(module_startup)
+        .request_shutdown_function(request_shutdown)
+        .info_function(module_info);
 
     // Register interfaces
     module = module
@@ -137,11 +169,18 @@ pub fn module(mut module: ModuleBuilder) -> ModuleBuilder {
 
     module = error::build(module);
     module = identifier::build(module);
+    module = secret::build(module);
+    module = binary::build(module);
+    module = blob::build(module);
     module = select_clause::build(module);
     module = by_clause::build(module);
     module = paginate_clause::build(module);
     module = query_builder::build(module);
+    module = profiler::build(module);
     module = driver_factory::build(module);
+    module = connection_manager::build(module);
+    module = pending::build(module);
+    module = batch::build(module);
     module = types::build(module);
 
     #[cfg(feature = "mysql")]
@@ -166,3 +205,99 @@ pub fn module(mut module: ModuleBuilder) -> ModuleBuilder {
 
     module
 }
+
+/// Module startup handler (`MINIT`): registers the `sqlx.*` `php.ini` directives.
+extern "C" fn module_startup(_type: i32, module_number: i32) -> i32 {
+    ini::register(module_number);
+    0
+}
+
+/// `phpinfo()` section: compiled features, effective defaults and live persistent pools.
+extern "C" fn module_info(module: *mut ModuleEntry) {
+    let enabled = |on: bool| if on { "enabled" } else { "disabled" };
+
+    info_table_start!();
+    info_table_row!("sqlx support", "enabled");
+    info_table_row!("Version", env!("CARGO_PKG_VERSION"));
+    info_table_row!("PostgreSQL driver", enabled(cfg!(feature = "postgres")));
+    info_table_row!("MySQL driver", enabled(cfg!(feature = "mysql")));
+    info_table_row!("MS SQL driver", enabled(cfg!(feature = "mssql")));
+    info_table_row!("Lazy rows", enabled(cfg!(feature = "lazy-row")));
+    info_table_row!("SIMD JSON", enabled(cfg!(feature = "simd-json")));
+    info_table_end!();
+
+    info_table_start!();
+    info_table_header!("Effective default", "Value");
+    for (directive, value) in ini::defaults().describe() {
+        info_table_row!(directive, value);
+    }
+    info_table_end!();
+
+    let mut pools = Vec::new();
+
+    #[cfg(feature = "mysql")]
+    {
+        pools.extend(mysql::MySqlDriver::persistent_pools());
+    }
+
+    #[cfg(feature = "postgres")]
+    {
+        pools.extend(postgres::PgDriver::persistent_pools());
+    }
+
+    #[cfg(feature = "mssql")]
+    {
+        pools.extend(mssql::MssqlDriver::persistent_pools());
+    }
+
+    info_table_start!();
+    info_table_header!("Persistent pool", "Driver", "URL", "Size", "Idle", "Max");
+    for pool in &pools {
+        let field = |key: &str| match &pool[key] {
+            serde_json::Value::String(value) => value.clone(),
+            value => value.to_string(),
+        };
+        info_table_row!(
+            field("name"),
+            field("driver"),
+            field("url"),
+            field("size"),
+            field("idle"),
+            field("max_connections")
+        );
+    }
+    info_table_end!();
+
+    unsafe { display_ini_entries(module) };
+}
+
+/// Request shutdown handler (`RSHUTDOWN`).
+///
+/// Persistent drivers outlive the request, so a request that dies mid-transaction
+/// or with a pinned connection would hand them over to the next request on the
+/// worker. The per-request state this request set up on them is reset here, and
+/// dangling transactions or connections are reported as PHP warnings. Under ZTS
+/// the state of requests running on other threads is left alone.
+extern "C" fn request_shutdown(_type: i32, _module_number: i32) -> i32 {
+    let mut warnings = Vec::new();
+
+    #[cfg(feature = "mysql")]
+    {
+        warnings.extend(mysql::MySqlDriver::reset_persistent());
+    }
+
+    #[cfg(feature = "postgres")]
+    {
+        warnings.extend(postgres::PgDriver::reset_persistent());
+    }
+
+    #[cfg(feature = "mssql")]
+    {
+        warnings.extend(mssql::MssqlDriver::reset_persistent());
+    }
+
+    for warning in &warnings {
+        php_error(&ErrorType::Warning, warning);
+    }
+    0
+}
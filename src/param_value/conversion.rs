@@ -1,13 +1,18 @@
 use crate::by_clause::ByClauseRendered;
+use crate::error::Error as SqlxError;
 use crate::paginate_clause::PaginateClauseRendered;
 use crate::param_value::ParameterValue;
+use crate::secret::Secret;
 use crate::select_clause::SelectClauseRendered;
+use crate::time_zone::OFFSET_DATETIME_FORMAT;
 use crate::types::JsonWrapper;
 use ext_php_rs::convert::{FromZval, IntoZval};
+use ext_php_rs::exception::PhpException;
 use ext_php_rs::flags::DataType;
-use ext_php_rs::types::{ZendClassObject, ZendHashTable, Zval};
+use ext_php_rs::types::{ZendClassObject, ZendHashTable, ZendObject, Zval};
 use itertools::Itertools;
 use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+use std::collections::BTreeMap;
 
 impl From<&str> for ParameterValue {
     /// Converts a `&str` into a `ParameterValue::Str`.
@@ -43,7 +48,9 @@ impl IntoZval for ParameterValue {
     /// Converts a `ParameterValue` into a PHP `Zval`.
     ///
     /// - `Str`, `Int`, `Float`, `Bool` map to PHP scalars.
+    /// - `Binary` becomes a PHP string holding the raw bytes.
     /// - `Array` and `Object` become PHP arrays.
+    /// - `Secret` stays a `Sqlx\Secret` object.
     /// - `Null` and clause values render as `null`.
     ///
     /// # Errors
@@ -74,6 +81,11 @@ impl IntoZval for ParameterValue {
             Self::DateTime(dt) => {
                 zv.set_string(&dt.format("%Y-%m-%d %H:%M:%S").to_string(), persistent)?;
             }
+            Self::DateTimeTz(dt) => {
+                zv.set_string(&dt.format(OFFSET_DATETIME_FORMAT).to_string(), persistent)?;
+            }
+            Self::Binary(bytes) => zv.set_binary(bytes),
+            Self::Secret(secret) => secret.set_zval(zv, persistent)?,
             Self::Null
             | Self::ByClauseRendered(_)
             | Self::SelectClauseRendered(_)
@@ -92,6 +104,9 @@ impl FromZval<'_> for ParameterValue {
     /// - Arrays are parsed into either `Array` or `Object` depending on their keys.
     /// - `stdClass` maps to `Object`.
     /// - Instances of known clause types are wrapped appropriately.
+    /// - Strings that are not valid UTF-8 become `Binary`.
+    /// - Stream resources are refused with a `ParameterException`: binding one
+    ///   would read it whole into memory.
     fn from_zval(zval: &Zval) -> Option<Self> {
         match zval.get_type() {
             DataType::Undef | DataType::Null | DataType::Void => Some(Self::Null),
@@ -99,7 +114,10 @@ impl FromZval<'_> for ParameterValue {
             DataType::True => Some(Self::Bool(true)),
             DataType::Long => Some(Self::Int(zval.long()?)),
             DataType::Double => Some(Self::Float(zval.double()?)),
-            DataType::String => Some(Self::String(zval.string()?)),
+            DataType::String => match zval.string() {
+                Some(string) => Some(Self::String(string)),
+                None => Some(Self::Binary(zval.binary::<u8>()?)),
+            },
             DataType::Array => {
                 let array = zval.array()?;
                 if array.has_sequential_keys() {
@@ -148,6 +166,17 @@ impl FromZval<'_> for ParameterValue {
                             .and_then(|x| x.obj.as_ref())?
                             .to_owned(),
                     )),
+                    "Sqlx\\Binary" => Some(Self::Binary(
+                        ZendClassObject::<crate::binary::Binary>::from_zend_obj(obj)
+                            .and_then(|x| x.obj.as_ref())?
+                            .bytes
+                            .clone(),
+                    )),
+                    "Sqlx\\Secret" => Some(
+                        ZendClassObject::<Secret>::from_zend_obj(obj)
+                            .and_then(|x| x.obj.as_ref())
+                            .map(|x| x.value.as_ref().clone().into_secret())?,
+                    ),
                     "Sqlx\\JsonWrapper" => Some(Self::Json(
                         ZendClassObject::<JsonWrapper>::from_zend_obj(obj)
                             .and_then(|x| x.obj.as_ref())
@@ -166,21 +195,7 @@ impl FromZval<'_> for ParameterValue {
                             .ok()?,
                     )),
                     // Handle DateTime and DateTimeImmutable (implements DateTimeInterface)
-                    "DateTime" | "DateTimeImmutable" => {
-                        // Call format() method to get ISO 8601 string representation
-                        // Format: Y-m-d H:i:s (e.g., "2024-06-15 14:30:45")
-                        let mut format_arg = Zval::new();
-                        format_arg.set_string("Y-m-d H:i:s", false).ok()?;
-                        let result = obj.try_call_method("format", vec![&format_arg]).ok()?;
-                        let datetime_str = result.string()?;
-                        // Parse into NaiveDateTime for proper database binding
-                        let ndt = chrono::NaiveDateTime::parse_from_str(
-                            &datetime_str,
-                            "%Y-%m-%d %H:%M:%S",
-                        )
-                        .ok()?;
-                        Some(Self::DateTime(ndt))
-                    }
+                    "DateTime" | "DateTimeImmutable" => datetime_from_object(obj),
                     other => {
                         // For other classes, try calling format() method if it exists
                         // This handles custom DateTimeInterface implementations
@@ -188,25 +203,26 @@ impl FromZval<'_> for ParameterValue {
                             || other.ends_with("Date")
                             || other.ends_with("Time")
                         {
-                            let mut format_arg = Zval::new();
-                            if format_arg.set_string("Y-m-d H:i:s", false).is_ok()
-                                && let Ok(result) = obj.try_call_method("format", vec![&format_arg])
-                                && let Some(datetime_str) = result.string()
-                                && let Ok(ndt) = chrono::NaiveDateTime::parse_from_str(
-                                    &datetime_str,
-                                    "%Y-%m-%d %H:%M:%S",
-                                )
-                            {
-                                return Some(Self::DateTime(ndt));
-                            }
+                            return datetime_from_object(obj);
                         }
                         None
                     }
                 }
             }
+            DataType::Resource => {
+                // Thrown here, as a failed conversion of an optional argument is
+                // otherwise taken for a missing one
+                let _ = PhpException::from(SqlxError::InvalidParameter {
+                    name: "resource".to_string(),
+                    expected: "a value rather than a stream: bind stream_get_contents($stream), \
+                               or write through largeObject()->copyFrom()"
+                        .to_string(),
+                })
+                .throw();
+                None
+            }
             DataType::Iterable
             | DataType::Mixed
-            | DataType::Resource
             | DataType::Reference
             | DataType::Callable
             | DataType::ConstantExpression
@@ -217,6 +233,64 @@ impl FromZval<'_> for ParameterValue {
     }
 }
 
+/// Reads a `DateTimeInterface` through its `format()` method, keeping the
+/// microseconds and the UTC offset (e.g. `2024-06-15 14:30:45.123456+02:00`).
+fn datetime_from_object(obj: &ZendObject) -> Option<ParameterValue> {
+    let mut format_arg = Zval::new();
+    format_arg.set_string("Y-m-d H:i:s.uP", false).ok()?;
+    let result = obj.try_call_method("format", vec![&format_arg]).ok()?;
+    chrono::DateTime::parse_from_str(&result.string()?, OFFSET_DATETIME_FORMAT)
+        .ok()
+        .map(ParameterValue::DateTimeTz)
+}
+
+/// Converts the parameter sets passed to `executeMany()`, an array or
+/// `Traversable` of parameter arrays (or `null` for none), in iteration order.
+///
+/// # Errors
+/// Returns `InvalidParameter` if `value` is not iterable or a set is not an
+/// array of supported values.
+pub fn parameter_sets(
+    value: &Zval,
+) -> crate::error::Result<Vec<Option<BTreeMap<String, ParameterValue>>>> {
+    let invalid = |name: String, expected: &str| SqlxError::InvalidParameter {
+        name,
+        expected: expected.to_string(),
+    };
+    let mut iterable = value
+        .iterable()
+        .ok_or_else(|| invalid("paramSets".to_string(), "an array or Traversable"))?;
+    let sets = iterable
+        .iter()
+        .ok_or_else(|| invalid("paramSets".to_string(), "an array or Traversable"))?;
+
+    let mut parameter_sets = Vec::new();
+    for (index, (_, set)) in sets.enumerate() {
+        if set.is_null() {
+            parameter_sets.push(None);
+            continue;
+        }
+        let array = set.array().ok_or_else(|| {
+            invalid(
+                format!("paramSets[{index}]"),
+                "an array of parameters or null",
+            )
+        })?;
+        let parameters = array
+            .iter()
+            .map(|(key, value)| {
+                ParameterValue::from_zval(value)
+                    .map(|value| (key.to_string(), value))
+                    .ok_or_else(|| {
+                        invalid(format!("paramSets[{index}][{key}]"), "a supported value")
+                    })
+            })
+            .try_collect()?;
+        parameter_sets.push(Some(parameters));
+    }
+    Ok(parameter_sets)
+}
+
 impl Serialize for ParameterValue {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -224,6 +298,7 @@ impl Serialize for ParameterValue {
     {
         match self {
             Self::Json(pv) => pv.serialize(serializer),
+            Self::Secret(secret) => secret.value.serialize(serializer),
             Self::Null => serializer.serialize_none(),
             Self::String(s) => serializer.serialize_str(s),
             Self::Int(i) => serializer.serialize_i64(*i),
@@ -232,6 +307,12 @@ impl Serialize for ParameterValue {
             Self::DateTime(dt) => {
                 serializer.serialize_str(&dt.format("%Y-%m-%d %H:%M:%S").to_string())
             }
+            Self::DateTimeTz(dt) => {
+                serializer.serialize_str(&dt.format(OFFSET_DATETIME_FORMAT).to_string())
+            }
+            Self::Binary(bytes) => {
+                serializer.serialize_str(&format!("\\x{}", crate::binary::to_hex(bytes)))
+            }
 
             Self::Array(arr) => {
                 let mut seq = serializer.serialize_seq(Some(arr.len()))?;
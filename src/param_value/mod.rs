@@ -10,7 +10,9 @@
 //! - **Arrays**: Homogeneous lists for IN clauses and batch operations
 //! - **Objects**: Key-value maps for JSON columns
 //! - **JSON**: Explicit JSON wrapper for proper serialization
+//! - **Binary**: Raw bytes, from `Binary` or strings that are not valid UTF-8
 //! - **Clauses**: Pre-rendered SELECT, ORDER BY, and pagination fragments
+//! - **Secrets**: Values redacted from inline SQL
 //!
 //! # PHP to Rust Conversion
 //!
@@ -23,8 +25,11 @@ pub mod quote;
 pub mod utils;
 pub mod write;
 
+pub use conversion::parameter_sets;
+
 use crate::by_clause::ByClauseRendered;
 use crate::paginate_clause::PaginateClauseRendered;
+use crate::secret::Secret;
 use crate::select_clause::SelectClauseRendered;
 use std::collections::BTreeMap;
 
@@ -57,8 +62,14 @@ pub enum ParameterValue {
     Object(BTreeMap<String, ParameterValue>),
     /// Explicit JSON wrapper that forces JSON serialization.
     Json(Box<ParameterValue>),
-    /// `DateTime` value from PHP `DateTimeInterface`, stored as `NaiveDateTime`.
+    /// Date-time without an offset, bound as `TIMESTAMP`/`DATETIME`.
     DateTime(chrono::NaiveDateTime),
+    /// Date-time from PHP `DateTimeInterface`, keeping its UTC offset; bound as
+    /// `timestamptz`/`DATETIMEOFFSET`, or converted to the session time zone on `MySQL`.
+    DateTimeTz(chrono::DateTime<chrono::FixedOffset>),
+    /// Raw bytes from `Binary`, bound as `bytea`/`VARBINARY`/`BLOB` and rendered as a
+    /// hex literal.
+    Binary(Vec<u8>),
     /// Pre-rendered ORDER BY clause from `ByClause`.
     ByClauseRendered(ByClauseRendered),
     /// Pre-rendered SELECT clause from `SelectClause`.
@@ -70,6 +81,9 @@ pub enum ParameterValue {
     /// A validated SQL identifier from `Identifier`, rendered as a quoted
     /// identifier (e.g. `"col"`) rather than a bound value.
     Identifier(String),
+    /// A value from `Secret`, bound like the value it wraps but rendered as
+    /// `'***'` in inline SQL.
+    Secret(Secret),
 }
 
 impl ParameterValue {
@@ -90,11 +104,14 @@ impl ParameterValue {
             | Self::Bool(_)
             | Self::Object(_)
             | Self::DateTime(_)
+            | Self::DateTimeTz(_)
+            | Self::Binary(_)
             | Self::PaginateClauseRendered(_)
             | Self::Identifier(_)
             | Self::Builder(_) => false,
             Self::Null => true,
             Self::Json(inner) => inner.is_empty(),
+            Self::Secret(secret) => secret.value.is_empty(),
         }
     }
 }
@@ -1,6 +1,7 @@
 use crate::ast::{IdentifierQuoteStyle, Settings};
 use crate::error::Error as SqlxError;
 use crate::param_value::ParameterValue;
+use crate::time_zone::session_wall_clock;
 
 /// Quotes an identifier (table name, column name) according to the database's quoting style.
 ///
@@ -70,6 +71,9 @@ impl ParameterValue {
     /// This is used for generating safe inline SQL expressions (e.g. for debugging or logging),
     /// but **should not** be used for query execution — always use placeholders and bind values instead.
     ///
+    /// With `settings.redact_secrets`, secrets — and JSON values containing one — render as
+    /// `'***'`.
+    ///
     /// # Errors
     /// Return Err if the value is a structured clause or an unsupported type.
     pub fn quote(&self, settings: &Settings) -> crate::error::Result<String> {
@@ -98,6 +102,13 @@ impl ParameterValue {
         Ok(match self {
             Self::Null => "NULL".to_string(),
 
+            Self::Secret(_) | Self::Json(_) | Self::Object(_)
+                if settings.redact_secrets && self.contains_secret() =>
+            {
+                crate::secret::REDACTED.to_string()
+            }
+            Self::Secret(secret) => secret.value.quote(settings)?,
+
             Self::Identifier(name) => quote_identifier(name, settings),
 
             Self::Int(i) => i.to_string(),
@@ -118,6 +129,18 @@ impl ParameterValue {
                 escape_sql_string(&dt.format("%Y-%m-%d %H:%M:%S").to_string(), settings)
             }
 
+            Self::DateTimeTz(dt) if settings.datetime_offsets => {
+                escape_sql_string(&dt.to_rfc3339(), settings)
+            }
+            Self::DateTimeTz(dt) => escape_sql_string(
+                &session_wall_clock(dt, settings.time_zone)
+                    .format("%Y-%m-%d %H:%M:%S%.f")
+                    .to_string(),
+                settings,
+            ),
+
+            Self::Binary(bytes) => crate::binary::hex_literal(bytes, settings),
+
             Self::Array(values) => {
                 let elements = values
                     .iter()
@@ -236,6 +259,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_quote_secret() {
+        let secret = ParameterValue::String("hunter2".into()).into_secret();
+        let object =
+            ParameterValue::Object(BTreeMap::from([("token".to_string(), secret.clone())]));
+
+        let settings = Settings::default();
+        assert_eq!(secret.quote(&settings).unwrap(), "'hunter2'");
+        assert_eq!(
+            object.quote(&settings).unwrap(),
+            "'{\"token\":\"hunter2\"}'"
+        );
+
+        let redacted = Settings {
+            redact_secrets: true,
+            ..Settings::default()
+        };
+        assert_eq!(secret.quote(&redacted).unwrap(), "'***'");
+        assert_eq!(object.quote(&redacted).unwrap(), "'***'");
+        assert_eq!(
+            ParameterValue::Array(vec![ParameterValue::Int(1), ParameterValue::Int(2)])
+                .into_secret()
+                .quote(&redacted)
+                .unwrap(),
+            "('***', '***')"
+        );
+        assert_eq!(format!("{secret:?}"), "Secret(***)");
+    }
+
     #[test]
     fn test_quote_string() {
         let settings = Settings {
@@ -257,6 +309,46 @@ mod tests {
         assert_eq!(quoted, "N'line1\\nline2'");
     }
 
+    #[test]
+    fn test_quote_datetime_tz() {
+        use crate::time_zone::TimeZone;
+
+        let value = ParameterValue::DateTimeTz(
+            chrono::DateTime::parse_from_rfc3339("2024-01-01T10:00:00.5+01:00").unwrap(),
+        );
+        let pg = Settings {
+            datetime_offsets: true,
+            ..Settings::default()
+        };
+        assert_eq!(value.quote(&pg).unwrap(), "'2024-01-01T10:00:00.500+01:00'");
+
+        let mysql = Settings::default();
+        assert_eq!(value.quote(&mysql).unwrap(), "'2024-01-01 10:00:00.500'");
+        let mysql = Settings {
+            time_zone: TimeZone::parse("UTC"),
+            ..Settings::default()
+        };
+        assert_eq!(value.quote(&mysql).unwrap(), "'2024-01-01 09:00:00.500'");
+    }
+
+    #[test]
+    fn test_quote_binary() {
+        use crate::ast::BinaryLiteralStyle;
+
+        let value = ParameterValue::Binary(vec![0x00, 0x27, 0xff]);
+        let quoted = |binary_literal_style| {
+            value
+                .quote(&Settings {
+                    binary_literal_style,
+                    ..Settings::default()
+                })
+                .unwrap()
+        };
+        assert_eq!(quoted(BinaryLiteralStyle::Bytea), "'\\x0027ff'::bytea");
+        assert_eq!(quoted(BinaryLiteralStyle::XString), "X'0027ff'");
+        assert_eq!(quoted(BinaryLiteralStyle::HexNumber), "0x0027ff");
+    }
+
     #[test]
     fn test_quote_array() {
         let settings = Settings::default();
@@ -1,6 +1,6 @@
 use crate::error::Error as SqlxError;
 use crate::param_value::ParameterValue;
-use chrono::NaiveDateTime;
+use chrono::{DateTime, NaiveDateTime, Utc};
 use sqlx_oldapi::database::HasArguments;
 use sqlx_oldapi::query::Query;
 use sqlx_oldapi::{Database, Encode, Type};
@@ -13,7 +13,9 @@ use std::option::Option as StdOption;
 ///
 /// # Supported types
 /// - `Str`, `Int`, `Float`, `Bool` — bound directly
+/// - `Binary` — bound as raw bytes (`bytea`, `VARBINARY`, `BLOB`)
 /// - `Array`, `Object` — recursively expanded and flattened into positional bindings
+/// - `Secret` — bound as the value it wraps
 ///
 /// # Unsupported types
 /// - `ByClauseRendered`, `SelectClauseRendered`, `PaginateClauseRendered`, and `Builder` are not bindable and will
@@ -48,6 +50,10 @@ where
     StdOption<String>: Encode<'a, D>,
     NaiveDateTime: Type<D>,
     NaiveDateTime: Encode<'a, D>,
+    DateTime<Utc>: Type<D>,
+    DateTime<Utc>: Encode<'a, D>,
+    Vec<u8>: Type<D>,
+    Vec<u8>: Encode<'a, D>,
 {
     fn walker<'a, D: Database>(
         q: Query<'a, D, <D as HasArguments<'a>>::Arguments>,
@@ -66,6 +72,10 @@ where
         StdOption<String>: Encode<'a, D>,
         NaiveDateTime: Type<D>,
         NaiveDateTime: Encode<'a, D>,
+        DateTime<Utc>: Type<D>,
+        DateTime<Utc>: Encode<'a, D>,
+        Vec<u8>: Type<D>,
+        Vec<u8>: Encode<'a, D>,
     {
         Ok(match value {
             ParameterValue::Json(pv) => q.bind(pv.to_json()?),
@@ -74,7 +84,10 @@ where
             ParameterValue::Bool(s) => q.bind(s),
             ParameterValue::Float(s) => q.bind(s),
             ParameterValue::DateTime(dt) => q.bind(dt),
+            ParameterValue::DateTimeTz(dt) => q.bind(dt.with_timezone(&Utc)),
+            ParameterValue::Binary(bytes) => q.bind(bytes),
             ParameterValue::Array(s) => s.iter().try_fold(q, walker)?,
+            ParameterValue::Secret(secret) => walker(q, &secret.value)?,
             ParameterValue::Object(_) => q.bind(value.to_json()?),
             ParameterValue::Null => q.bind(StdOption::<String>::None),
             ParameterValue::ByClauseRendered(_)
@@ -1,5 +1,6 @@
 use crate::ast::Settings;
 use crate::param_value::ParameterValue;
+use crate::time_zone::session_wall_clock;
 use std::fmt::Write;
 
 pub trait ParamVecWriteSqlTo {
@@ -33,7 +34,12 @@ impl ParameterValue {
         settings: &Settings,
     ) -> crate::error::Result<()> {
         if out_vals.len() < settings.max_placeholders {
-            out_vals.push(self.clone());
+            out_vals.push(match self {
+                Self::DateTimeTz(dt) if !settings.datetime_offsets => {
+                    Self::DateTime(session_wall_clock(dt, settings.time_zone))
+                }
+                _ => self.clone(),
+            });
             if settings.placeholder_dollar_sign {
                 write!(sql, "${}", out_vals.len())?;
             } else if settings.placeholder_at_sign {
@@ -0,0 +1,294 @@
+//! Statements running in the background: `queryAllAsync()`,
+//! `queryMaybeRowAsync()`, `executeAsync()` and `parallel()`.
+//!
+//! Driver calls normally block PHP until their statement is done, so independent
+//! queries pay one round trip after another. These APIs spawn each statement on
+//! the Tokio runtime, on a pool connection of its own, and return at once: up to
+//! `OPT_MAX_CONNECTIONS` statements run concurrently.
+//!
+//! Statements are prepared on the request thread, so the before-query hook, the
+//! query firewall, the cost guard and query tags apply as usual. They are reported
+//! to `onQuery()`, the slow query log and the profiler when collected, with the
+//! time they actually took. Background statements are not retried. Inside a
+//! transaction or `withConnection()` they run at once, one after another, on
+//! that connection.
+//!
+//! # PHP Usage
+//!
+//! ```php
+//! $pending = $driver->queryAllAsync('SELECT * FROM orders WHERE user_id = ?', [42]);
+//! $profile = $driver->queryRow('SELECT * FROM users WHERE id = ?', [42]);
+//! $orders = $pending->wait();
+//! ```
+//!
+//! # Event Loops
+//!
+//! `Pending::completionFd()` returns a file descriptor that becomes readable once
+//! the statement is done, so an event loop can watch it instead of blocking in
+//! `wait()`. The `Sqlx\Async` adapter (`async.php`) uses it to suspend the
+//! current Fiber on Revolt until the result is ready.
+
+use crate::error::{Error as SqlxError, Result};
+use crate::param_value::ParameterValue;
+use crate::query_hook::DetachedQueryTimer;
+use ext_php_rs::prelude::ModuleBuilder;
+use ext_php_rs::types::Zval;
+use ext_php_rs::{php_class, php_impl};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::io::{PipeReader, PipeWriter, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Registers the `Pending` class with the PHP module builder.
+pub fn build(module: ModuleBuilder) -> ModuleBuilder {
+    module.class::<Pending>()
+}
+
+/// A statement running in the background, resolving to a `T`: its rows, or
+/// the number of affected rows.
+pub enum StatementTask<T> {
+    /// Already ran on the request thread (inside a transaction or on a pinned
+    /// connection), or failed before it could be spawned.
+    Ready(Result<T>),
+    /// Running on the runtime.
+    Running {
+        /// Resolves to the result and the time the statement took.
+        handle: JoinHandle<(Result<T>, Duration)>,
+        /// Reports the statement to the query hook, slow query log and profiler.
+        timer: Option<DetachedQueryTimer>,
+        /// Parameters of the statement, for the error context.
+        parameters: Option<BTreeMap<String, ParameterValue>>,
+        /// Signalled by the task once the statement is done.
+        completion: Arc<Completion>,
+    },
+}
+
+impl<T> StatementTask<T> {
+    /// Returns whether the statement is done, without blocking.
+    pub fn is_finished(&self) -> bool {
+        match self {
+            Self::Ready(_) => true,
+            Self::Running { handle, .. } => handle.is_finished(),
+        }
+    }
+
+    /// Returns the completion of the statement.
+    fn completion(&self) -> Arc<Completion> {
+        match self {
+            Self::Ready(_) => Arc::new(Completion::done()),
+            Self::Running { completion, .. } => completion.clone(),
+        }
+    }
+}
+
+/// Completion of a background statement, observable through a pipe.
+///
+/// The task writes a single byte to the pipe once done, so the write never blocks.
+/// The writer is dropped along with its `Pending`, so the task never writes to a
+/// pipe whose reader is gone.
+#[derive(Default)]
+pub struct Completion {
+    state: Mutex<CompletionState>,
+}
+
+#[derive(Default)]
+struct CompletionState {
+    done: bool,
+    writer: Option<PipeWriter>,
+}
+
+impl Completion {
+    fn done() -> Self {
+        Self {
+            state: Mutex::new(CompletionState {
+                done: true,
+                writer: None,
+            }),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, CompletionState> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Marks the statement done and wakes up the reader, if any.
+    pub fn complete(&self) {
+        let mut state = self.state();
+        state.done = true;
+        if let Some(mut writer) = state.writer.take() {
+            let _ = writer.write_all(&[1]);
+        }
+    }
+
+    /// Returns a pipe that becomes readable once the statement is done.
+    fn subscribe(&self) -> std::io::Result<PipeReader> {
+        let (reader, mut writer) = std::io::pipe()?;
+        let mut state = self.state();
+        if state.done {
+            writer.write_all(&[1])?;
+        } else {
+            state.writer = Some(writer);
+        }
+        Ok(reader)
+    }
+
+    fn unsubscribe(&self) {
+        self.state().writer = None;
+    }
+}
+
+#[cfg(unix)]
+#[allow(clippy::unnecessary_wraps)]
+fn raw_fd(reader: &PipeReader) -> Result<i64> {
+    use std::os::fd::AsRawFd;
+    Ok(i64::from(reader.as_raw_fd()))
+}
+
+#[cfg(not(unix))]
+fn raw_fd(_reader: &PipeReader) -> Result<i64> {
+    Err(SqlxError::Other(
+        "Completion file descriptors are only supported on Unix".into(),
+    ))
+}
+
+/// Awaits `fut` for at most `timeout` and the `remaining` request deadline: the
+/// async counterpart of the limits applied to blocking calls.
+///
+/// # Errors
+/// Returns [`SqlxError::Timeout`] or [`SqlxError::DeadlineExceeded`] if the
+/// corresponding limit expires first, or the error of `fut`.
+pub async fn within<T>(
+    fut: impl Future<Output = Result<T>>,
+    operation: &str,
+    timeout: Option<Duration>,
+    remaining: Option<Duration>,
+) -> Result<T> {
+    if let Some(remaining) =
+        remaining.filter(|remaining| timeout.is_none_or(|timeout| *remaining < timeout))
+    {
+        return tokio::time::timeout(remaining, fut).await.map_err(|_| {
+            SqlxError::DeadlineExceeded {
+                operation: operation.to_string(),
+            }
+        })?;
+    }
+    match timeout {
+        Some(timeout) => {
+            tokio::time::timeout(timeout, fut)
+                .await
+                .map_err(|_| SqlxError::Timeout {
+                    operation: operation.to_string(),
+                    timeout_ms: u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX),
+                })?
+        }
+        None => fut.await,
+    }
+}
+
+/// A background statement together with the conversion of its result to PHP.
+trait Resolve {
+    fn is_finished(&self) -> bool;
+    fn resolve(self: Box<Self>) -> Result<Zval>;
+}
+
+struct Deferred<T, F> {
+    task: StatementTask<T>,
+    resolve: F,
+}
+
+impl<T, F: FnOnce(StatementTask<T>) -> Result<Zval>> Resolve for Deferred<T, F> {
+    fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    fn resolve(self: Box<Self>) -> Result<Zval> {
+        (self.resolve)(self.task)
+    }
+}
+
+/// Handle to a statement running in the background, returned by
+/// `queryAllAsync()`, `queryMaybeRowAsync()` and `executeAsync()`.
+#[php_class]
+#[php(name = "Sqlx\\Pending")]
+pub struct Pending {
+    /// The statement, until collected by `wait()`.
+    task: Option<Box<dyn Resolve>>,
+    /// The result, once collected.
+    result: Option<Zval>,
+    /// Completion of the statement.
+    completion: Arc<Completion>,
+    /// Read end of the completion pipe, once requested by `completionFd()`.
+    reader: Option<PipeReader>,
+}
+
+impl Pending {
+    /// Wraps `task`; `resolve` collects it and converts its result on `wait()`.
+    pub fn new<T: 'static>(
+        task: StatementTask<T>,
+        resolve: impl FnOnce(StatementTask<T>) -> Result<Zval> + 'static,
+    ) -> Self {
+        Self {
+            completion: task.completion(),
+            task: Some(Box::new(Deferred { task, resolve })),
+            result: None,
+            reader: None,
+        }
+    }
+}
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        if self.reader.is_some() {
+            self.completion.unsubscribe();
+        }
+    }
+}
+
+#[php_impl]
+impl Pending {
+    /// Returns whether the statement is done, without blocking.
+    pub fn is_ready(&self) -> bool {
+        self.task.as_ref().is_none_or(|task| task.is_finished())
+    }
+
+    /// Returns a file descriptor that becomes readable once the statement is
+    /// done, for event loops. Open it with `fopen("php://fd/$fd", 'r')`; the
+    /// descriptor stays valid as long as this object.
+    ///
+    /// # Exceptions
+    /// Throws if the pipe can't be created, or on platforms other than Unix.
+    pub fn completion_fd(&mut self) -> Result<i64> {
+        if let Some(reader) = &self.reader {
+            return raw_fd(reader);
+        }
+        let reader = self
+            .completion
+            .subscribe()
+            .map_err(|err| SqlxError::Other(format!("Failed to create completion pipe: {err}")))?;
+        raw_fd(self.reader.insert(reader))
+    }
+
+    /// Blocks until the statement is done and returns its result: the rows of
+    /// `queryAllAsync()`, the row or `null` of `queryMaybeRowAsync()`, the
+    /// number of affected rows of `executeAsync()`. Later calls return the same
+    /// result.
+    ///
+    /// # Exceptions
+    /// Throws the error of the statement, if it failed. Since the exception is
+    /// thrown once, later calls then throw a `SqlxException`.
+    pub fn wait(&mut self) -> Result<Zval> {
+        if let Some(task) = self.task.take() {
+            self.result = Some(task.resolve()?);
+        }
+        self.result
+            .as_ref()
+            .map(Zval::shallow_clone)
+            .ok_or_else(|| {
+                SqlxError::Other("The statement failed and its exception was already thrown".into())
+            })
+    }
+}
@@ -8,6 +8,8 @@ macro_rules! php_sqlx_impl_prepared_query {
         use std::sync::Arc;
         use $crate::interfaces::PreparedQueryInterface;
         use $crate::param_value::ParameterValue;
+        use $crate::query_tags::QueryTagsScope;
+        use $crate::statement_cache::{PreparedScope, StatementStats};
         use $crate::utils::types::ColumnArgument;
 
         /// A reusable prepared SQL query with parameter support. Created using `PgDriver::prepare()`, shares context with original driver.
@@ -16,6 +18,8 @@ macro_rules! php_sqlx_impl_prepared_query {
         pub struct $struct {
             pub(crate) query: String,
             pub(crate) driver_inner: Arc<$driver_inner>,
+            pub(crate) query_tags: BTreeMap<String, String>,
+            pub(crate) statements: Arc<StatementStats>,
         }
 
         #[php_impl_interface]
@@ -25,6 +29,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 &self,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<u64> {
+                let _scope = self.scope()?;
                 self.driver_inner.execute(self.query.as_str(), parameters)
             }
 
@@ -33,6 +38,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 &self,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<Zval> {
+                let _scope = self.scope()?;
                 self.driver_inner.query_row(&self.query, parameters, None)
             }
 
@@ -41,6 +47,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 &self,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<Zval> {
+                let _scope = self.scope()?;
                 self.driver_inner
                     .query_row(&self.query, parameters, Some(true))
             }
@@ -50,6 +57,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 &self,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<Zval> {
+                let _scope = self.scope()?;
                 self.driver_inner
                     .query_row(&self.query, parameters, Some(false))
             }
@@ -59,6 +67,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 &self,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<Zval> {
+                let _scope = self.scope()?;
                 self.driver_inner
                     .query_maybe_row(&self.query, parameters, None)
             }
@@ -68,6 +77,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 &self,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<Vec<Zval>> {
+                let _scope = self.scope()?;
                 self.driver_inner.query_all(&self.query, parameters, None)
             }
 
@@ -76,6 +86,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 &self,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<Vec<Zval>> {
+                let _scope = self.scope()?;
                 self.driver_inner
                     .query_all(&self.query, parameters, Some(true))
             }
@@ -85,6 +96,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 &self,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<Vec<Zval>> {
+                let _scope = self.scope()?;
                 self.driver_inner
                     .query_all(&self.query, parameters, Some(false))
             }
@@ -95,6 +107,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
                 column: Option<ColumnArgument>,
             ) -> $crate::error::Result<Zval> {
+                let _scope = self.scope()?;
                 self.driver_inner
                     .query_value(&self.query, parameters, column, None)
             }
@@ -105,6 +118,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
                 column: Option<ColumnArgument>,
             ) -> $crate::error::Result<Vec<Zval>> {
+                let _scope = self.scope()?;
                 self.driver_inner
                     .query_column(&self.query, parameters, column, None)
             }
@@ -115,11 +129,95 @@ macro_rules! php_sqlx_impl_prepared_query {
                 Self {
                     query: query.to_owned(),
                     driver_inner,
+                    query_tags: BTreeMap::new(),
+                    statements: Arc::new(StatementStats::default()),
                 }
             }
+
+            /// Applies this query's tags and routes its statements through the
+            /// driver's statement cache accounting until the guards are dropped.
+            fn scope(&self) -> $crate::error::Result<(QueryTagsScope<'_>, PreparedScope<'_>)> {
+                if self.statements.is_closed() {
+                    return Err($crate::error::Error::Other(
+                        "Prepared query has been closed. Call prepare() again to continue."
+                            .to_string(),
+                    ));
+                }
+                Ok((
+                    self.driver_inner.query_tags.scope(self.query_tags.clone()),
+                    self.driver_inner
+                        .prepared_statements
+                        .scope(self.statements.clone()),
+                ))
+            }
         }
         #[php_impl]
         impl $struct {
+            /// Executes the prepared statement once per parameter set and returns the
+            /// total of affected rows.
+            ///
+            /// The query is parsed once, and every set runs on one connection, in the
+            /// current transaction or a new one committed once all sets have run. The
+            /// statement is prepared on that connection by the first set of each
+            /// rendered shape and reused by the others, with their values bound.
+            ///
+            /// # Arguments
+            /// - `param_sets`: Array or `Traversable` of parameter arrays.
+            ///
+            /// # Example
+            /// ```php
+            /// $insert = $driver->prepare('INSERT INTO logs (level, message) VALUES (?, ?)');
+            /// $insert->executeMany([['info', 'started'], ['warn', 'slow disk']]); // 2
+            /// ```
+            ///
+            /// # Exceptions
+            /// Throws on the first failing set, rolling back a transaction started by
+            /// this call; `QueryException::getIndex()` returns the position of that set.
+            pub fn execute_many(&self, param_sets: &Zval) -> $crate::error::Result<u64> {
+                let _scope = self.scope()?;
+                let parameter_sets = $crate::param_value::parameter_sets(param_sets)?;
+                self.driver_inner.execute_many(&self.query, parameter_sets)
+            }
+
+            /// Returns how often this query's statement was found prepared on the
+            /// connection it ran on (`hits`), how often it had to be prepared
+            /// (`misses`), and how often it ran on a full cache, where the two can't
+            /// be told apart (`unknown`).
+            ///
+            /// Always zero on databases without a statement cache (MSSQL).
+            pub fn statement_cache_stats(&self) -> BTreeMap<String, u64> {
+                self.statements.to_map()
+            }
+
+            /// Closes the prepared query: further executions throw.
+            ///
+            /// Its statements stay in the statement caches of the connections they
+            /// were prepared on, shared with every other query of the driver, until
+            /// they are evicted or the connection is closed.
+            pub fn close(&self) {
+                self.statements.close();
+            }
+
+            /// Returns true once `close()` has been called.
+            pub fn is_closed(&self) -> bool {
+                self.statements.is_closed()
+            }
+
+            /// Sets `sqlcommenter` tags appended to every execution of this prepared query.
+            ///
+            /// Tags are merged over the driver's tags (see `Driver::setQueryTags()`);
+            /// passing an empty array removes them.
+            ///
+            /// # Exceptions
+            /// Throws if a tag value is not a scalar.
+            pub fn set_query_tags(
+                &mut self,
+                tags: BTreeMap<String, ParameterValue>,
+            ) -> $crate::error::Result<()> {
+                self.query_tags = $crate::query_tags::tags_from_params(tags)?;
+                Ok(())
+            }
+
             /// Executes the prepared query and hydrates every row into `target`.
             ///
             /// `target` is either a class name or an `alias => class` map; see
@@ -134,6 +232,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 target: $crate::conversion::HydrationTarget,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<Vec<Zval>> {
+                let _scope = self.scope()?;
                 self.driver_inner
                     .query_all_into(&target, &self.query, parameters, None)
             }
@@ -150,6 +249,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 target: $crate::conversion::HydrationTarget,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<Zval> {
+                let _scope = self.scope()?;
                 self.driver_inner
                     .query_row_into(&target, &self.query, parameters, None)
             }
@@ -167,6 +267,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 target: $crate::conversion::HydrationTarget,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<Zval> {
+                let _scope = self.scope()?;
                 self.driver_inner
                     .query_maybe_row_into(&target, &self.query, parameters, None)
             }
@@ -196,6 +297,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 &self,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<Zval> {
+                let _scope = self.scope()?;
                 self.driver_inner
                     .query_column_dictionary(&self.query, parameters, None)
             }
@@ -217,6 +319,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 &self,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<Zval> {
+                let _scope = self.scope()?;
                 self.driver_inner
                     .query_column_dictionary(&self.query, parameters, Some(true))
             }
@@ -238,6 +341,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 &self,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<Zval> {
+                let _scope = self.scope()?;
                 self.driver_inner
                     .query_column_dictionary(&self.query, parameters, Some(false))
             }
@@ -265,6 +369,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 &self,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<Zval> {
+                let _scope = self.scope()?;
                 self.driver_inner
                     .query_dictionary(&self.query, parameters, None)
             }
@@ -290,6 +395,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 &self,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<Zval> {
+                let _scope = self.scope()?;
                 self.driver_inner
                     .query_dictionary(&self.query, parameters, Some(true))
             }
@@ -315,6 +421,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 &self,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<Zval> {
+                let _scope = self.scope()?;
                 self.driver_inner
                     .query_dictionary(&self.query, parameters, Some(false))
             }
@@ -332,6 +439,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 &self,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<Zval> {
+                let _scope = self.scope()?;
                 self.driver_inner
                     .query_grouped_dictionary(&self.query, parameters, None)
             }
@@ -341,6 +449,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 &self,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<Zval> {
+                let _scope = self.scope()?;
                 self.driver_inner
                     .query_grouped_dictionary(&self.query, parameters, Some(true))
             }
@@ -350,6 +459,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 &self,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<Zval> {
+                let _scope = self.scope()?;
                 self.driver_inner
                     .query_grouped_dictionary(&self.query, parameters, Some(false))
             }
@@ -366,6 +476,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 &self,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<Zval> {
+                let _scope = self.scope()?;
                 self.driver_inner
                     .query_grouped_column_dictionary(&self.query, parameters, None)
             }
@@ -379,6 +490,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 &self,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<Zval> {
+                let _scope = self.scope()?;
                 self.driver_inner.query_grouped_column_dictionary(
                     &self.query,
                     parameters,
@@ -395,6 +507,7 @@ macro_rules! php_sqlx_impl_prepared_query {
                 &self,
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> $crate::error::Result<Zval> {
+                let _scope = self.scope()?;
                 self.driver_inner.query_grouped_column_dictionary(
                     &self.query,
                     parameters,
@@ -0,0 +1,375 @@
+//! Per-request query profiler.
+//!
+//! `$driver->profile()` starts a profile that aggregates every statement the
+//! driver executes by its normalized [`Fingerprint`] — the query template with
+//! placeholders, literals and `IN`-list lengths collapsed — so the 50 lookups
+//! issued by an N+1 loop show up as one line with a count of 50.
+//!
+//! For each fingerprint the profile reports how often it ran, the total time
+//! spent in it and how many executions were exact duplicates (same SQL, same
+//! parameter values) of an earlier one. With a warning threshold, the first
+//! execution that takes a fingerprint past it adds a warning to the `$warnings`
+//! argument of the `onQuery()` hook and to the report.
+//!
+//! # PHP Usage
+//!
+//! ```php
+//! $profile = $driver->profile(warnAfter: 10);
+//! foreach ($orders as $order) {
+//!     $driver->queryRow('SELECT * FROM customers WHERE id = ?', [$order->customer_id]);
+//! }
+//! $report = $profile->stop();
+//! // $report['fingerprints'][0] === [
+//! //     'fingerprint' => '5c1f...', 'sql' => 'select * from customers where id = ?',
+//! //     'count' => 50, 'total_ms' => 12.7, 'duplicates' => 38,
+//! // ]
+//! ```
+
+use crate::ast::Fingerprint;
+use crate::conversion::json_into_zval;
+use crate::param_value::ParameterValue;
+use ext_php_rs::prelude::ModuleBuilder;
+use ext_php_rs::types::Zval;
+use ext_php_rs::{php_class, php_impl};
+use serde_json::{Value, json};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+/// Registers the `Profile` class with the PHP module builder.
+pub fn build(module: ModuleBuilder) -> ModuleBuilder {
+    module.class::<Profile>()
+}
+
+/// Holds the profile currently running on a driver, if any.
+#[derive(Debug, Default)]
+pub struct Profiler {
+    active: RwLock<Option<Arc<ProfileSession>>>,
+}
+
+impl Profiler {
+    /// Starts a new profile, stopping the previous one.
+    pub fn start(&self, warn_after: Option<u64>) -> Arc<ProfileSession> {
+        let session = Arc::new(ProfileSession::new(warn_after));
+        let previous = self
+            .active
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .replace(session.clone());
+        if let Some(previous) = previous {
+            previous.stop();
+        }
+        session
+    }
+
+    /// Stops the running profile, if any.
+    pub fn stop(&self) {
+        let previous = self
+            .active
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        if let Some(previous) = previous {
+            previous.stop();
+        }
+    }
+
+    /// Returns the running profile, if any.
+    #[must_use]
+    pub fn active(&self) -> Option<Arc<ProfileSession>> {
+        self.active
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+            .filter(|session| session.is_active())
+            .cloned()
+    }
+}
+
+/// Aggregated statistics for one fingerprint.
+#[derive(Debug)]
+struct FingerprintStats {
+    normalized: String,
+    count: u64,
+    total: Duration,
+    duplicates: u64,
+    /// Hashes of the distinct (SQL, parameters) pairs seen so far.
+    statements: HashSet<u64>,
+}
+
+/// Mutable state of a profile.
+#[derive(Debug, Default)]
+struct ProfileState {
+    fingerprints: HashMap<String, FingerprintStats>,
+    warnings: Vec<String>,
+}
+
+/// A running (or stopped) profile.
+#[derive(Debug)]
+pub struct ProfileSession {
+    started: Instant,
+    /// Warn once a fingerprint runs more than this many times.
+    warn_after: Option<u64>,
+    stopped: AtomicBool,
+    state: Mutex<ProfileState>,
+}
+
+impl ProfileSession {
+    /// Creates an empty profile.
+    #[must_use]
+    pub fn new(warn_after: Option<u64>) -> Self {
+        Self {
+            started: Instant::now(),
+            warn_after,
+            stopped: AtomicBool::new(false),
+            state: Mutex::new(ProfileState::default()),
+        }
+    }
+
+    /// Returns false once the profile has been stopped.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        !self.stopped.load(Ordering::Relaxed)
+    }
+
+    /// Stops collecting; statements already in flight are still recorded.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+
+    /// Prepares a sample for a statement about to run.
+    ///
+    /// `sql` and `values` are the rendered statement and its bound values; they
+    /// identify exact duplicates within the fingerprint.
+    #[must_use]
+    pub fn sample(
+        self: &Arc<Self>,
+        fingerprint: Fingerprint,
+        sql: &str,
+        values: &[ParameterValue],
+    ) -> ProfileSample {
+        let mut hasher = DefaultHasher::new();
+        sql.hash(&mut hasher);
+        // Serialized rather than `Debug`-formatted: secrets debug-print as `***`.
+        serde_json::to_string(values)
+            .unwrap_or_default()
+            .hash(&mut hasher);
+        ProfileSample {
+            session: self.clone(),
+            fingerprint,
+            statement: hasher.finish(),
+        }
+    }
+
+    /// Builds the report returned to PHP.
+    #[must_use]
+    pub fn report(&self) -> Value {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut entries: Vec<(&String, &FingerprintStats)> = state.fingerprints.iter().collect();
+        entries.sort_by(|(a_hash, a), (b_hash, b)| {
+            b.count
+                .cmp(&a.count)
+                .then(b.total.cmp(&a.total))
+                .then(a_hash.cmp(b_hash))
+        });
+
+        let queries: u64 = entries.iter().map(|(_, stats)| stats.count).sum();
+        let duplicates: u64 = entries.iter().map(|(_, stats)| stats.duplicates).sum();
+        let total: Duration = entries.iter().map(|(_, stats)| stats.total).sum();
+        json!({
+            "queries": queries,
+            "distinct": entries.len(),
+            "duplicates": duplicates,
+            "total_ms": millis(total),
+            "elapsed_ms": millis(self.started.elapsed()),
+            "fingerprints": entries
+                .iter()
+                .map(|(hash, stats)| json!({
+                    "fingerprint": hash,
+                    "sql": stats.normalized,
+                    "count": stats.count,
+                    "total_ms": millis(stats.total),
+                    "duplicates": stats.duplicates,
+                }))
+                .collect::<Vec<_>>(),
+            "warnings": state.warnings,
+        })
+    }
+}
+
+/// One statement's contribution to a profile, recorded once it finishes.
+#[derive(Debug)]
+pub struct ProfileSample {
+    session: Arc<ProfileSession>,
+    fingerprint: Fingerprint,
+    /// Hash of the rendered SQL and its values.
+    statement: u64,
+}
+
+impl ProfileSample {
+    /// Adds the statement to its profile.
+    ///
+    /// Returns a warning the first time its fingerprint runs more than the
+    /// profile's `warn_after` times.
+    pub fn record(self, duration: Duration) -> Option<String> {
+        let mut state = self
+            .session
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let Fingerprint {
+            hash, normalized, ..
+        } = self.fingerprint;
+        let entry = state
+            .fingerprints
+            .entry(hash)
+            .or_insert_with(|| FingerprintStats {
+                normalized,
+                count: 0,
+                total: Duration::ZERO,
+                duplicates: 0,
+                statements: HashSet::new(),
+            });
+        entry.count += 1;
+        entry.total += duration;
+        if !entry.statements.insert(self.statement) {
+            entry.duplicates += 1;
+        }
+
+        let limit = self.session.warn_after?;
+        if entry.count != limit + 1 {
+            return None;
+        }
+        let warning = format!(
+            "Statement executed more than {limit} times in this profile (possible N+1): {}",
+            entry.normalized
+        );
+        state.warnings.push(warning.clone());
+        Some(warning)
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// A query profile started with `$driver->profile()`.
+#[php_class]
+#[php(name = "Sqlx\\Profile")]
+pub struct Profile {
+    session: Arc<ProfileSession>,
+}
+
+impl Profile {
+    /// Wraps a running session.
+    #[must_use]
+    pub fn new(session: Arc<ProfileSession>) -> Self {
+        Self { session }
+    }
+}
+
+#[php_impl]
+impl Profile {
+    /// Stops the profile and returns its report.
+    ///
+    /// # Returns
+    /// `['queries' => int, 'distinct' => int, 'duplicates' => int, 'total_ms' => float,
+    /// 'elapsed_ms' => float, 'fingerprints' => list<array>, 'warnings' => string[]]`.
+    /// Each fingerprint entry has the keys `fingerprint`, `sql`, `count`, `total_ms`
+    /// and `duplicates`; entries are sorted by `count`, highest first.
+    pub fn stop(&self) -> crate::error::Result<Zval> {
+        self.session.stop();
+        self.report()
+    }
+
+    /// Returns the report collected so far without stopping the profile.
+    pub fn report(&self) -> crate::error::Result<Zval> {
+        json_into_zval(self.session.report(), true)
+    }
+
+    /// Returns true until the profile is stopped or replaced by a new one.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.session.is_active()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Literals;
+
+    fn fingerprint(hash: &str, normalized: &str) -> Fingerprint {
+        Fingerprint {
+            hash: hash.to_string(),
+            normalized: normalized.to_string(),
+            literals: Literals::default(),
+        }
+    }
+
+    #[test]
+    fn aggregates_by_fingerprint_and_counts_duplicates() {
+        let session = Arc::new(ProfileSession::new(None));
+        let by_id = fingerprint("a", "select * from users where id = ?");
+        for id in [1, 2, 1, 1] {
+            let sample = session.sample(
+                by_id.clone(),
+                "SELECT * FROM users WHERE id = $1",
+                &[ParameterValue::Int(id)],
+            );
+            assert_eq!(sample.record(Duration::from_millis(2)), None);
+        }
+        session
+            .sample(fingerprint("b", "select 1"), "SELECT 1", &[])
+            .record(Duration::from_millis(1));
+
+        let report = session.report();
+        assert_eq!(report["queries"], 5);
+        assert_eq!(report["distinct"], 2);
+        assert_eq!(report["duplicates"], 2);
+        assert_eq!(report["fingerprints"][0]["fingerprint"], "a");
+        assert_eq!(report["fingerprints"][0]["count"], 4);
+        assert_eq!(report["fingerprints"][0]["duplicates"], 2);
+        assert_eq!(report["fingerprints"][1]["sql"], "select 1");
+    }
+
+    #[test]
+    fn warns_once_past_the_threshold() {
+        let session = Arc::new(ProfileSession::new(Some(2)));
+        let warnings: Vec<Option<String>> = (0..5)
+            .map(|id| {
+                session
+                    .sample(
+                        fingerprint("a", "select ?"),
+                        "SELECT $1",
+                        &[ParameterValue::Int(id)],
+                    )
+                    .record(Duration::ZERO)
+            })
+            .collect();
+        assert!(warnings[..2].iter().all(Option::is_none));
+        assert!(
+            warnings[2]
+                .as_deref()
+                .unwrap()
+                .contains("more than 2 times")
+        );
+        assert!(warnings[3..].iter().all(Option::is_none));
+        assert_eq!(session.report()["warnings"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn starting_a_profile_stops_the_previous_one() {
+        let profiler = Profiler::default();
+        assert!(profiler.active().is_none());
+        let first = profiler.start(None);
+        let second = profiler.start(None);
+        assert!(!first.is_active());
+        assert!(Arc::ptr_eq(&profiler.active().unwrap(), &second));
+        second.stop();
+        assert!(profiler.active().is_none());
+    }
+}
@@ -0,0 +1,383 @@
+//! Query firewall (`OPT_QUERY_ALLOWLIST`).
+//!
+//! When enabled, the driver only runs statements whose [`Fingerprint`] appears
+//! in an allowlist file. The fingerprint is computed from the parsed query
+//! template, so placeholder values, inline literals and `IN`-list lengths don't
+//! matter; a new *shape* of query does.
+//!
+//! The file holds one approved statement per line: the fingerprint hash,
+//! optionally followed by the normalized SQL for reviewers. Blank lines and lines
+//! starting with `#` are ignored.
+//!
+//! ```text
+//! # checkout service
+//! 5f0c3a1e9b27d44c select * from orders where id = ?
+//! a91e07d2c35b6f18 update orders set status = ? where id = ?
+//! ```
+//!
+//! Modes (`OPT_QUERY_ALLOWLIST_MODE`):
+//!
+//! - `enforce` (default): an unlisted statement is refused with a
+//!   `NotPermittedException` before it reaches the database.
+//! - `learn`: unlisted statements run, and their fingerprints are appended to the
+//!   file so it can be reviewed and committed.
+//!
+//! Statements with inline string literals are violations in both modes: a
+//! string in the template usually means a value was concatenated into the SQL
+//! instead of bound. Enforce mode refuses them; learn mode runs them, reports a
+//! warning through the `onQuery` hook's `$warnings` and does not learn them.
+//! Numbers are written inline routinely (`LIMIT 10`, `COALESCE(x, 0)`,
+//! `count(1)`), so they only count with `OPT_QUERY_ALLOWLIST_LITERALS` set to
+//! `"strict"`; `true` accepts every literal.
+//!
+//! The file is read once, when the driver is created.
+
+use crate::ast::{Fingerprint, Literals};
+use crate::error::{Error as SqlxError, Result};
+use crate::param_value::ParameterValue;
+use std::collections::HashSet;
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::sync::{PoisonError, RwLock};
+
+/// How the allowlist treats statements it doesn't know.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AllowlistMode {
+    /// Refuse unlisted statements.
+    #[default]
+    Enforce,
+    /// Run unlisted statements and append their fingerprints to the file.
+    Learn,
+}
+
+impl AllowlistMode {
+    /// Parses an `OPT_QUERY_ALLOWLIST_MODE` value: `"enforce"` or `"learn"`.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        if value.eq_ignore_ascii_case("enforce") {
+            Some(Self::Enforce)
+        } else if value.eq_ignore_ascii_case("learn") {
+            Some(Self::Learn)
+        } else {
+            None
+        }
+    }
+}
+
+/// Which inline literals make a statement a violation
+/// (`OPT_QUERY_ALLOWLIST_LITERALS`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RefusedLiterals {
+    /// None: every literal is accepted (`true`).
+    None,
+    /// String literals (`false`, the default).
+    #[default]
+    Strings,
+    /// String and numeric literals (`"strict"`).
+    All,
+}
+
+impl RefusedLiterals {
+    /// Parses an `OPT_QUERY_ALLOWLIST_LITERALS` value: a boolean or `"strict"`.
+    #[must_use]
+    pub fn parse(value: &ParameterValue) -> Option<Self> {
+        match value {
+            ParameterValue::Bool(true) => Some(Self::None),
+            ParameterValue::Bool(false) => Some(Self::Strings),
+            ParameterValue::String(value) if value.eq_ignore_ascii_case("strict") => {
+                Some(Self::All)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns how many of `literals` are refused.
+    #[must_use]
+    pub fn count(self, literals: Literals) -> usize {
+        match self {
+            Self::None => 0,
+            Self::Strings => literals.strings,
+            Self::All => literals.strings + literals.numbers,
+        }
+    }
+}
+
+/// An allowlist of approved statement fingerprints, loaded from a file.
+#[derive(Debug)]
+pub struct QueryAllowlist {
+    path: String,
+    mode: AllowlistMode,
+    /// Inline literals that make a statement a violation.
+    refused_literals: RefusedLiterals,
+    /// Approved fingerprint hashes.
+    approved: RwLock<HashSet<String>>,
+}
+
+impl QueryAllowlist {
+    /// Loads the allowlist at `path`.
+    ///
+    /// In learn mode a missing file is an empty allowlist; it is created on the
+    /// first append.
+    ///
+    /// # Errors
+    /// Returns a configuration error if the file can't be read (or, in enforce
+    /// mode, doesn't exist).
+    pub fn load(
+        path: &str,
+        mode: AllowlistMode,
+        refused_literals: RefusedLiterals,
+    ) -> Result<Self> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound && mode == AllowlistMode::Learn => {
+                String::new()
+            }
+            Err(err) => {
+                return Err(SqlxError::config(
+                    "query_allowlist",
+                    format!("cannot read `{path}`: {err}"),
+                ));
+            }
+        };
+        Ok(Self::from_contents(
+            path.to_string(),
+            mode,
+            refused_literals,
+            &contents,
+        ))
+    }
+
+    /// Builds an allowlist from file contents.
+    fn from_contents(
+        path: String,
+        mode: AllowlistMode,
+        refused_literals: RefusedLiterals,
+        contents: &str,
+    ) -> Self {
+        let approved = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_whitespace().next())
+            .map(str::to_string)
+            .collect();
+        Self {
+            path,
+            mode,
+            refused_literals,
+            approved: RwLock::new(approved),
+        }
+    }
+
+    /// Returns the number of approved fingerprints.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.approved
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Returns true if no fingerprint is approved.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Checks a statement against the allowlist.
+    ///
+    /// Returns the warnings to report to the query hook (learn mode only).
+    ///
+    /// # Errors
+    /// In enforce mode, returns [`SqlxError::InlineLiterals`] if the statement
+    /// contains inline literals that are refused, or
+    /// [`SqlxError::NotAllowlisted`] if its fingerprint isn't approved.
+    pub fn check(&self, fingerprint: &Fingerprint) -> Result<Vec<String>> {
+        let refused = self.refused_literals.count(fingerprint.literals);
+        if refused > 0 {
+            let err = SqlxError::InlineLiterals {
+                count: refused,
+                sql: fingerprint.normalized.clone(),
+            };
+            return match self.mode {
+                AllowlistMode::Enforce => Err(err),
+                AllowlistMode::Learn => Ok(vec![err.to_string()]),
+            };
+        }
+
+        if self
+            .approved
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains(&fingerprint.hash)
+        {
+            return Ok(Vec::new());
+        }
+
+        match self.mode {
+            AllowlistMode::Enforce => Err(SqlxError::NotAllowlisted {
+                fingerprint: fingerprint.hash.clone(),
+                sql: fingerprint.normalized.clone(),
+            }),
+            AllowlistMode::Learn => Ok(self.learn(fingerprint).into_iter().collect()),
+        }
+    }
+
+    /// Approves `fingerprint` and appends it to the file.
+    ///
+    /// Returns a warning if the file can't be written; the fingerprint stays
+    /// approved for the lifetime of the driver either way.
+    fn learn(&self, fingerprint: &Fingerprint) -> Option<String> {
+        let mut approved = self
+            .approved
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        if !approved.insert(fingerprint.hash.clone()) {
+            return None;
+        }
+        let line = format!(
+            "{} {}\n",
+            fingerprint.hash,
+            fingerprint.normalized.replace(['\r', '\n'], " ")
+        );
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .err()
+            .map(|err| format!("Cannot append to query allowlist `{}`: {err}", self.path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprint(hash: &str, strings: usize) -> Fingerprint {
+        Fingerprint {
+            hash: hash.to_string(),
+            normalized: "select * from t where id = ?".to_string(),
+            literals: Literals {
+                strings,
+                numbers: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn parses_modes() {
+        assert_eq!(
+            AllowlistMode::parse("Enforce"),
+            Some(AllowlistMode::Enforce)
+        );
+        assert_eq!(AllowlistMode::parse("learn"), Some(AllowlistMode::Learn));
+        assert_eq!(AllowlistMode::parse("audit"), None);
+    }
+
+    #[test]
+    fn reads_hashes_and_skips_comments() {
+        let allowlist = QueryAllowlist::from_contents(
+            String::new(),
+            AllowlistMode::Enforce,
+            RefusedLiterals::Strings,
+            "# reviewed\n\n0123456789abcdef select 1\n  fedcba9876543210\n",
+        );
+        assert_eq!(allowlist.len(), 2);
+        assert!(allowlist.check(&fingerprint("0123456789abcdef", 0)).is_ok());
+        assert!(allowlist.check(&fingerprint("fedcba9876543210", 0)).is_ok());
+    }
+
+    #[test]
+    fn enforce_refuses_unlisted_statements_and_literals() {
+        let allowlist = QueryAllowlist::from_contents(
+            String::new(),
+            AllowlistMode::Enforce,
+            RefusedLiterals::Strings,
+            "0123456789abcdef\n",
+        );
+        assert!(matches!(
+            allowlist.check(&fingerprint("1111111111111111", 0)),
+            Err(SqlxError::NotAllowlisted { .. })
+        ));
+        assert!(matches!(
+            allowlist.check(&fingerprint("0123456789abcdef", 2)),
+            Err(SqlxError::InlineLiterals { count: 2, .. })
+        ));
+
+        let lenient = QueryAllowlist::from_contents(
+            String::new(),
+            AllowlistMode::Enforce,
+            RefusedLiterals::None,
+            "0123456789abcdef\n",
+        );
+        assert!(lenient.check(&fingerprint("0123456789abcdef", 2)).is_ok());
+    }
+
+    #[test]
+    fn numeric_literals_only_count_when_strict() {
+        let numeric = Fingerprint {
+            literals: Literals {
+                strings: 0,
+                numbers: 2,
+            },
+            ..fingerprint("0123456789abcdef", 0)
+        };
+        let allowlist = |refused_literals| {
+            QueryAllowlist::from_contents(
+                String::new(),
+                AllowlistMode::Enforce,
+                refused_literals,
+                "0123456789abcdef\n",
+            )
+        };
+        assert!(allowlist(RefusedLiterals::Strings).check(&numeric).is_ok());
+        assert!(matches!(
+            allowlist(RefusedLiterals::All).check(&numeric),
+            Err(SqlxError::InlineLiterals { count: 2, .. })
+        ));
+
+        assert_eq!(
+            RefusedLiterals::parse(&ParameterValue::Bool(true)),
+            Some(RefusedLiterals::None)
+        );
+        assert_eq!(
+            RefusedLiterals::parse(&"Strict".into()),
+            Some(RefusedLiterals::All)
+        );
+        assert_eq!(RefusedLiterals::parse(&"loose".into()), None);
+    }
+
+    #[test]
+    fn learn_appends_new_fingerprints_once() {
+        let path = std::env::temp_dir().join(format!("sqlx-allowlist-{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+
+        let allowlist =
+            QueryAllowlist::load(path, AllowlistMode::Learn, RefusedLiterals::Strings).unwrap();
+        assert!(allowlist.is_empty());
+        for _ in 0..2 {
+            let warnings = allowlist
+                .check(&fingerprint("0123456789abcdef", 0))
+                .unwrap();
+            assert!(warnings.is_empty());
+        }
+        let warnings = allowlist
+            .check(&fingerprint("1111111111111111", 1))
+            .unwrap();
+        assert!(warnings[0].contains("inline literal"));
+
+        let contents = std::fs::read_to_string(path).unwrap();
+        assert_eq!(contents, "0123456789abcdef select * from t where id = ?\n");
+
+        let reloaded =
+            QueryAllowlist::load(path, AllowlistMode::Enforce, RefusedLiterals::Strings).unwrap();
+        assert!(reloaded.check(&fingerprint("0123456789abcdef", 0)).is_ok());
+        std::fs::remove_file(path).unwrap();
+
+        assert!(
+            QueryAllowlist::load(path, AllowlistMode::Enforce, RefusedLiterals::Strings).is_err()
+        );
+    }
+}
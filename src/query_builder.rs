@@ -249,6 +249,7 @@ macro_rules! php_sqlx_impl_query_builder {
             pub(crate) driver_inner: Arc<$driver_inner>,
             pub(crate) placeholders: BTreeSet<String>,
             pub(crate) parameters: BTreeMap<String, ParameterValue>,
+            pub(crate) query_tags: BTreeMap<String, String>,
         }
 
         #[php_impl_interface]
@@ -296,6 +297,7 @@ macro_rules! php_sqlx_impl_query_builder {
                     placeholders: Default::default(),
                     parameters: Default::default(),
                     query: Default::default(),
+                    query_tags: Default::default(),
                 }
             }
 
@@ -1601,6 +1603,27 @@ macro_rules! php_sqlx_impl_query_builder {
                 self.parameters.clone()
             }
 
+            /// Sets `sqlcommenter` tags appended to every statement executed by this builder.
+            ///
+            /// Builder tags are merged over the driver's tags (see `Driver::setQueryTags()`);
+            /// passing an empty array removes them.
+            ///
+            /// # Example
+            /// ```php
+            /// $driver->builder()
+            ///     ->setQueryTags(['controller' => 'users'])
+            ///     ->select('*')
+            ///     ->from('users')
+            ///     ->queryAll();
+            /// ```
+            fn set_query_tags(
+                self_: &mut ZendClassObject<$struct>,
+                tags: BTreeMap<String, ParameterValue>,
+            ) -> crate::error::Result<&mut ZendClassObject<$struct>> {
+                self_.query_tags = $crate::query_tags::tags_from_params(tags)?;
+                Ok(self_)
+            }
+
             /// Appends a raw SQL fragment to the query without structural validation.
             ///
             /// This method allows injecting raw SQL clauses directly into the query. It's intended
@@ -2265,6 +2288,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Zval> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_column_dictionary(&self.query, merged_params, None)
             }
@@ -2287,6 +2311,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Zval> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_column_dictionary(&self.query, merged_params, Some(true))
             }
@@ -2309,6 +2334,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Zval> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_column_dictionary(&self.query, merged_params, Some(false))
             }
@@ -2337,6 +2363,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Zval> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_dictionary(&self.query, merged_params, None)
             }
@@ -2363,6 +2390,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Zval> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_dictionary(&self.query, merged_params, Some(true))
             }
@@ -2389,6 +2417,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Zval> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_dictionary(&self.query, merged_params, Some(false))
             }
@@ -2407,6 +2436,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Zval> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_grouped_dictionary(&self.query, merged_params, None)
             }
@@ -2417,6 +2447,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Zval> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_grouped_dictionary(&self.query, merged_params, Some(true))
             }
@@ -2427,6 +2458,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Zval> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_grouped_dictionary(&self.query, merged_params, Some(false))
             }
@@ -2444,6 +2476,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Zval> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_grouped_column_dictionary(&self.query, merged_params, None)
             }
@@ -2458,6 +2491,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Zval> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner.query_grouped_column_dictionary(
                     &self.query,
                     merged_params,
@@ -2475,6 +2509,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Zval> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner.query_grouped_column_dictionary(
                     &self.query,
                     merged_params,
@@ -2500,9 +2535,35 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<u64> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner.execute(self.query.as_str(), merged_params)
             }
 
+            /// Returns the execution plan of the built query.
+            ///
+            /// # Arguments
+            /// - `parameters`: Optional array of indexed/named parameters to bind.
+            /// - `options`: `['analyze' => bool, 'buffers' => bool]`, as for `Driver::explain()`.
+            ///
+            /// # Returns
+            /// `['plan' => ..., 'summary' => [...]]`, as for `Driver::explain()`.
+            ///
+            /// # Exceptions
+            /// Throws an exception if the query can't be rendered, an option is invalid,
+            /// or the database rejects the `EXPLAIN`.
+            pub fn explain(
+                &self,
+                parameters: Option<BTreeMap<String, ParameterValue>>,
+                options: Option<BTreeMap<String, ParameterValue>>,
+            ) -> crate::error::Result<Zval> {
+                let options = $crate::explain::ExplainOptions::from_params(options)?;
+                let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
+                self.driver_inner
+                    .explain(&self.query, merged_params, options)?
+                    .into_zval()
+            }
+
             /// Executes the prepared query and returns a single result.
             ///
             /// # Arguments
@@ -2522,6 +2583,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Zval> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner.query_row(&self.query, merged_params, None)
             }
 
@@ -2534,6 +2596,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Zval> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_row(&self.query, merged_params, Some(true))
             }
@@ -2547,6 +2610,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Zval> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_row(&self.query, merged_params, Some(false))
             }
@@ -2567,6 +2631,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Zval> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_maybe_row(&self.query, merged_params, None)
             }
@@ -2590,6 +2655,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Zval> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_maybe_row(&self.query, merged_params, Some(true))
             }
@@ -2612,6 +2678,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Zval> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_maybe_row(&self.query, merged_params, Some(false))
             }
@@ -2637,6 +2704,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 column: Option<ColumnArgument>,
             ) -> crate::error::Result<Vec<Zval>> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_column(&self.query, merged_params, column, None)
             }
@@ -2659,6 +2727,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 column: Option<ColumnArgument>,
             ) -> crate::error::Result<Vec<Zval>> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_column(&self.query, merged_params, column, Some(true))
             }
@@ -2680,6 +2749,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 column: Option<ColumnArgument>,
             ) -> crate::error::Result<Vec<Zval>> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_column(&self.query, merged_params, column, Some(false))
             }
@@ -2703,6 +2773,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Vec<Zval>> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner.query_all(&self.query, merged_params, None)
             }
 
@@ -2722,6 +2793,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Vec<Zval>> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_all(&self.query, merged_params, Some(true))
             }
@@ -2742,6 +2814,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Vec<Zval>> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_all(&self.query, merged_params, Some(false))
             }
@@ -2761,6 +2834,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Vec<Zval>> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_all_into(&target, &self.query, merged_params, None)
             }
@@ -2778,6 +2852,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Zval> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_row_into(&target, &self.query, merged_params, None)
             }
@@ -2796,6 +2871,7 @@ macro_rules! php_sqlx_impl_query_builder {
                 parameters: Option<BTreeMap<String, ParameterValue>>,
             ) -> crate::error::Result<Zval> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 self.driver_inner
                     .query_maybe_row_into(&target, &self.query, merged_params, None)
             }
@@ -2817,10 +2893,11 @@ macro_rules! php_sqlx_impl_query_builder {
                 batch_size: Option<usize>,
             ) -> crate::error::Result<super::query_result::$query_result> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 let batch_size = batch_size.unwrap_or($crate::query_result::DEFAULT_BATCH_SIZE);
                 let assoc = self.driver_inner.options.associative_arrays;
-                let (receiver, cancel_token) = self.driver_inner.query_stream(&self.query, merged_params, batch_size)?;
-                Ok(super::query_result::$query_result::new(receiver, cancel_token, assoc, batch_size))
+                let (receiver, cancel_token, timer) = self.driver_inner.query_stream(&self.query, merged_params, batch_size)?;
+                Ok(super::query_result::$query_result::new(self.driver_inner.clone(), receiver, cancel_token, timer, assoc, batch_size))
             }
 
             /// Executes the query and returns a lazy `QueryResult` iterator with rows as associative arrays.
@@ -2837,9 +2914,10 @@ macro_rules! php_sqlx_impl_query_builder {
                 batch_size: Option<usize>,
             ) -> crate::error::Result<super::query_result::$query_result> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 let batch_size = batch_size.unwrap_or($crate::query_result::DEFAULT_BATCH_SIZE);
-                let (receiver, cancel_token) = self.driver_inner.query_stream(&self.query, merged_params, batch_size)?;
-                Ok(super::query_result::$query_result::new(receiver, cancel_token, true, batch_size))
+                let (receiver, cancel_token, timer) = self.driver_inner.query_stream(&self.query, merged_params, batch_size)?;
+                Ok(super::query_result::$query_result::new(self.driver_inner.clone(), receiver, cancel_token, timer, true, batch_size))
             }
 
             /// Executes the query and returns a lazy `QueryResult` iterator with rows as objects.
@@ -2856,9 +2934,10 @@ macro_rules! php_sqlx_impl_query_builder {
                 batch_size: Option<usize>,
             ) -> crate::error::Result<super::query_result::$query_result> {
                 let merged_params = self.merge_parameters(parameters);
+                let _tags = self.driver_inner.query_tags.scope(self.query_tags.clone());
                 let batch_size = batch_size.unwrap_or($crate::query_result::DEFAULT_BATCH_SIZE);
-                let (receiver, cancel_token) = self.driver_inner.query_stream(&self.query, merged_params, batch_size)?;
-                Ok(super::query_result::$query_result::new(receiver, cancel_token, false, batch_size))
+                let (receiver, cancel_token, timer) = self.driver_inner.query_stream(&self.query, merged_params, batch_size)?;
+                Ok(super::query_result::$query_result::new(self.driver_inner.clone(), receiver, cancel_token, timer, false, batch_size))
             }
         }
     };
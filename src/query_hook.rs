@@ -7,6 +7,15 @@
 //! - The execution duration in milliseconds
 //! - The number of rows affected (writes) or returned (reads), if known
 //! - The error message, if the query failed (`null` on success)
+//! - The metadata attached by the before-query hook (`null` if none)
+//! - Warnings about the statement, e.g. from the cost guard (empty array if none)
+//!
+//! A separate before-query hook runs *before* execution and can rewrite the
+//! SQL/parameters, veto the query by throwing, or attach metadata that is
+//! passed on to the after-query hook.
+//!
+//! A retry hook is called before the driver retries a failed statement under
+//! its retry policy, with the retry number, the delay and the error.
 //!
 //! # Example
 //!
@@ -24,12 +33,25 @@
 //!         Logger::debug("Query returned {$rows} row(s) in {$durationMs}ms: $sql");
 //!     }
 //! });
+//!
+//! $driver->onBeforeQuery(function (string $sql, array $params, string $kind) use ($tenantId) {
+//!     if ($kind === 'execute' && !str_contains($sql, 'tenant_id')) {
+//!         throw new \RuntimeException('Writes must be scoped to a tenant');
+//!     }
+//!     return ['meta' => ['tenant' => $tenantId]];
+//! });
 //! ```
 
+use crate::error::{Error as SqlxError, PhpThrowable};
+use crate::param_value::ParameterValue;
+use crate::profiler::ProfileSample;
+use crate::slow_query_log::SlowQueryLog;
+use ext_php_rs::convert::{FromZval, IntoZval};
 use ext_php_rs::types::{ZendCallable, Zval};
 use saturating_cast::SaturatingCast;
-use std::cell::RefCell;
-use std::time::Instant;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
 
 /// Information about an executed query, passed to the hook callback.
 #[derive(Debug)]
@@ -46,29 +68,97 @@ pub struct QueryInfo {
     pub rows: Option<u64>,
     /// Error message if the query failed; `None` on success.
     pub error: Option<String>,
+    /// Metadata attached by the before-query hook, if any.
+    pub meta: Option<Zval>,
+    /// Warnings about the statement (e.g. cost guard violations).
+    pub warnings: Vec<String>,
 }
 
-/// Storage for the query hook callback.
+/// Extras collected while rendering a statement and reported once it finishes.
+#[derive(Debug, Default)]
+pub struct Instrumentation {
+    /// Warnings about the statement (e.g. cost guard violations).
+    pub warnings: Vec<String>,
+    /// Sample to add to the running profile, if one was started with `profile()`.
+    pub profile: Option<ProfileSample>,
+}
+
+/// Storage for a hook's PHP callable (closure, function name, or [object, method]).
 ///
 /// Uses `RefCell` because PHP is single-threaded and we need interior mutability.
-/// The `Zval` stores the PHP callable (closure, function name, or [object, method]).
+/// The callable is cloned out of the cell before it runs, so the callback may
+/// replace or clear its own hook. While it runs, further calls are skipped: a
+/// hook that runs a query on the same driver doesn't call itself again.
 #[derive(Default)]
-pub struct QueryHook {
+pub struct PhpCallbackSlot {
     callback: RefCell<Option<Zval>>,
+    running: Cell<bool>,
 }
 
-// SAFETY: PHP is single-threaded per request. The QueryHook is only accessed
+// SAFETY: PHP is single-threaded per request. A PhpCallbackSlot is only accessed
 // from the same thread that created it.
-unsafe impl Send for QueryHook {}
-unsafe impl Sync for QueryHook {}
+unsafe impl Send for PhpCallbackSlot {}
+unsafe impl Sync for PhpCallbackSlot {}
+
+impl PhpCallbackSlot {
+    /// Creates an empty slot.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores the callback, replacing any previous one.
+    pub fn set(&self, callback: Zval) {
+        *self.callback.borrow_mut() = Some(callback);
+    }
+
+    /// Removes the callback.
+    pub fn clear(&self) {
+        *self.callback.borrow_mut() = None;
+    }
+
+    /// Returns true if a callback is stored.
+    #[must_use]
+    pub fn is_set(&self) -> bool {
+        self.callback.borrow().is_some()
+    }
+
+    /// Runs `f` with the callback.
+    ///
+    /// Returns `None` without running `f` if no callback is stored, it is not
+    /// callable, or it is already running.
+    pub fn call<R>(&self, f: impl FnOnce(&ZendCallable<'_>) -> R) -> Option<R> {
+        if self.running.get() {
+            return None;
+        }
+        let zval = self.callback.borrow().as_ref().map(Zval::shallow_clone)?;
+        let callable = ZendCallable::new(&zval).ok()?;
+        self.running.set(true);
+        let _running = RunningGuard(&self.running);
+        Some(f(&callable))
+    }
+}
+
+/// Resets [`PhpCallbackSlot::running`] when the callback returns or unwinds.
+struct RunningGuard<'a>(&'a Cell<bool>);
+
+impl Drop for RunningGuard<'_> {
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
+}
+
+/// Storage for the query hook callback.
+#[derive(Default)]
+pub struct QueryHook {
+    callback: PhpCallbackSlot,
+}
 
 impl QueryHook {
     /// Creates a new empty query hook.
     #[must_use]
     pub fn new() -> Self {
-        Self {
-            callback: RefCell::new(None),
-        }
+        Self::default()
     }
 
     /// Sets the query hook callback.
@@ -79,35 +169,28 @@ impl QueryHook {
     /// - `float $durationMs` - Execution time in milliseconds
     /// - `?int $rows` - Rows affected/returned, or `null` if unknown
     /// - `?string $error` - Error message, or `null` on success
+    /// - `mixed $meta` - Metadata attached by the before-query hook, or `null`
+    /// - `array $warnings` - Warnings about the statement (e.g. from the cost guard)
     pub fn set(&self, callback: Zval) {
-        *self.callback.borrow_mut() = Some(callback);
+        self.callback.set(callback);
     }
 
     /// Clears the query hook callback.
     pub fn clear(&self) {
-        *self.callback.borrow_mut() = None;
+        self.callback.clear();
     }
 
     /// Returns true if a hook is registered.
     #[must_use]
     pub fn is_set(&self) -> bool {
-        self.callback.borrow().is_some()
+        self.callback.is_set()
     }
 
     /// Calls the hook with query information.
     ///
-    /// This is a no-op if no hook is registered.
+    /// This is a no-op if no hook is registered, or for queries the hook itself runs.
     /// Errors from the callback are silently ignored to avoid disrupting query execution.
     pub fn call(&self, info: &QueryInfo) {
-        let callback = self.callback.borrow();
-        let Some(ref zval) = *callback else {
-            return;
-        };
-
-        let Ok(callable) = ZendCallable::new(zval) else {
-            return;
-        };
-
         // Prepare arguments as string slices
         let sql: &str = &info.sql;
         let sql_inline: &str = info.sql_inline.as_deref().unwrap_or(&info.sql);
@@ -116,9 +199,22 @@ impl QueryHook {
         // overflow rather than wrapping.
         let rows: Option<i64> = info.rows.map(SaturatingCast::saturating_cast);
         let error: Option<&str> = info.error.as_deref();
+        let null = Zval::new();
+        let meta: &Zval = info.meta.as_ref().unwrap_or(&null);
+        let warnings = &info.warnings;
 
         // Call the hook, ignoring any errors
-        let _ = callable.try_call(vec![&sql, &sql_inline, &duration, &rows, &error]);
+        self.callback.call(|callable| {
+            let _ = callable.try_call(vec![
+                &sql,
+                &sql_inline,
+                &duration,
+                &rows,
+                &error,
+                meta,
+                warnings,
+            ]);
+        });
     }
 }
 
@@ -130,25 +226,44 @@ impl std::fmt::Debug for QueryHook {
     }
 }
 
-/// A guard that measures query execution time and calls the hook on drop.
+/// A guard that measures query execution time, then calls the hook and feeds
+/// the slow query log.
 pub struct QueryTimer<'a> {
     hook: &'a QueryHook,
+    slow_log: Option<&'a SlowQueryLog>,
+    escape_backslash: bool,
     sql: String,
     sql_inline: Option<String>,
+    meta: Option<Zval>,
+    instrumentation: Instrumentation,
     start: Instant,
 }
 
 impl<'a> QueryTimer<'a> {
     /// Creates a new query timer.
     ///
-    /// Returns `None` if no hook is registered (avoids timing overhead).
+    /// Returns `None` if neither a hook, a slow query log nor a profile is active
+    /// (avoids timing overhead). `escape_backslash` tells the slow query log how to
+    /// redact string literals in `sql_inline`; `meta` is forwarded to the hook.
     #[must_use]
-    pub fn new(hook: &'a QueryHook, sql: String, sql_inline: Option<String>) -> Option<Self> {
-        if hook.is_set() {
+    pub fn new(
+        hook: &'a QueryHook,
+        slow_log: Option<&'a SlowQueryLog>,
+        escape_backslash: bool,
+        sql: String,
+        sql_inline: Option<String>,
+        meta: Option<Zval>,
+        instrumentation: Instrumentation,
+    ) -> Option<Self> {
+        if hook.is_set() || slow_log.is_some() || instrumentation.profile.is_some() {
             Some(Self {
                 hook,
+                slow_log,
+                escape_backslash,
                 sql,
                 sql_inline,
+                meta,
+                instrumentation,
                 start: Instant::now(),
             })
         } else {
@@ -156,12 +271,44 @@ impl<'a> QueryTimer<'a> {
         }
     }
 
-    /// Completes the timer and calls the hook.
+    /// Completes the timer, records a slow query and profile sample if applicable,
+    /// and calls the hook.
     ///
     /// - `rows`: rows affected (writes) or returned (reads), if known.
     /// - `error`: the error message if the query failed, or `None` on success.
     pub fn finish(self, rows: Option<u64>, error: Option<&str>) {
         let duration = self.start.elapsed();
+        self.finish_after(duration, rows, error);
+    }
+
+    /// Like [`Self::finish`], with the duration measured by the caller: queries
+    /// run in the background are reported once collected, not once finished.
+    pub fn finish_after(self, duration: Duration, rows: Option<u64>, error: Option<&str>) {
+        let Instrumentation {
+            mut warnings,
+            profile,
+        } = self.instrumentation;
+
+        if let Some(sample) = profile
+            && let Some(warning) = sample.record(duration)
+        {
+            warnings.push(warning);
+        }
+
+        if let Some(log) = self.slow_log {
+            log.record(
+                duration,
+                rows,
+                error,
+                &self.sql,
+                self.sql_inline.as_deref(),
+                self.escape_backslash,
+            );
+        }
+
+        if !self.hook.is_set() {
+            return;
+        }
         let duration_ms = duration.as_secs_f64() * 1000.0;
 
         self.hook.call(&QueryInfo {
@@ -170,6 +317,313 @@ impl<'a> QueryTimer<'a> {
             duration_ms,
             rows,
             error: error.map(ToOwned::to_owned),
+            meta: self.meta,
+            warnings,
+        });
+    }
+}
+
+impl QueryTimer<'_> {
+    /// Detaches the timer from the driver so that it can outlive the current call.
+    ///
+    /// Used by streaming results, which report once the stream is exhausted or dropped.
+    #[must_use]
+    pub fn detach(self) -> DetachedQueryTimer {
+        DetachedQueryTimer {
+            escape_backslash: self.escape_backslash,
+            sql: self.sql,
+            sql_inline: self.sql_inline,
+            meta: self.meta,
+            instrumentation: self.instrumentation,
+            start: self.start,
+        }
+    }
+}
+
+/// A [`QueryTimer`] that no longer borrows the driver; see [`QueryTimer::detach`].
+pub struct DetachedQueryTimer {
+    escape_backslash: bool,
+    sql: String,
+    sql_inline: Option<String>,
+    meta: Option<Zval>,
+    instrumentation: Instrumentation,
+    start: Instant,
+}
+
+impl DetachedQueryTimer {
+    /// Re-attaches the timer to the driver's hook and slow query log, keeping the
+    /// original start time.
+    #[must_use]
+    pub fn attach<'a>(
+        self,
+        hook: &'a QueryHook,
+        slow_log: Option<&'a SlowQueryLog>,
+    ) -> QueryTimer<'a> {
+        QueryTimer {
+            hook,
+            slow_log,
+            escape_backslash: self.escape_backslash,
+            sql: self.sql,
+            sql_inline: self.sql_inline,
+            meta: self.meta,
+            instrumentation: self.instrumentation,
+            start: self.start,
+        }
+    }
+}
+
+/// The kind of operation reported to the before-query hook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryKind {
+    /// `execute()`, `insert()`, `upsert()` and other statements returning an affected-row count.
+    Execute,
+    /// Any buffered `query*()` method.
+    Query,
+    /// Streaming `query()` returning a `QueryResult`.
+    Stream,
+    /// `PostgreSQL` `copyIn()`.
+    Copy,
+    /// Transaction start.
+    Begin,
+    /// Transaction commit.
+    Commit,
+    /// Transaction rollback.
+    Rollback,
+    /// `SAVEPOINT`.
+    Savepoint,
+    /// `ROLLBACK TO SAVEPOINT`.
+    RollbackToSavepoint,
+    /// `RELEASE SAVEPOINT`.
+    ReleaseSavepoint,
+}
+
+impl QueryKind {
+    /// Returns the name passed to PHP as `$kind`.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Execute => "execute",
+            Self::Query => "query",
+            Self::Stream => "stream",
+            Self::Copy => "copy",
+            Self::Begin => "begin",
+            Self::Commit => "commit",
+            Self::Rollback => "rollback",
+            Self::Savepoint => "savepoint",
+            Self::RollbackToSavepoint => "rollback_to_savepoint",
+            Self::ReleaseSavepoint => "release_savepoint",
+        }
+    }
+
+    /// Returns true for transaction control operations.
+    #[must_use]
+    pub const fn is_transaction(self) -> bool {
+        matches!(
+            self,
+            Self::Begin
+                | Self::Commit
+                | Self::Rollback
+                | Self::Savepoint
+                | Self::RollbackToSavepoint
+                | Self::ReleaseSavepoint
+        )
+    }
+}
+
+/// What the before-query hook asked for.
+#[derive(Debug, Default)]
+pub struct BeforeQueryOutcome {
+    /// Replacement SQL, if the hook rewrote it.
+    pub sql: Option<String>,
+    /// Replacement parameters, if the hook rewrote them.
+    pub params: Option<BTreeMap<String, ParameterValue>>,
+    /// Metadata to pass on to the after-query hook.
+    pub meta: Option<Zval>,
+}
+
+/// Storage for the before-query hook callback.
+///
+/// The callback receives `(string $sql, array $params, string $kind)` and may return:
+/// - `null` to let the query run unchanged;
+/// - a `string` to replace the SQL;
+/// - an array with any of the keys `sql` (string), `params` (array) and `meta` (any value).
+///
+/// Throwing from the callback vetoes the query. Queries the callback itself runs
+/// don't go through it.
+#[derive(Default)]
+pub struct BeforeQueryHook {
+    callback: PhpCallbackSlot,
+}
+
+impl BeforeQueryHook {
+    /// Creates a new empty before-query hook.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the before-query hook callback.
+    pub fn set(&self, callback: Zval) {
+        self.callback.set(callback);
+    }
+
+    /// Clears the before-query hook callback.
+    pub fn clear(&self) {
+        self.callback.clear();
+    }
+
+    /// Returns true if a hook is registered.
+    #[must_use]
+    pub fn is_set(&self) -> bool {
+        self.callback.is_set()
+    }
+
+    /// Calls the hook and interprets its return value.
+    ///
+    /// Returns an empty outcome if no hook is registered or it is already running.
+    ///
+    /// # Errors
+    /// Returns [`SqlxError::Vetoed`], chaining the exception, if the callback throws, or
+    /// [`SqlxError::Other`] if it returns something other than `null`, a string or an array.
+    pub fn call(
+        &self,
+        sql: &str,
+        params: Option<&BTreeMap<String, ParameterValue>>,
+        kind: QueryKind,
+    ) -> crate::error::Result<BeforeQueryOutcome> {
+        if !self.is_set() {
+            return Ok(BeforeQueryOutcome::default());
+        }
+        let params = ParameterValue::Object(params.cloned().unwrap_or_default())
+            .into_zval(false)
+            .map_err(|err| SqlxError::Conversion {
+                message: format!("{err:?}"),
+            })?;
+        let kind_str = kind.as_str();
+
+        let Some(ret) = self
+            .callback
+            .call(|callable| callable.try_call(vec![&sql, &params, &kind_str]))
+        else {
+            return Ok(BeforeQueryOutcome::default());
+        };
+        let ret = ret.map_err(|err| match err {
+            ext_php_rs::error::Error::Exception(exception) => SqlxError::Vetoed {
+                message: exception
+                    .try_call_method("getMessage", vec![])
+                    .ok()
+                    .and_then(|message| message.string())
+                    .unwrap_or_else(|| "Vetoed by onBeforeQuery hook".to_string()),
+                previous: PhpThrowable::new(exception),
+            },
+            other => SqlxError::Vetoed {
+                message: other.to_string(),
+                previous: None,
+            },
+        })?;
+
+        if ret.is_null() {
+            return Ok(BeforeQueryOutcome::default());
+        }
+        if let Some(sql) = ret.string() {
+            return Ok(BeforeQueryOutcome {
+                sql: Some(sql),
+                ..Default::default()
+            });
+        }
+        let Some(array) = ret.array() else {
+            return Err(SqlxError::Other(
+                "onBeforeQuery hook must return null, a string or an array".to_string(),
+            ));
+        };
+
+        let sql = match array.get("sql") {
+            None => None,
+            Some(value) if value.is_null() => None,
+            Some(value) => Some(value.string().ok_or_else(|| {
+                SqlxError::Other("onBeforeQuery hook: `sql` must be a string".to_string())
+            })?),
+        };
+        let params = match array.get("params") {
+            None => None,
+            Some(value) if value.is_null() => None,
+            Some(value) => Some(
+                BTreeMap::<String, ParameterValue>::from_zval(value).ok_or_else(|| {
+                    SqlxError::Other("onBeforeQuery hook: `params` must be an array".to_string())
+                })?,
+            ),
+        };
+        let meta = array
+            .get("meta")
+            .filter(|value| !value.is_null())
+            .map(Zval::shallow_clone);
+
+        Ok(BeforeQueryOutcome { sql, params, meta })
+    }
+}
+
+impl std::fmt::Debug for BeforeQueryHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BeforeQueryHook")
+            .field("is_set", &self.is_set())
+            .finish()
+    }
+}
+
+/// Storage for the retry hook callback.
+///
+/// The callback receives `(int $attempt, float $delayMs, string $error)` before the
+/// driver sleeps and retries a failed statement: `$attempt` is the number of the
+/// retry about to run (starting at 1) and `$error` the message of the failure.
+#[derive(Default)]
+pub struct RetryHook {
+    callback: PhpCallbackSlot,
+}
+
+impl RetryHook {
+    /// Creates a new empty retry hook.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the retry hook callback.
+    pub fn set(&self, callback: Zval) {
+        self.callback.set(callback);
+    }
+
+    /// Clears the retry hook callback.
+    pub fn clear(&self) {
+        self.callback.clear();
+    }
+
+    /// Returns true if a hook is registered.
+    #[must_use]
+    pub fn is_set(&self) -> bool {
+        self.callback.is_set()
+    }
+
+    /// Calls the hook before retry number `attempt`, which runs after `delay`.
+    ///
+    /// This is a no-op if no hook is registered.
+    /// Errors from the callback are silently ignored so that they can't stop the retry.
+    pub fn call(&self, attempt: u32, delay: Duration, error: &SqlxError) {
+        if !self.is_set() {
+            return;
+        }
+        let attempt = i64::from(attempt);
+        let delay_ms = delay.as_secs_f64() * 1000.0;
+        let error = error.to_string();
+        self.callback.call(|callable| {
+            let _ = callable.try_call(vec![&attempt, &delay_ms, &error]);
         });
     }
 }
+
+impl std::fmt::Debug for RetryHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryHook")
+            .field("is_set", &self.is_set())
+            .finish()
+    }
+}
@@ -6,6 +6,10 @@
 //! Rows are fetched from the database on demand through a channel, with the
 //! database streaming happening in a background task. This provides true lazy
 //! loading without needing to fetch all rows upfront.
+//!
+//! A stream reports to the driver's query hook and slow query log once, when it
+//! is exhausted or dropped, with the number of rows streamed and the total time
+//! since the query was sent.
 
 /// Default batch size for lazy row fetching (channel buffer size).
 pub const DEFAULT_BATCH_SIZE: usize = 100;
@@ -28,6 +32,7 @@ macro_rules! php_sqlx_impl_query_result {
         use ext_php_rs::types::Zval;
         use ext_php_rs::zend::ce;
         use saturating_cast::SaturatingCast;
+        use std::sync::Arc;
 
         /// A lazy query result iterator that streams rows on demand.
         ///
@@ -72,12 +77,17 @@ macro_rules! php_sqlx_impl_query_result {
             last_error: Option<$crate::error::Error>,
             /// Configured buffer size for streaming
             buffer_size: usize,
+            /// Driver that issued the query; owns the hook the stream reports through
+            driver_inner: Arc<$inner>,
+            /// Pending hook/slow-log report, taken when the stream finishes
+            timer: Option<$crate::query_hook::DetachedQueryTimer>,
         }
 
         impl $struct {
             /// Creates a new streaming `QueryResult`.
             #[allow(dead_code)]
             pub fn new(
+                driver_inner: Arc<$inner>,
                 receiver: tokio::sync::mpsc::Receiver<
                     Result<
                         <sqlx_oldapi::$database as sqlx_oldapi::Database>::Row,
@@ -85,6 +95,7 @@ macro_rules! php_sqlx_impl_query_result {
                     >,
                 >,
                 cancel_token: tokio_util::sync::CancellationToken,
+                timer: Option<$crate::query_hook::DetachedQueryTimer>,
                 associative_arrays: bool,
                 buffer_size: usize,
             ) -> Self {
@@ -99,6 +110,23 @@ macro_rules! php_sqlx_impl_query_result {
                     initialized: false,
                     last_error: None,
                     buffer_size,
+                    driver_inner,
+                    timer,
+                }
+            }
+
+            /// Reports the stream to the driver's query hook and slow query log.
+            ///
+            /// Called once, when the stream is exhausted or dropped.
+            fn report(&mut self) {
+                if let Some(timer) = self.timer.take() {
+                    let error = self.last_error.as_ref().map(ToString::to_string);
+                    timer
+                        .attach(
+                            &self.driver_inner.query_hook,
+                            self.driver_inner.slow_query_log.as_ref(),
+                        )
+                        .finish(u64::try_from(self.total_fetched).ok(), error.as_deref());
                 }
             }
 
@@ -115,7 +143,7 @@ macro_rules! php_sqlx_impl_query_result {
                 match self.receiver.blocking_recv() {
                     Some(Ok(row)) => {
                         // Convert the row to Zval
-                        match row.into_zval(self.associative_arrays) {
+                        match row.into_zval(self.associative_arrays, self.driver_inner.options.time_zone) {
                             Ok(zval) => {
                                 self.current = Some(zval);
                                 self.total_fetched += 1;
@@ -147,6 +175,10 @@ macro_rules! php_sqlx_impl_query_result {
                         self.exhausted = true;
                     }
                 }
+
+                if self.exhausted {
+                    self.report();
+                }
             }
         }
 
@@ -154,6 +186,7 @@ macro_rules! php_sqlx_impl_query_result {
             fn drop(&mut self) {
                 // Cancel the background streaming task to release the database connection
                 self.cancel_token.cancel();
+                self.report();
             }
         }
 
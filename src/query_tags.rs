@@ -0,0 +1,317 @@
+//! `sqlcommenter`-style query tagging.
+//!
+//! Tags are key/value pairs appended to every rendered statement as a trailing
+//! SQL comment, following the [sqlcommenter](https://google.github.io/sqlcommenter/spec/)
+//! format:
+//!
+//! ```sql
+//! SELECT * FROM users WHERE id = $1 /*controller='users',route='%2Fusers%2F%3Aid'*/
+//! ```
+//!
+//! This lets database-side tooling (`pg_stat_statements`, slow query logs,
+//! Cloud SQL Insights, ...) attribute load to the application code that issued it.
+//!
+//! Keys and values are percent-encoded so that only unreserved characters
+//! (`A-Z a-z 0-9 - _ . ~`) and `%` reach the comment. A tag therefore can never
+//! close the comment (`*/`), introduce a quote, or look like a placeholder.
+//!
+//! The comment is appended **after** rendering, so tags never take part in the
+//! AST cache key.
+//!
+//! # Example
+//!
+//! ```php
+//! $driver->setQueryTags(['application' => 'billing', 'route' => '/invoices']);
+//!
+//! $driver->withQueryTags(['controller' => 'InvoiceController'], function ($driver) {
+//!     $driver->queryAll('SELECT * FROM invoices');
+//! });
+//! ```
+
+use crate::error::Error as SqlxError;
+use crate::param_value::ParameterValue;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Driver-level and scoped query tags.
+///
+/// Driver-level tags are set with `setQueryTags()` and apply to every query.
+/// Scoped tags are pushed for the duration of a `withQueryTags()` callback and
+/// override driver-level tags with the same key.
+#[derive(Debug, Default)]
+pub struct QueryTags {
+    tags: RwLock<BTreeMap<String, String>>,
+    scopes: RwLock<Vec<(u64, BTreeMap<String, String>)>>,
+    next_scope_id: AtomicU64,
+}
+
+impl QueryTags {
+    /// Creates an empty tag set.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the driver-level tags.
+    pub fn set(&self, tags: BTreeMap<String, String>) {
+        *self.tags.write().expect("Poisoned query tags") = tags;
+    }
+
+    /// Removes the driver-level tags and every scope, including scopes left
+    /// behind by a request that ended inside `withQueryTags()`.
+    pub fn clear(&self) {
+        self.tags.write().expect("Poisoned query tags").clear();
+        self.scopes.write().expect("Poisoned query tags").clear();
+    }
+
+    /// Returns the effective tags: driver-level tags overlaid with every active scope.
+    #[must_use]
+    pub fn effective(&self) -> BTreeMap<String, String> {
+        let mut tags = self.tags.read().expect("Poisoned query tags").clone();
+        for scope in self.scopes.read().expect("Poisoned query tags").iter() {
+            tags.extend(scope.1.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        tags
+    }
+
+    /// Returns true if no tags would be appended.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tags.read().expect("Poisoned query tags").is_empty()
+            && self
+                .scopes
+                .read()
+                .expect("Poisoned query tags")
+                .iter()
+                .all(|(_, scope)| scope.is_empty())
+    }
+
+    /// Pushes a tag scope that stays active until the returned guard is dropped.
+    ///
+    /// An empty `tags` map pushes nothing.
+    #[must_use]
+    pub fn scope(&self, tags: BTreeMap<String, String>) -> QueryTagsScope<'_> {
+        let id = (!tags.is_empty()).then(|| {
+            let id = self.next_scope_id.fetch_add(1, Ordering::Relaxed);
+            self.scopes
+                .write()
+                .expect("Poisoned query tags")
+                .push((id, tags));
+            id
+        });
+        QueryTagsScope { owner: self, id }
+    }
+
+    /// Appends the `sqlcommenter` comment for the effective tags to `sql`.
+    ///
+    /// Returns `sql` unchanged when there are no tags.
+    #[must_use]
+    pub fn apply(&self, mut sql: String) -> String {
+        if self.is_empty() {
+            return sql;
+        }
+        if let Some(comment) = format_comment(&self.effective()) {
+            append_comment(&mut sql, &comment);
+        }
+        sql
+    }
+}
+
+/// Guard returned by [`QueryTags::scope`]; removes its scope on drop, even if
+/// scopes pushed after it are still active.
+pub struct QueryTagsScope<'a> {
+    owner: &'a QueryTags,
+    id: Option<u64>,
+}
+
+impl Drop for QueryTagsScope<'_> {
+    fn drop(&mut self) {
+        let Some(id) = self.id else {
+            return;
+        };
+        self.owner
+            .scopes
+            .write()
+            .expect("Poisoned query tags")
+            .retain(|(scope_id, _)| *scope_id != id);
+    }
+}
+
+/// Converts PHP tag values into strings.
+///
+/// Only scalars are accepted; `null` drops the tag.
+pub fn tags_from_params(
+    tags: BTreeMap<String, ParameterValue>,
+) -> crate::error::Result<BTreeMap<String, String>> {
+    let mut out = BTreeMap::new();
+    for (key, value) in tags {
+        let value = match value {
+            ParameterValue::Null => continue,
+            ParameterValue::String(s) => s,
+            ParameterValue::Int(i) => i.to_string(),
+            ParameterValue::Float(f) => f.to_string(),
+            ParameterValue::Bool(b) => b.to_string(),
+            _ => {
+                return Err(SqlxError::InvalidParameter {
+                    name: key,
+                    expected: "scalar query tag value".to_string(),
+                });
+            }
+        };
+        out.insert(key, value);
+    }
+    Ok(out)
+}
+
+/// Formats tags as a `sqlcommenter` comment, e.g. `/*a='1',b='x%20y'*/`.
+///
+/// Tags are sorted by key. Returns `None` if there are no tags.
+#[must_use]
+pub fn format_comment(tags: &BTreeMap<String, String>) -> Option<String> {
+    if tags.is_empty() {
+        return None;
+    }
+    let mut out = String::from("/*");
+    for (i, (key, value)) in tags.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        encode_into(key, &mut out);
+        out.push_str("='");
+        encode_into(value, &mut out);
+        out.push('\'');
+    }
+    out.push_str("*/");
+    Some(out)
+}
+
+/// Appends `comment` to `sql`, placing it before a trailing semicolon if there is one.
+///
+/// If the last line holds a `--` or `#` line comment, which would swallow the
+/// tag comment, it goes on a new line instead.
+pub fn append_comment(sql: &mut String, comment: &str) {
+    sql.truncate(sql.trim_end().len());
+    let last_line = sql.rsplit('\n').next().unwrap_or_default();
+    if last_line.contains("--") || last_line.contains('#') {
+        sql.push('\n');
+        sql.push_str(comment);
+        return;
+    }
+    let body_len = sql.trim_end().trim_end_matches(';').trim_end().len();
+    let tail = sql.split_off(body_len);
+    sql.push(' ');
+    sql.push_str(comment);
+    if tail.contains(';') {
+        sql.push(';');
+    }
+}
+
+/// Percent-encodes everything except RFC 3986 unreserved characters.
+fn encode_into(s: &str, out: &mut String) {
+    for byte in s.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            out.push(byte as char);
+        } else {
+            let _ = write!(out, "%{byte:02X}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn formats_sorted_comment() {
+        let comment = format_comment(&tags(&[("route", "/users"), ("app", "billing")]));
+        assert_eq!(
+            comment.as_deref(),
+            Some("/*app='billing',route='%2Fusers'*/")
+        );
+    }
+
+    #[test]
+    fn empty_tags_produce_no_comment() {
+        assert_eq!(format_comment(&BTreeMap::new()), None);
+    }
+
+    #[test]
+    fn hostile_values_cannot_escape_the_comment() {
+        let comment = format_comment(&tags(&[("x*/", "'; DROP TABLE users; --*/")])).unwrap();
+        assert!(!comment[2..comment.len() - 2].contains("*/"));
+        assert_eq!(comment.matches('\'').count(), 2);
+        assert!(!comment.contains(';'));
+        assert!(!comment.contains('$'));
+        assert!(!comment.contains('?'));
+    }
+
+    #[test]
+    fn comment_goes_before_trailing_semicolon() {
+        let mut sql = "SELECT 1;".to_string();
+        append_comment(&mut sql, "/*a='b'*/");
+        assert_eq!(sql, "SELECT 1 /*a='b'*/;");
+
+        let mut sql = "SELECT 1".to_string();
+        append_comment(&mut sql, "/*a='b'*/");
+        assert_eq!(sql, "SELECT 1 /*a='b'*/");
+    }
+
+    #[test]
+    fn comment_goes_after_trailing_line_comment() {
+        let mut sql = "SELECT 1 -- one;".to_string();
+        append_comment(&mut sql, "/*a='b'*/");
+        assert_eq!(sql, "SELECT 1 -- one;\n/*a='b'*/");
+
+        let mut sql = "SELECT 1 # one\n".to_string();
+        append_comment(&mut sql, "/*a='b'*/");
+        assert_eq!(sql, "SELECT 1 # one\n/*a='b'*/");
+    }
+
+    #[test]
+    fn scopes_dropped_out_of_order_remove_their_own_tags() {
+        let qt = QueryTags::new();
+        let outer = qt.scope(tags(&[("a", "1")]));
+        let inner = qt.scope(tags(&[("b", "2")]));
+        drop(outer);
+        assert_eq!(qt.effective(), tags(&[("b", "2")]));
+        drop(inner);
+        assert!(qt.is_empty());
+    }
+
+    #[test]
+    fn scopes_override_and_pop() {
+        let qt = QueryTags::new();
+        qt.set(tags(&[("app", "billing"), ("route", "a")]));
+        {
+            let _scope = qt.scope(tags(&[("route", "b")]));
+            assert_eq!(
+                qt.apply("SELECT 1".into()),
+                "SELECT 1 /*app='billing',route='b'*/"
+            );
+        }
+        assert_eq!(
+            qt.apply("SELECT 1".into()),
+            "SELECT 1 /*app='billing',route='a'*/"
+        );
+        qt.set(BTreeMap::new());
+        assert_eq!(qt.apply("SELECT 1".into()), "SELECT 1");
+    }
+
+    #[test]
+    fn clear_drops_leaked_scopes() {
+        let qt = QueryTags::new();
+        qt.set(tags(&[("app", "billing")]));
+        std::mem::forget(qt.scope(tags(&[("route", "b")])));
+        qt.clear();
+        assert!(qt.is_empty());
+    }
+}
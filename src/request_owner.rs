@@ -0,0 +1,65 @@
+//! Which request holds a persistent driver's per-request state.
+//!
+//! A persistent driver outlives the request that created it, and under ZTS it is
+//! shared by every thread of the process, each serving its own request. At the
+//! end of a request (`RSHUTDOWN`) only the state that request set up may be
+//! reset: rolling back a transaction begun by a request still running on
+//! another thread would break it. A [`RequestOwner`] records the thread that set
+//! up a piece of state, and the reset skips what other threads own.
+
+use std::sync::{Mutex, PoisonError};
+use std::thread::{self, ThreadId};
+
+/// The thread whose request last set up a piece of per-request state.
+#[derive(Debug, Default)]
+pub struct RequestOwner {
+    thread: Mutex<Option<ThreadId>>,
+}
+
+impl RequestOwner {
+    /// Creates an owner held by no request.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the current thread's request as the owner.
+    pub fn claim(&self) {
+        *self.thread.lock().unwrap_or_else(PoisonError::into_inner) = Some(thread::current().id());
+    }
+
+    /// Gives up ownership if the current thread's request holds it, and returns
+    /// whether it did.
+    pub fn release_current(&self) -> bool {
+        let mut owner = self.thread.lock().unwrap_or_else(PoisonError::into_inner);
+        if *owner == Some(thread::current().id()) {
+            *owner = None;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn releases_only_for_the_claiming_thread() {
+        let owner = RequestOwner::new();
+        assert!(!owner.release_current());
+
+        owner.claim();
+        thread::scope(|scope| {
+            scope.spawn(|| assert!(!owner.release_current()));
+        });
+        assert!(owner.release_current());
+        assert!(!owner.release_current());
+
+        thread::scope(|scope| {
+            scope.spawn(|| owner.claim());
+        });
+        assert!(!owner.release_current());
+    }
+}
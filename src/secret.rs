@@ -0,0 +1,348 @@
+//! Redacted parameter values.
+//!
+//! `Sqlx\Secret` wraps a parameter value that must never appear in logs. It is
+//! bound to the statement like the value it wraps, but wherever the driver
+//! renders values into SQL text for humans — `dryInline()`, the `$sqlInline`
+//! argument of the `onQuery()` hook, the slow query log — it renders as `'***'`.
+//! Debug output used in exception messages prints `***` as well.
+//!
+//! Placeholders named in `OPT_REDACT_PARAMS` are redacted the same way without
+//! wrapping. The parameters reported by `QueryException::getParameters()` are
+//! masked by [`mask_parameters`], which also catches sensitive-looking names.
+//!
+//! # PHP Usage
+//!
+//! ```php
+//! $driver->execute(
+//!     'UPDATE users SET password_hash = :hash WHERE id = :id',
+//!     ['hash' => new Sqlx\Secret($hash), 'id' => 42],
+//! );
+//! $driver->dryInline('SELECT * FROM users WHERE token = ?', [new Sqlx\Secret($token)]);
+//! // SELECT * FROM users WHERE token = '***'
+//! ```
+
+use crate::error::Error as SqlxError;
+use crate::param_value::{ParameterValue, ParamsMap};
+use ext_php_rs::prelude::ModuleBuilder;
+use ext_php_rs::{php_class, php_impl};
+use std::fmt;
+
+/// Registers the `Secret` class with the PHP module builder.
+pub fn build(module: ModuleBuilder) -> ModuleBuilder {
+    module.class::<Secret>()
+}
+
+/// The SQL literal a secret renders as in inline SQL.
+pub const REDACTED: &str = "'***'";
+
+/// A parameter value that is bound normally but redacted in rendered SQL.
+#[php_class]
+#[php(name = "Sqlx\\Secret")]
+#[derive(Clone, PartialEq)]
+pub struct Secret {
+    /// The wrapped value; never another secret or a clause.
+    pub(crate) value: Box<ParameterValue>,
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+impl Secret {
+    /// Wraps `value`.
+    ///
+    /// # Errors
+    /// Returns [`SqlxError::InvalidParameter`] if `value` is a clause, a query
+    /// builder or an identifier, which are rendered into the SQL text rather
+    /// than bound.
+    pub fn _new(value: ParameterValue) -> crate::error::Result<Self> {
+        match value {
+            ParameterValue::ByClauseRendered(_)
+            | ParameterValue::SelectClauseRendered(_)
+            | ParameterValue::PaginateClauseRendered(_)
+            | ParameterValue::Builder(_)
+            | ParameterValue::Identifier(_) => Err(SqlxError::InvalidParameter {
+                name: "value".to_string(),
+                expected: "a bindable value".to_string(),
+            }),
+            ParameterValue::Secret(secret) => Ok(secret),
+            value => Ok(Self {
+                value: Box::new(value),
+            }),
+        }
+    }
+}
+
+#[php_impl]
+impl Secret {
+    /// Wraps a value so it is redacted wherever SQL is rendered for logging.
+    ///
+    /// # Arguments
+    /// - `value`: Any bindable value: scalar, array, `DateTimeInterface`, `Sqlx\Binary`
+    ///   or `Sqlx\JSON(...)`.
+    ///
+    /// # Exceptions
+    /// Throws if `value` is a clause, a query builder or an `Sqlx\Identifier`.
+    pub fn __construct(value: ParameterValue) -> crate::error::Result<Self> {
+        Self::_new(value)
+    }
+
+    /// Returns the wrapped value.
+    #[must_use]
+    pub fn reveal(&self) -> ParameterValue {
+        self.value.as_ref().clone()
+    }
+
+    /// Returns `***`.
+    #[must_use]
+    pub fn __to_string(&self) -> String {
+        "***".to_string()
+    }
+}
+
+impl ParameterValue {
+    /// Marks the value as secret.
+    ///
+    /// Arrays become arrays of secrets so they still expand into `IN` lists.
+    /// `null` stays `null`, since there is nothing to hide, and values rendered
+    /// into the SQL text (clauses, builders, identifiers) are left as they are.
+    #[must_use]
+    pub fn into_secret(self) -> Self {
+        match self {
+            Self::Array(items) => Self::Array(items.into_iter().map(Self::into_secret).collect()),
+            Self::String(_)
+            | Self::Int(_)
+            | Self::Float(_)
+            | Self::Bool(_)
+            | Self::Object(_)
+            | Self::Json(_)
+            | Self::DateTime(_)
+            | Self::DateTimeTz(_)
+            | Self::Binary(_) => Self::Secret(Secret {
+                value: Box::new(self),
+            }),
+            Self::Null
+            | Self::Secret(_)
+            | Self::ByClauseRendered(_)
+            | Self::SelectClauseRendered(_)
+            | Self::PaginateClauseRendered(_)
+            | Self::Builder(_)
+            | Self::Identifier(_) => self,
+        }
+    }
+
+    /// Returns the value behind a secret, or the value itself.
+    #[must_use]
+    pub fn revealed(&self) -> &Self {
+        if let Self::Secret(secret) = self {
+            &secret.value
+        } else {
+            self
+        }
+    }
+
+    /// Returns true if the value is, or contains, a secret.
+    #[must_use]
+    pub fn contains_secret(&self) -> bool {
+        match self {
+            Self::Secret(_) => true,
+            Self::Array(items) => items.iter().any(Self::contains_secret),
+            Self::Object(map) => map.values().any(Self::contains_secret),
+            Self::Json(inner) => inner.contains_secret(),
+            _ => false,
+        }
+    }
+}
+
+/// Fragments of placeholder names whose values are masked in `QueryException`
+/// parameters.
+const SENSITIVE_NAMES: &[&str] = &[
+    "password",
+    "passwd",
+    "pwd",
+    "secret",
+    "token",
+    "api_key",
+    "apikey",
+    "auth",
+    "credential",
+    "private_key",
+];
+
+/// Returns true if a placeholder name looks like it holds a credential.
+fn is_sensitive_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SENSITIVE_NAMES
+        .iter()
+        .any(|fragment| name.contains(fragment))
+}
+
+/// Copies `parameters` for error reporting, replacing with `'***'` the values of
+/// sensitive-looking names, of names listed in `redact` and of secrets.
+///
+/// Object keys are checked too, so a `password` inside a JSON value is masked.
+#[must_use]
+pub fn mask_parameters(parameters: &ParamsMap, redact: &[String]) -> ParamsMap {
+    fn mask(value: &ParameterValue) -> ParameterValue {
+        match value {
+            ParameterValue::Secret(_) => ParameterValue::String("***".to_string()),
+            ParameterValue::Array(items) => ParameterValue::Array(items.iter().map(mask).collect()),
+            ParameterValue::Object(map) => ParameterValue::Object(mask_map(map, &[])),
+            ParameterValue::Json(inner) => ParameterValue::Json(Box::new(mask(inner))),
+            other => other.clone(),
+        }
+    }
+    fn mask_map(map: &ParamsMap, redact: &[String]) -> ParamsMap {
+        map.iter()
+            .map(|(name, value)| {
+                let masked = if is_sensitive_name(name)
+                    || redact
+                        .iter()
+                        .any(|redacted| redacted.eq_ignore_ascii_case(name))
+                {
+                    ParameterValue::String("***".to_string())
+                } else {
+                    mask(value)
+                };
+                (name.clone(), masked)
+            })
+            .collect()
+    }
+    mask_map(parameters, redact)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{Ast, Settings};
+
+    fn settings() -> Settings {
+        Settings {
+            collapsible_in_enabled: true,
+            escaping_double_single_quotes: true,
+            placeholder_dollar_sign: true,
+            max_placeholders: 65535,
+            ..Settings::default()
+        }
+    }
+
+    #[test]
+    fn binds_the_wrapped_value() {
+        let ast = Ast::parse(
+            "UPDATE users SET token = :token WHERE id = :id",
+            &settings(),
+        )
+        .unwrap();
+        let secret = ParameterValue::String("s3cr3t".into()).into_secret();
+        let (sql, values) = ast
+            .render(
+                [("token", secret.clone()), ("id", ParameterValue::Int(1))],
+                &settings(),
+            )
+            .unwrap();
+        assert_eq!(sql, "UPDATE users SET token = $1 WHERE id = $2");
+        assert_eq!(values, [secret, ParameterValue::Int(1)]);
+        assert_eq!(
+            values[0].revealed(),
+            &ParameterValue::String("s3cr3t".into())
+        );
+    }
+
+    #[test]
+    fn redacts_inline_rendering() {
+        let ast = Ast::parse(
+            "SELECT * FROM users WHERE token = :token AND id IN (:ids) AND name = :name",
+            &settings(),
+        )
+        .unwrap();
+        let inline = Settings {
+            max_placeholders: 0,
+            redact_secrets: true,
+            ..settings()
+        };
+        let (sql, values) = ast
+            .render(
+                [
+                    (
+                        "token",
+                        ParameterValue::String("s3cr3t".into()).into_secret(),
+                    ),
+                    (
+                        "ids",
+                        ParameterValue::Array(vec![ParameterValue::Int(1), ParameterValue::Int(2)])
+                            .into_secret(),
+                    ),
+                    ("name", ParameterValue::String("alice".into())),
+                ],
+                &inline,
+            )
+            .unwrap();
+        assert_eq!(
+            sql,
+            "SELECT * FROM users WHERE token = '***' AND id IN ('***', '***') AND name = 'alice'"
+        );
+        assert!(values.is_empty());
+    }
+
+    #[test]
+    fn typed_placeholders_check_the_wrapped_value() {
+        let ast = Ast::parse("SELECT * FROM users WHERE id = ?i", &settings()).unwrap();
+        assert!(
+            ast.render([("0", ParameterValue::Int(1).into_secret())], &settings())
+                .is_ok()
+        );
+        assert!(
+            ast.render(
+                [("0", ParameterValue::String("x".into()).into_secret())],
+                &settings()
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn masks_sensitive_parameters() {
+        let parameters = ParamsMap::from([
+            ("id".to_string(), ParameterValue::Int(1)),
+            ("userPassword".to_string(), "hunter2".into()),
+            ("pin".to_string(), "1234".into()),
+            (
+                "note".to_string(),
+                ParameterValue::String("x".into()).into_secret(),
+            ),
+            (
+                "profile".to_string(),
+                ParameterValue::Object(ParamsMap::from([
+                    ("api_token".to_string(), "abc".into()),
+                    ("name".to_string(), "alice".into()),
+                ])),
+            ),
+        ]);
+        let masked = mask_parameters(&parameters, &["PIN".to_string()]);
+        let stars = ParameterValue::String("***".into());
+        assert_eq!(masked["id"], ParameterValue::Int(1));
+        assert_eq!(masked["userPassword"], stars);
+        assert_eq!(masked["pin"], stars);
+        assert_eq!(masked["note"], stars);
+        assert_eq!(
+            masked["profile"],
+            ParameterValue::Object(ParamsMap::from([
+                ("api_token".to_string(), stars.clone()),
+                ("name".to_string(), "alice".into()),
+            ]))
+        );
+    }
+
+    #[test]
+    fn rejects_values_rendered_into_the_sql() {
+        assert!(Secret::_new(ParameterValue::Identifier("name".into())).is_err());
+        let nested = Secret::_new(ParameterValue::Int(1)).unwrap();
+        assert_eq!(
+            Secret::_new(ParameterValue::Secret(nested.clone())).unwrap(),
+            nested
+        );
+        assert_eq!(format!("{nested:?}"), "***");
+    }
+}
@@ -0,0 +1,305 @@
+//! Native slow query log.
+//!
+//! When `OPT_SLOW_QUERY_THRESHOLD` is set, every query that takes at least that
+//! long is written as one JSON object per line to `OPT_SLOW_QUERY_LOG` (a file
+//! path, or `stderr`):
+//!
+//! ```json
+//! {"timestamp":"2025-01-01T12:00:00.123Z","duration_ms":1532.4,"rows":10,"error":null,"sql":"SELECT ...","driver":"postgres","pool":"main"}
+//! ```
+//!
+//! With `OPT_SLOW_QUERY_LOG_INLINE` enabled, the entry also carries `sql_inline`:
+//! the statement with parameter values inlined and then every literal (strings,
+//! numbers, hex and binary values, booleans) replaced by `***`, so values don't
+//! reach the log. Identifiers and comments, query tags included, are kept.
+//!
+//! The file is opened in append mode when the driver is created, so a path that
+//! can't be opened is reported right away. Lines are then handed to a background
+//! Tokio task over a channel and written asynchronously, so logging never blocks
+//! PHP.
+
+use crate::RUNTIME;
+use serde_json::json;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
+
+/// Where slow query entries are written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlowQueryLogTarget {
+    /// The process' standard error stream.
+    Stderr,
+    /// A file, opened in append mode (created if missing).
+    File(String),
+}
+
+impl SlowQueryLogTarget {
+    /// Parses an `OPT_SLOW_QUERY_LOG` value: `"stderr"` or a file path.
+    #[must_use]
+    pub fn parse(value: &str) -> Self {
+        if value.eq_ignore_ascii_case("stderr") {
+            Self::Stderr
+        } else {
+            Self::File(value.to_string())
+        }
+    }
+}
+
+/// A slow query log sink owned by an inner driver.
+#[derive(Debug)]
+pub struct SlowQueryLog {
+    /// Queries at or above this duration are logged.
+    pub threshold: Duration,
+    /// Whether entries include the redacted `sql_inline`.
+    pub include_inline: bool,
+    /// Driver name written to every entry (`postgres`, `mysql`, `mssql`).
+    driver: String,
+    /// Persistent pool name written to every entry, if any.
+    pool: Option<String>,
+    /// Channel to the background writer task.
+    sender: UnboundedSender<String>,
+}
+
+impl SlowQueryLog {
+    /// Opens the log file, if any, and spawns the background writer on the
+    /// global runtime.
+    ///
+    /// # Errors
+    /// Returns a message naming the path if the file can't be opened.
+    pub fn new(
+        target: &SlowQueryLogTarget,
+        threshold: Duration,
+        include_inline: bool,
+        driver: String,
+        pool: Option<String>,
+    ) -> Result<Self, String> {
+        let file = match target {
+            SlowQueryLogTarget::Stderr => None,
+            SlowQueryLogTarget::File(path) => Some(
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|err| format!("cannot open the slow query log `{path}`: {err}"))?,
+            ),
+        };
+        let (sender, mut receiver) = unbounded_channel::<String>();
+        RUNTIME.spawn(async move {
+            let mut sink: Box<dyn tokio::io::AsyncWrite + Unpin + Send> = match file {
+                None => Box::new(tokio::io::stderr()),
+                Some(file) => Box::new(tokio::fs::File::from_std(file)),
+            };
+            while let Some(line) = receiver.recv().await {
+                if sink.write_all(line.as_bytes()).await.is_err() {
+                    break;
+                }
+                // Drain whatever else is queued before flushing.
+                while let Ok(line) = receiver.try_recv() {
+                    if sink.write_all(line.as_bytes()).await.is_err() {
+                        return;
+                    }
+                }
+                let _ = sink.flush().await;
+            }
+        });
+        Ok(Self {
+            threshold,
+            include_inline,
+            driver,
+            pool,
+            sender,
+        })
+    }
+
+    /// Writes an entry if `duration` reaches the threshold.
+    pub fn record(
+        &self,
+        duration: Duration,
+        rows: Option<u64>,
+        error: Option<&str>,
+        sql: &str,
+        sql_inline: Option<&str>,
+        escape_backslash: bool,
+    ) {
+        if duration < self.threshold {
+            return;
+        }
+        let mut entry = json!({
+            "timestamp": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            "duration_ms": duration.as_secs_f64() * 1000.0,
+            "rows": rows,
+            "error": error,
+            "sql": sql,
+            "driver": self.driver,
+            "pool": self.pool,
+        });
+        if self.include_inline
+            && let Some(inline) = sql_inline
+        {
+            entry["sql_inline"] = json!(redact_literals(inline, escape_backslash));
+        }
+        let mut line = entry.to_string();
+        line.push('\n');
+        let _ = self.sender.send(line);
+    }
+}
+
+/// Replaces every literal with `***`: the contents of single-quoted strings
+/// (keeping the quotes and an `N`, `E` or `X` prefix), numbers, hex literals
+/// (`0xcafe`) and `TRUE`/`FALSE`.
+///
+/// Identifiers, bare or quoted with `"`, `` ` `` or `[]`, comments and `$1`
+/// placeholders are copied as they are, digits included. `''` is treated as an
+/// escaped quote; `\'` is too when `escape_backslash` is set (`MySQL`).
+#[must_use]
+pub fn redact_literals(sql: &str, escape_backslash: bool) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                out.push_str("'***'");
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' if escape_backslash => {
+                            chars.next();
+                        }
+                        '\'' => {
+                            if chars.peek() == Some(&'\'') {
+                                chars.next();
+                            } else {
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            '"' | '`' | '[' => {
+                let close = if c == '[' { ']' } else { c };
+                out.push(c);
+                for c in chars.by_ref() {
+                    out.push(c);
+                    if c == close {
+                        break;
+                    }
+                }
+            }
+            '-' if chars.peek() == Some(&'-') => {
+                out.push(c);
+                for c in chars.by_ref() {
+                    out.push(c);
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                out.push(c);
+                out.extend(chars.next());
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    out.push(c);
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            '$' => {
+                out.push(c);
+                while let Some(c) = chars.next_if(char::is_ascii_digit) {
+                    out.push(c);
+                }
+            }
+            c if c.is_ascii_digit()
+                || (c == '.' && chars.peek().is_some_and(char::is_ascii_digit)) =>
+            {
+                out.push_str("***");
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '.') {
+                    if matches!(c, 'e' | 'E') {
+                        chars.next_if(|c| matches!(c, '+' | '-'));
+                    }
+                }
+            }
+            c if c.is_alphabetic() || c == '_' || c == '@' || c == '#' => {
+                let mut word = String::from(c);
+                while let Some(c) =
+                    chars.next_if(|c| c.is_alphanumeric() || matches!(c, '_' | '$' | '@' | '#'))
+                {
+                    word.push(c);
+                }
+                if word.eq_ignore_ascii_case("true") || word.eq_ignore_ascii_case("false") {
+                    out.push_str("***");
+                } else {
+                    out.push_str(&word);
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_target() {
+        assert_eq!(
+            SlowQueryLogTarget::parse("stderr"),
+            SlowQueryLogTarget::Stderr
+        );
+        assert_eq!(
+            SlowQueryLogTarget::parse("STDERR"),
+            SlowQueryLogTarget::Stderr
+        );
+        assert_eq!(
+            SlowQueryLogTarget::parse("/var/log/slow.log"),
+            SlowQueryLogTarget::File("/var/log/slow.log".to_string())
+        );
+    }
+
+    #[test]
+    fn redacts_string_literals() {
+        assert_eq!(
+            redact_literals(
+                "SELECT * FROM users WHERE name = 'O''Brien' AND id = 5",
+                false
+            ),
+            "SELECT * FROM users WHERE name = '***' AND id = ***"
+        );
+        assert_eq!(
+            redact_literals("INSERT INTO t VALUES (N'secret', 'a\\'b')", true),
+            "INSERT INTO t VALUES (N'***', '***')"
+        );
+    }
+
+    #[test]
+    fn redacts_numbers_binary_and_booleans() {
+        assert_eq!(
+            redact_literals(
+                "UPDATE t2 SET a = -1.5e-3, b = 0xcafe, c = X'cafe', d = TRUE WHERE id IN (1, .5)",
+                true
+            ),
+            "UPDATE t2 SET a = -***, b = ***, c = X'***', d = *** WHERE id IN (***, ***)"
+        );
+        assert_eq!(
+            redact_literals(
+                "SELECT \"col1\", [col 2] FROM t WHERE id = $1 /* job 42 */",
+                false
+            ),
+            "SELECT \"col1\", [col 2] FROM t WHERE id = $1 /* job 42 */"
+        );
+        assert_eq!(
+            redact_literals("SELECT E'\\x00'::bytea -- id 7\nFROM t", false),
+            "SELECT E'***'::bytea -- id 7\nFROM t"
+        );
+    }
+
+    #[test]
+    fn redacts_unterminated_literal() {
+        assert_eq!(redact_literals("SELECT 'oops", false), "SELECT '***'");
+    }
+}
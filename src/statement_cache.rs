@@ -0,0 +1,283 @@
+//! Server-side prepared statements of `PreparedQuery`.
+//!
+//! Each connection keeps the statements it has prepared in an LRU cache keyed by
+//! the rendered SQL, holding up to `OPT_STATEMENT_CACHE_CAPACITY` entries. Since
+//! `IN (?)` expansion and query tags change the rendered SQL, one prepared query
+//! may own several entries, one per shape.
+//!
+//! A `PreparedQuery` runs its statements on a connection the driver holds, and
+//! compares the size of that connection's cache before and after: a statement
+//! that grew it had to be parsed and planned by the server (a miss), one that
+//! didn't on a cache with room left was found in it (a hit). On a full cache, a
+//! miss evicts another statement and leaves the size unchanged, so such
+//! executions are counted as unknown rather than guessed.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+const PARAM: &str = "statement-cache-capacity=";
+
+/// Returns `url` with its `statement-cache-capacity` parameter set to `capacity`,
+/// unless the URL already sets it.
+#[must_use]
+pub fn with_capacity(url: &str, capacity: usize) -> String {
+    let query = url.split_once('?').map(|(_, query)| query);
+    if query.is_some_and(|query| query.split('&').any(|pair| pair.starts_with(PARAM))) {
+        return url.to_string();
+    }
+    let separator = if query.is_some() { '&' } else { '?' };
+    format!("{url}{separator}{PARAM}{capacity}")
+}
+
+/// Returns the `statement-cache-capacity` parameter of `url`, if it sets one.
+#[must_use]
+pub fn capacity(url: &str) -> Option<usize> {
+    let (_, query) = url.split_once('?')?;
+    query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix(PARAM)?.parse().ok())
+}
+
+/// How the statement of an execution was found on its connection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CacheOutcome {
+    /// Already prepared.
+    Hit,
+    /// Prepared for this execution.
+    Miss,
+    /// Ran on a full cache, where a miss can't be told apart from a hit.
+    Unknown,
+}
+
+impl CacheOutcome {
+    /// Classifies an execution from the size of the connection's statement cache
+    /// before and after it, holding up to `capacity` statements.
+    #[must_use]
+    pub fn observe(before: usize, after: usize, capacity: usize) -> Self {
+        if after > before || capacity == 0 {
+            Self::Miss
+        } else if before < capacity {
+            Self::Hit
+        } else {
+            Self::Unknown
+        }
+    }
+}
+
+/// Statement cache hits and misses of a prepared query, or of a whole driver.
+#[derive(Debug, Default)]
+pub struct StatementStats {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    unknown: AtomicU64,
+    closed: AtomicBool,
+}
+
+impl StatementStats {
+    /// Records one execution.
+    pub fn record(&self, outcome: CacheOutcome) {
+        let counter = match outcome {
+            CacheOutcome::Hit => &self.hits,
+            CacheOutcome::Miss => &self.misses,
+            CacheOutcome::Unknown => &self.unknown,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Executions whose statement was already prepared on the connection.
+    #[must_use]
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Executions whose statement had to be prepared.
+    #[must_use]
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Executions on a full cache, which can't be classified.
+    #[must_use]
+    pub fn unknown(&self) -> u64 {
+        self.unknown.load(Ordering::Relaxed)
+    }
+
+    /// Returns `{hits, misses, unknown}` for PHP.
+    #[must_use]
+    pub fn to_map(&self) -> BTreeMap<String, u64> {
+        BTreeMap::from([
+            ("hits".to_string(), self.hits()),
+            ("misses".to_string(), self.misses()),
+            ("unknown".to_string(), self.unknown()),
+        ])
+    }
+
+    /// Marks the prepared query closed.
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true once the prepared query is closed.
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+
+    /// Returns `{hits, misses, unknown}`.
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "hits": self.hits(),
+            "misses": self.misses(),
+            "unknown": self.unknown(),
+        })
+    }
+}
+
+/// Tracks the prepared query being executed, if any, and the driver's totals.
+#[derive(Debug, Default)]
+pub struct PreparedStatements {
+    active: Mutex<Option<Arc<StatementStats>>>,
+    /// Hits and misses of every prepared query of the driver.
+    pub totals: StatementStats,
+}
+
+impl PreparedStatements {
+    /// Makes `stats` the active prepared query until the returned guard is dropped.
+    #[must_use]
+    pub fn scope(&self, stats: Arc<StatementStats>) -> PreparedScope<'_> {
+        let previous = self.slot().replace(stats);
+        PreparedScope {
+            owner: self,
+            previous,
+        }
+    }
+
+    /// Returns true while a prepared query is executing.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.slot().is_some()
+    }
+
+    /// Records an execution of the active prepared query, if any.
+    pub fn record(&self, outcome: CacheOutcome) {
+        if let Some(stats) = self.slot().as_ref() {
+            stats.record(outcome);
+            self.totals.record(outcome);
+        }
+    }
+
+    fn slot(&self) -> std::sync::MutexGuard<'_, Option<Arc<StatementStats>>> {
+        self.active.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Guard returned by [`PreparedStatements::scope`]; restores the previous
+/// prepared query on drop.
+pub struct PreparedScope<'a> {
+    owner: &'a PreparedStatements,
+    previous: Option<Arc<StatementStats>>,
+}
+
+impl Drop for PreparedScope<'_> {
+    fn drop(&mut self) {
+        *self.owner.slot() = self.previous.take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_only_inside_scope() {
+        let statements = PreparedStatements::default();
+        let stats = Arc::new(StatementStats::default());
+
+        statements.record(CacheOutcome::Miss);
+        assert!(!statements.is_active());
+        {
+            let _scope = statements.scope(stats.clone());
+            assert!(statements.is_active());
+            statements.record(CacheOutcome::Miss);
+            statements.record(CacheOutcome::Hit);
+            statements.record(CacheOutcome::Hit);
+        }
+        assert!(!statements.is_active());
+        statements.record(CacheOutcome::Hit);
+
+        assert_eq!((stats.hits(), stats.misses()), (2, 1));
+        assert_eq!(
+            (statements.totals.hits(), statements.totals.misses()),
+            (2, 1)
+        );
+    }
+
+    #[test]
+    fn test_nested_scopes_restore_previous() {
+        let statements = PreparedStatements::default();
+        let outer = Arc::new(StatementStats::default());
+        let inner = Arc::new(StatementStats::default());
+
+        let _outer = statements.scope(outer.clone());
+        {
+            let _inner = statements.scope(inner.clone());
+            statements.record(CacheOutcome::Miss);
+        }
+        statements.record(CacheOutcome::Hit);
+
+        assert_eq!((inner.hits(), inner.misses()), (0, 1));
+        assert_eq!((outer.hits(), outer.misses()), (1, 0));
+    }
+
+    #[test]
+    fn test_with_capacity() {
+        assert_eq!(
+            with_capacity("postgres://localhost/db", 0),
+            "postgres://localhost/db?statement-cache-capacity=0"
+        );
+        assert_eq!(
+            with_capacity("mysql://localhost/db?ssl-mode=disabled", 50),
+            "mysql://localhost/db?ssl-mode=disabled&statement-cache-capacity=50"
+        );
+        assert_eq!(
+            with_capacity("postgres://localhost/db?statement-cache-capacity=10", 50),
+            "postgres://localhost/db?statement-cache-capacity=10"
+        );
+    }
+
+    #[test]
+    fn test_close() {
+        let stats = StatementStats::default();
+        assert!(!stats.is_closed());
+        stats.close();
+        assert!(stats.is_closed());
+        assert_eq!(
+            stats.to_json(),
+            serde_json::json!({"hits": 0, "misses": 0, "unknown": 0})
+        );
+    }
+
+    #[test]
+    fn test_capacity() {
+        assert_eq!(capacity("postgres://localhost/db"), None);
+        assert_eq!(
+            capacity("mysql://localhost/db?ssl-mode=disabled&statement-cache-capacity=50"),
+            Some(50)
+        );
+        assert_eq!(
+            capacity(&with_capacity("postgres://localhost/db", 0)),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_observe() {
+        assert_eq!(CacheOutcome::observe(3, 4, 100), CacheOutcome::Miss);
+        assert_eq!(CacheOutcome::observe(3, 3, 100), CacheOutcome::Hit);
+        // A miss on a full cache evicts a statement: the size doesn't change
+        assert_eq!(CacheOutcome::observe(100, 100, 100), CacheOutcome::Unknown);
+        assert_eq!(CacheOutcome::observe(0, 0, 0), CacheOutcome::Miss);
+    }
+}
@@ -0,0 +1,233 @@
+//! Session time zone (`OPT_TIMEZONE`).
+//!
+//! PHP `DateTimeInterface` parameters keep their UTC offset: `PostgreSQL` binds
+//! them as `timestamptz` and SQL Server as `DATETIMEOFFSET`, so the instant is
+//! stored whatever the session time zone. `MySQL` has no offset-aware type; its
+//! values are converted to the wall-clock time of `OPT_TIMEZONE`, or keep their
+//! own wall-clock time when the option is not set.
+//!
+//! `OPT_TIMEZONE` also sets the session time zone of each connection (`SET TIME
+//! ZONE` on `PostgreSQL`, `SET time_zone` on `MySQL`; SQL Server has none), and
+//! `TIMESTAMPTZ` and `DATETIMEOFFSET` results are rendered in it with their
+//! offset, e.g. `2024-01-01 10:00:00+01:00`.
+//!
+//! # PHP Usage
+//!
+//! ```php
+//! $driver = Sqlx\DriverFactory::make([
+//!     Sqlx\DriverOptions::OPT_URL => 'postgres://localhost/app',
+//!     Sqlx\DriverOptions::OPT_TIMEZONE => 'Europe/Berlin',
+//! ]);
+//! $driver->execute('INSERT INTO events (at) VALUES (?)', [new DateTimeImmutable('now')]);
+//! $driver->queryValue('SELECT at FROM events');
+//! // 2024-06-15 14:30:45.123456+02:00
+//! ```
+
+use chrono::{DateTime, FixedOffset, NaiveDateTime, Offset, TimeZone as _, Utc};
+use chrono_tz::Tz;
+
+/// Format of offset-aware date-times rendered to PHP, e.g. `2024-01-01 10:00:00.5+01:00`.
+pub const OFFSET_DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f%:z";
+
+/// A time zone given to `OPT_TIMEZONE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeZone {
+    /// A fixed offset from UTC, such as `+02:00`; `UTC` is `+00:00`.
+    Fixed(FixedOffset),
+    /// An IANA time zone, such as `Europe/Berlin`, following its daylight saving rules.
+    Named(Tz),
+}
+
+impl TimeZone {
+    /// Parses an `OPT_TIMEZONE` value: `UTC`, an offset (`+02:00`, `-0530`, `+02`)
+    /// or an IANA name (`Europe/Berlin`).
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if value.eq_ignore_ascii_case("utc") || value == "Z" {
+            return FixedOffset::east_opt(0).map(Self::Fixed);
+        }
+        if let Some(digits) = value.strip_prefix(['+', '-']) {
+            let digits = digits.replace(':', "");
+            if !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+                return None;
+            }
+            let (hours, minutes) = match digits.len() {
+                2 => (digits.parse::<i32>().ok()?, 0),
+                4 => (
+                    digits[..2].parse::<i32>().ok()?,
+                    digits[2..].parse::<i32>().ok()?,
+                ),
+                _ => return None,
+            };
+            if hours > 15 || minutes > 59 {
+                return None;
+            }
+            let seconds = (hours * 60 + minutes) * 60;
+            let seconds = if value.starts_with('-') {
+                -seconds
+            } else {
+                seconds
+            };
+            return FixedOffset::east_opt(seconds).map(Self::Fixed);
+        }
+        value.parse::<Tz>().ok().map(Self::Named)
+    }
+
+    /// Returns the IANA name, or the offset as `+HH:MM`.
+    #[must_use]
+    pub fn name(&self) -> String {
+        match self {
+            Self::Fixed(offset) => offset.to_string(),
+            Self::Named(tz) => tz.name().to_string(),
+        }
+    }
+
+    /// Returns `instant` in this time zone.
+    #[must_use]
+    pub fn convert(&self, instant: &DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            Self::Fixed(offset) => instant.with_timezone(offset),
+            Self::Named(tz) => {
+                let local = instant.with_timezone(tz);
+                local.with_timezone(&local.offset().fix())
+            }
+        }
+    }
+
+    /// Returns the instant the wall-clock time `local` denotes in this time zone;
+    /// the earlier one when it is ambiguous, `None` when it is skipped.
+    #[must_use]
+    pub fn localize(&self, local: &NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+        match self {
+            Self::Fixed(offset) => offset.from_local_datetime(local).earliest(),
+            Self::Named(tz) => tz
+                .from_local_datetime(local)
+                .earliest()
+                .map(|local| local.with_timezone(&local.offset().fix())),
+        }
+    }
+
+    /// Returns the wall-clock time of `datetime` in this time zone.
+    #[must_use]
+    pub fn wall_clock(&self, datetime: &DateTime<FixedOffset>) -> NaiveDateTime {
+        self.convert(&datetime.with_timezone(&Utc)).naive_local()
+    }
+}
+
+/// Returns the wall-clock time `datetime` is bound as on databases without an
+/// offset-aware type: in `time_zone` if set, otherwise its own.
+#[must_use]
+pub fn session_wall_clock(
+    datetime: &DateTime<FixedOffset>,
+    time_zone: Option<TimeZone>,
+) -> NaiveDateTime {
+    time_zone.map_or_else(
+        || datetime.naive_local(),
+        |time_zone| time_zone.wall_clock(datetime),
+    )
+}
+
+/// Renders a `TIMESTAMPTZ` or `DATETIMEOFFSET` result: in `time_zone` with its
+/// offset when `OPT_TIMEZONE` is set, otherwise in UTC (`2024-01-01 09:00:00 UTC`).
+#[must_use]
+pub fn render_instant(instant: &DateTime<Utc>, time_zone: Option<TimeZone>) -> String {
+    match time_zone {
+        Some(time_zone) => time_zone
+            .convert(instant)
+            .format(OFFSET_DATETIME_FORMAT)
+            .to_string(),
+        None => instant.to_string(),
+    }
+}
+
+/// Renders a `MySQL` `TIMESTAMP` result, which the server sends as the wall-clock
+/// time of the session time zone: with its offset when `OPT_TIMEZONE` is set,
+/// otherwise unchanged (`2024-01-01 09:00:00 UTC`).
+#[must_use]
+pub fn render_session_timestamp(wall_clock: &DateTime<Utc>, time_zone: Option<TimeZone>) -> String {
+    time_zone
+        .and_then(|time_zone| time_zone.localize(&wall_clock.naive_utc()))
+        .map_or_else(
+            || wall_clock.to_string(),
+            |local| local.format(OFFSET_DATETIME_FORMAT).to_string(),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let fixed = |seconds| Some(TimeZone::Fixed(FixedOffset::east_opt(seconds).unwrap()));
+        assert_eq!(TimeZone::parse("UTC"), fixed(0));
+        assert_eq!(TimeZone::parse("+02:00"), fixed(7200));
+        assert_eq!(TimeZone::parse("-0530"), fixed(-19800));
+        assert_eq!(TimeZone::parse("+09"), fixed(32400));
+        assert_eq!(
+            TimeZone::parse("Europe/Berlin"),
+            Some(TimeZone::Named(Tz::Europe__Berlin))
+        );
+        assert_eq!(TimeZone::parse("+2"), None);
+        assert_eq!(TimeZone::parse("+25:00"), None);
+        assert_eq!(TimeZone::parse("Mars/Olympus"), None);
+        assert_eq!(TimeZone::parse("'; DROP TABLE x; --"), None);
+    }
+
+    #[test]
+    fn test_name() {
+        assert_eq!(TimeZone::parse("-05:30").unwrap().name(), "-05:30");
+        assert_eq!(TimeZone::parse("utc").unwrap().name(), "+00:00");
+        assert_eq!(
+            TimeZone::parse("Europe/Berlin").unwrap().name(),
+            "Europe/Berlin"
+        );
+    }
+
+    #[test]
+    fn test_render_instant() {
+        let winter = Utc.with_ymd_and_hms(2024, 1, 1, 9, 0, 0).unwrap();
+        let summer = Utc.with_ymd_and_hms(2024, 7, 1, 9, 0, 0).unwrap();
+        let berlin = TimeZone::parse("Europe/Berlin");
+        assert_eq!(render_instant(&winter, None), "2024-01-01 09:00:00 UTC");
+        assert_eq!(render_instant(&winter, berlin), "2024-01-01 10:00:00+01:00");
+        assert_eq!(render_instant(&summer, berlin), "2024-07-01 11:00:00+02:00");
+        assert_eq!(
+            render_instant(&winter, TimeZone::parse("-03:00")),
+            "2024-01-01 06:00:00-03:00"
+        );
+    }
+
+    #[test]
+    fn test_render_session_timestamp() {
+        let wall_clock = Utc.with_ymd_and_hms(2024, 7, 1, 11, 0, 0).unwrap();
+        let berlin = TimeZone::parse("Europe/Berlin");
+        assert_eq!(
+            render_session_timestamp(&wall_clock, None),
+            "2024-07-01 11:00:00 UTC"
+        );
+        assert_eq!(
+            render_session_timestamp(&wall_clock, berlin),
+            "2024-07-01 11:00:00+02:00"
+        );
+        let skipped = Utc.with_ymd_and_hms(2024, 3, 31, 2, 30, 0).unwrap();
+        assert_eq!(
+            render_session_timestamp(&skipped, berlin),
+            "2024-03-31 02:30:00 UTC"
+        );
+    }
+
+    #[test]
+    fn test_wall_clock() {
+        let datetime = DateTime::parse_from_rfc3339("2024-01-01T10:00:00+01:00").unwrap();
+        let wall_clock = |zone| {
+            TimeZone::parse(zone)
+                .unwrap()
+                .wall_clock(&datetime)
+                .to_string()
+        };
+        assert_eq!(wall_clock("UTC"), "2024-01-01 09:00:00");
+        assert_eq!(wall_clock("America/New_York"), "2024-01-01 04:00:00");
+    }
+}
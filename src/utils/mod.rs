@@ -5,11 +5,13 @@
 //! - [`ident`]: SQL identifier validation
 //! - [`hashmap_fold`]: Helpers for folding query results into PHP hash maps
 //! - [`indent_sql`]: SQL formatting with indentation
+//! - [`redact_url`]: Credential masking for database URLs
 //! - [`strip_prefix`]: Case-insensitive prefix stripping for SQL parsing
 //! - [`types`]: Common type definitions
 
 pub mod hashmap_fold;
 pub mod ident;
 pub mod indent_sql;
+pub mod redact_url;
 pub mod strip_prefix;
 pub mod types;
@@ -405,6 +405,96 @@ abstract class AbstractDriverTest extends TestCase
         }
     }
 
+    public function testPreparedQueryStatementCache(): void
+    {
+        if (str_starts_with($this->getConnectionUrl(), 'mssql')) {
+            $this->markTestSkipped('MSSQL has no statement cache');
+        }
+
+        $prepared = $this->driver->prepare('SELECT ? AS n');
+        $this->driver->withConnection(function () use ($prepared) {
+            for ($i = 1; $i <= 3; $i++) {
+                $this->assertEquals($i, $prepared->queryValue([$i]));
+            }
+        });
+
+        $this->assertSame(['hits' => 2, 'misses' => 1, 'unknown' => 0], $prepared->statementCacheStats());
+        $this->assertSame(['hits' => 2, 'misses' => 1, 'unknown' => 0], $this->driver->statementCacheStats());
+
+        $this->assertFalse($prepared->isClosed());
+        $prepared->close();
+        $this->assertTrue($prepared->isClosed());
+
+        $this->expectException(\Sqlx\Exceptions\SqlxException::class);
+        $prepared->queryValue([1]);
+    }
+
+    public function testPreparedQueryStatementCacheFull(): void
+    {
+        if (str_starts_with($this->getConnectionUrl(), 'mssql')) {
+            $this->markTestSkipped('MSSQL has no statement cache');
+        }
+
+        $driver = DriverFactory::make([
+            \Sqlx\DriverOptions::OPT_URL => $this->getConnectionUrl(),
+            \Sqlx\DriverOptions::OPT_STATEMENT_CACHE_CAPACITY => 1,
+        ]);
+
+        try {
+            $first = $driver->prepare('SELECT ? AS n');
+            $second = $driver->prepare('SELECT ? AS m');
+            $driver->withConnection(function () use ($first, $second) {
+                $first->queryValue([1]);
+                // Evicts the first statement, which is prepared again below
+                $second->queryValue([2]);
+                $first->queryValue([3]);
+            });
+
+            // Misses on a full cache don't change its size: they are not counted as hits
+            $this->assertSame(['hits' => 0, 'misses' => 1, 'unknown' => 1], $first->statementCacheStats());
+            $this->assertSame(['hits' => 0, 'misses' => 0, 'unknown' => 1], $second->statementCacheStats());
+        } finally {
+            $driver->close();
+        }
+    }
+
+    public function testPreparedQueryExecuteMany(): void
+    {
+        $this->createTestTable();
+
+        try {
+            $insert = $this->driver->prepare('INSERT INTO test_users (name, email) VALUES (?, ?)');
+            $this->assertSame(0, $insert->executeMany([]));
+            $this->assertSame(2, $insert->executeMany([
+                ['Alice', 'alice@example.com'],
+                ['Bob', 'bob@example.com'],
+            ]));
+            if (!str_starts_with($this->getConnectionUrl(), 'mssql')) {
+                // Prepared by the first set, reused by the second
+                $this->assertSame(['hits' => 1, 'misses' => 1, 'unknown' => 0], $insert->statementCacheStats());
+            }
+            $this->assertSame(1, $insert->executeMany((function () {
+                yield ['Carol', 'carol@example.com'];
+            })()));
+            $this->assertEquals(3, $this->driver->queryValue('SELECT count(*) FROM test_users'));
+
+            try {
+                $insert->executeMany([
+                    ['Dave', 'dave@example.com'],
+                    ['Eve', null],
+                    ['Frank', 'frank@example.com'],
+                ]);
+                $this->fail('Expected a QueryException');
+            } catch (\Sqlx\Exceptions\QueryException $e) {
+                $this->assertSame(1, $e->getIndex());
+            }
+            // The failed call ran in its own transaction and was rolled back
+            $this->assertEquals(3, $this->driver->queryValue('SELECT count(*) FROM test_users'));
+        } finally {
+            $this->dropTestTable();
+        }
+    }
+
     // =========================================================================
     // Identifier (validated identifier value type)
     // =========================================================================
@@ -555,6 +645,30 @@ abstract class AbstractDriverTest extends TestCase
         }
     }
 
+    public function testStreamParameterIsRejected(): void
+    {
+        $this->createTestTable();
+
+        try {
+            $stream = fopen('php://memory', 'w+b');
+            fwrite($stream, 'Alice');
+            rewind($stream);
+            try {
+                $this->driver->execute('INSERT INTO test_users (name, email) VALUES (?, ?)', [$stream, 'a@example.com']);
+                $this->fail('A stream parameter was accepted');
+            } catch (\Sqlx\Exceptions\SqlxException $e) {
+                $messages = [];
+                for ($error = $e; $error !== null; $error = $error->getPrevious()) {
+                    $messages[] = $error->getMessage();
+                }
+                $this->assertStringContainsString('rather than a stream', implode("\n", $messages));
+            }
+            $this->assertSame(0, $this->driver->queryValue('SELECT COUNT(*) FROM test_users'));
+        } finally {
+            $this->dropTestTable();
+        }
+    }
+
     public function testTypedPlaceholders(): void
     {
         $this->createTestTable();
@@ -731,55 +845,1163 @@ abstract class AbstractDriverTest extends TestCase
     }
 
     // =========================================================================
-    // Query Hook Tests
+    // Explain Tests
     // =========================================================================
 
-    public function testQueryHook(): void
+    public function testExplainReturnsPlanAndSummary(): void
     {
         $this->createTestTable();
 
         try {
-            $queries = [];
-            $this->driver->onQuery(function (string $sql, string $sqlInline, float $durationMs) use (&$queries) {
-                $queries[] = [
-                    'sql' => $sql,
-                    'sqlInline' => $sqlInline,
-                    'durationMs' => $durationMs,
-                ];
-            });
+            $plan = $this->driver->explain('SELECT * FROM test_users WHERE name = :name', ['name' => 'Alice']);
+
+            $this->assertArrayHasKey('plan', $plan);
+            $this->assertNotEmpty($plan['plan']);
+            $this->assertArrayHasKey('total_cost', $plan['summary']);
+            $this->assertArrayHasKey('estimated_rows', $plan['summary']);
+            $this->assertIsBool($plan['summary']['seq_scan']);
+            $this->assertIsArray($plan['summary']['seq_scan_tables']);
+        } finally {
+            $this->dropTestTable();
+        }
+    }
 
+    public function testExplainAnalyzeRollsBackWrites(): void
+    {
+        $this->createTestTable();
+
+        try {
             $this->driver->execute("INSERT INTO test_users (name, email) VALUES ('Alice', 'alice@example.com')");
-            $this->driver->queryAll('SELECT * FROM test_users');
 
-            $this->driver->onQuery(null); // Disable hook
+            $this->driver->explain('DELETE FROM test_users', null, ['analyze' => true]);
 
-            $this->assertCount(2, $queries);
-            $this->assertGreaterThan(0, $queries[0]['durationMs']);
-            $this->assertGreaterThan(0, $queries[1]['durationMs']);
+            $this->assertEquals(1, $this->driver->queryValue('SELECT COUNT(*) FROM test_users'));
+        } finally {
+            $this->dropTestTable();
+        }
+    }
+
+    public function testExplainRejectsUnknownOption(): void
+    {
+        $this->expectException(SqlxException::class);
+        $this->driver->explain('SELECT 1', null, ['verbose' => true]);
+    }
+
+    public function testBuilderExplain(): void
+    {
+        $this->createTestTable();
+
+        try {
+            $plan = $this->driver->readBuilder()
+                ->select('*')
+                ->from('test_users')
+                ->explain();
+
+            $this->assertArrayHasKey('summary', $plan);
         } finally {
             $this->dropTestTable();
         }
     }
 
     // =========================================================================
-    // Connection Tagging Tests
+    // Cost Guard Tests
     // =========================================================================
 
-    public function testSetApplicationName(): void
+    public function testCostGuardThrowsOnSequentialScan(): void
     {
-        // Just verify it doesn't throw
-        $this->driver->setApplicationName('test-application');
-        $this->assertTrue(true);
+        $this->createTestTable();
+        $driver = DriverFactory::make([
+            \Sqlx\DriverOptions::OPT_URL => $this->getConnectionUrl(),
+            \Sqlx\DriverOptions::OPT_COST_GUARD => ['seq_scan_min_rows' => 0, 'action' => 'throw'],
+        ]);
+
+        try {
+            $this->expectException(\Sqlx\Exceptions\NotPermittedException::class);
+            $this->expectExceptionMessage('cost guard');
+            $driver->queryAll('SELECT * FROM test_users');
+        } finally {
+            $driver->close();
+            $this->dropTestTable();
+        }
     }
 
-    public function testSetClientInfo(): void
+    public function testCostGuardWarnsThroughQueryHook(): void
     {
-        // Just verify it doesn't throw
-        $this->driver->setClientInfo('test-app', [
-            'request_id' => 'abc123',
-            'user_id' => 42,
+        $this->createTestTable();
+        $driver = DriverFactory::make([
+            \Sqlx\DriverOptions::OPT_URL => $this->getConnectionUrl(),
+            \Sqlx\DriverOptions::OPT_COST_GUARD => ['seq_scan_min_rows' => 0],
         ]);
-        $this->assertTrue(true);
+        $warnings = [];
+        $driver->onQuery(function ($sql, $sqlInline, $durationMs, $rows, $error, $meta, $queryWarnings) use (&$warnings) {
+            $warnings[] = $queryWarnings;
+        });
+
+        try {
+            $driver->queryAll('SELECT * FROM test_users');
+            $driver->queryAll('SELECT * FROM test_users');
+            $driver->queryValue('SELECT 1');
+
+            $this->assertCount(3, $warnings);
+            $this->assertStringContainsString('test_users', $warnings[0][0]);
+            $this->assertSame($warnings[0], $warnings[1]);
+            $this->assertSame([], $warnings[2]);
+        } finally {
+            $driver->close();
+            $this->dropTestTable();
+        }
+    }
+
+    public function testCostGuardRejectsEmptyConfig(): void
+    {
+        $this->expectException(\Sqlx\Exceptions\ConfigurationException::class);
+        DriverFactory::make([
+            \Sqlx\DriverOptions::OPT_URL => $this->getConnectionUrl(),
+            \Sqlx\DriverOptions::OPT_COST_GUARD => ['action' => 'throw'],
+        ]);
+    }
+
+    // =========================================================================
+    // Query Profiler Tests
+    // =========================================================================
+
+    public function testProfileAggregatesByFingerprint(): void
+    {
+        $this->createTestTable();
+        $profile = $this->driver->profile();
+
+        try {
+            foreach ([1, 2, 1, 1] as $id) {
+                $this->driver->queryMaybeRow('SELECT name FROM test_users WHERE id = :id', ['id' => $id]);
+            }
+            $this->driver->queryAll('SELECT * FROM test_users WHERE id IN :ids', ['ids' => [1, 2, 3]]);
+            $this->driver->queryAll('SELECT * FROM test_users WHERE id IN :ids', ['ids' => [4]]);
+
+            $report = $profile->stop();
+            $this->assertFalse($profile->isActive());
+            $this->assertSame(6, $report['queries']);
+            $this->assertSame(2, $report['distinct']);
+            $this->assertSame(2, $report['duplicates']);
+
+            [$lookup, $inList] = $report['fingerprints'];
+            $this->assertSame('select name from test_users where id = ?', $lookup['sql']);
+            $this->assertSame(4, $lookup['count']);
+            $this->assertSame(2, $lookup['duplicates']);
+            $this->assertSame(2, $inList['count']);
+            $this->assertSame(16, strlen($lookup['fingerprint']));
+
+            $this->driver->queryValue('SELECT 1');
+            $this->assertSame(6, $profile->report()['queries']);
+        } finally {
+            $profile->stop();
+            $this->dropTestTable();
+        }
+    }
+
+    public function testProfileWarnsThroughQueryHook(): void
+    {
+        $this->createTestTable();
+        $warnings = [];
+        $this->driver->onQuery(function ($sql, $sqlInline, $durationMs, $rows, $error, $meta, $queryWarnings) use (&$warnings) {
+            $warnings = array_merge($warnings, $queryWarnings);
+        });
+        $profile = $this->driver->profile(2);
+
+        try {
+            for ($i = 0; $i < 5; $i++) {
+                $this->driver->queryMaybeRow('SELECT name FROM test_users WHERE id = :id', ['id' => $i]);
+            }
+
+            $this->assertCount(1, $warnings);
+            $this->assertStringContainsString('possible N+1', $warnings[0]);
+            $this->assertSame($warnings, $profile->stop()['warnings']);
+        } finally {
+            $profile->stop();
+            $this->driver->onQuery(null);
+            $this->dropTestTable();
+        }
+    }
+
+    // =========================================================================
+    // Query Allowlist Tests
+    // =========================================================================
+
+    public function testQueryAllowlistLearnsThenEnforces(): void
+    {
+        $this->createTestTable();
+        $path = tempnam(sys_get_temp_dir(), 'sqlx-allowlist');
+        unlink($path);
+
+        try {
+            $learner = DriverFactory::make([
+                \Sqlx\DriverOptions::OPT_URL => $this->getConnectionUrl(),
+                \Sqlx\DriverOptions::OPT_QUERY_ALLOWLIST => $path,
+                \Sqlx\DriverOptions::OPT_QUERY_ALLOWLIST_MODE => 'learn',
+            ]);
+            $learner->queryMaybeRow('SELECT name FROM test_users WHERE id = :id', ['id' => 1]);
+            $learner->queryMaybeRow('SELECT name FROM test_users WHERE id = :id', ['id' => 2]);
+            $learner->close();
+
+            $lines = file($path, FILE_IGNORE_NEW_LINES);
+            $this->assertCount(1, $lines);
+            $this->assertStringEndsWith(' select name from test_users where id = ?', $lines[0]);
+
+            $enforcer = DriverFactory::make([
+                \Sqlx\DriverOptions::OPT_URL => $this->getConnectionUrl(),
+                \Sqlx\DriverOptions::OPT_QUERY_ALLOWLIST => $path,
+            ]);
+            try {
+                $enforcer->queryMaybeRow("SELECT name\nFROM test_users\nWHERE id = :id", ['id' => 3]);
+
+                $this->expectException(\Sqlx\Exceptions\NotPermittedException::class);
+                $this->expectExceptionMessage('not in allowlist');
+                $enforcer->queryAll('SELECT email FROM test_users');
+            } finally {
+                $enforcer->close();
+            }
+        } finally {
+            @unlink($path);
+            $this->dropTestTable();
+        }
+    }
+
+    public function testQueryAllowlistRefusesInlineLiterals(): void
+    {
+        $this->createTestTable();
+        $path = tempnam(sys_get_temp_dir(), 'sqlx-allowlist');
+        $driver = DriverFactory::make([
+            \Sqlx\DriverOptions::OPT_URL => $this->getConnectionUrl(),
+            \Sqlx\DriverOptions::OPT_QUERY_ALLOWLIST => $path,
+            \Sqlx\DriverOptions::OPT_QUERY_ALLOWLIST_MODE => 'learn',
+        ]);
+        $warnings = [];
+        $driver->onQuery(function ($sql, $sqlInline, $durationMs, $rows, $error, $meta, $queryWarnings) use (&$warnings) {
+            $warnings = array_merge($warnings, $queryWarnings);
+        });
+
+        try {
+            $driver->queryMaybeRow("SELECT name FROM test_users WHERE email = 'alice@example.com'");
+
+            $this->assertCount(1, $warnings);
+            $this->assertStringContainsString('inline literal', $warnings[0]);
+            $this->assertStringNotContainsString('alice@example.com', $warnings[0]);
+            $this->assertSame('', file_get_contents($path));
+
+            // Numbers are part of the shape, not a bypass
+            $driver->queryValue('SELECT COALESCE(MAX(id), 0) FROM test_users');
+            $this->assertCount(1, $warnings);
+            $this->assertCount(1, file($path, FILE_IGNORE_NEW_LINES));
+        } finally {
+            $driver->close();
+            unlink($path);
+            $this->dropTestTable();
+        }
+    }
+
+    public function testQueryAllowlistRequiresFileInEnforceMode(): void
+    {
+        $this->expectException(\Sqlx\Exceptions\ConfigurationException::class);
+        DriverFactory::make([
+            \Sqlx\DriverOptions::OPT_URL => $this->getConnectionUrl(),
+            \Sqlx\DriverOptions::OPT_QUERY_ALLOWLIST => sys_get_temp_dir() . '/sqlx-missing-allowlist',
+        ]);
+    }
+
+    // =========================================================================
+    // Secret Parameter Tests
+    // =========================================================================
+
+    public function testSecretIsBoundButRedactedInline(): void
+    {
+        $this->createTestTable();
+
+        try {
+            $inline = [];
+            $this->driver->onQuery(function (string $sql, string $sqlInline) use (&$inline) {
+                $inline[] = $sqlInline;
+            });
+
+            $this->driver->execute(
+                'INSERT INTO test_users (name, email) VALUES (:name, :email)',
+                ['name' => 'Alice', 'email' => new \Sqlx\Secret('alice@example.com')]
+            );
+            $name = $this->driver->queryValue(
+                'SELECT name FROM test_users WHERE email = ?',
+                [new \Sqlx\Secret('alice@example.com')]
+            );
+
+            $this->assertSame('Alice', $name);
+            $this->assertCount(2, $inline);
+            foreach ($inline as $sql) {
+                $this->assertStringContainsString("'***'", $sql);
+                $this->assertStringNotContainsString('alice@example.com', $sql);
+            }
+            $this->assertStringNotContainsString(
+                'alice@example.com',
+                $this->driver->dryInline('SELECT * FROM test_users WHERE email IN (?)', [
+                    new \Sqlx\Secret(['alice@example.com', 'bob@example.com']),
+                ])
+            );
+            $this->assertSame('alice@example.com', (new \Sqlx\Secret('alice@example.com'))->reveal());
+        } finally {
+            $this->driver->onQuery(null);
+            $this->dropTestTable();
+        }
+    }
+
+    public function testRedactParamsOption(): void
+    {
+        $driver = DriverFactory::make([
+            \Sqlx\DriverOptions::OPT_URL => $this->getConnectionUrl(),
+            \Sqlx\DriverOptions::OPT_REDACT_PARAMS => ['password'],
+        ]);
+
+        try {
+            $sql = $driver->dryInline(
+                'SELECT * FROM test_users WHERE name = :name AND email = :Password',
+                ['name' => 'Alice', 'Password' => 'hunter2']
+            );
+            $this->assertStringContainsString("'Alice'", $sql);
+            $this->assertStringContainsString("'***'", $sql);
+            $this->assertStringNotContainsString('hunter2', $sql);
+        } finally {
+            $driver->close();
+        }
+    }
+
+    // =========================================================================
+    // Query Exception Context Tests
+    // =========================================================================
+
+    public function testQueryExceptionCarriesContext(): void
+    {
+        try {
+            $this->driver->queryAll(
+                'SELECT * FROM nonexistent_table WHERE id = :id AND secret_hash = :password',
+                ['id' => 1, 'password' => 'hunter2']
+            );
+            $this->fail('Expected QueryException');
+        } catch (\Sqlx\Exceptions\QueryException $e) {
+            $this->assertSame(\Sqlx\Exceptions\SqlxException::QUERY, $e->getCode());
+            $this->assertStringContainsString('nonexistent_table', $e->getSql());
+            $this->assertSame(['id' => 1, 'password' => '***'], $e->getParameters());
+            $this->assertContains($e->getDriverName(), ['postgres', 'mysql', 'mssql']);
+            $this->assertSame(1, $e->getAttempt());
+        }
+    }
+
+    // =========================================================================
+    // Retry Policy Tests
+    // =========================================================================
+
+    public function testRetryOnCodesWithHookAndJitter(): void
+    {
+        if (str_starts_with($this->getConnectionUrl(), 'mssql')) {
+            $this->markTestSkipped('MSSQL errors carry no SQLSTATE');
+        }
+
+        $driver = DriverFactory::make([
+            \Sqlx\DriverOptions::OPT_URL => $this->getConnectionUrl(),
+            \Sqlx\DriverOptions::OPT_RETRY_MAX_ATTEMPTS => 2,
+            \Sqlx\DriverOptions::OPT_RETRY_INITIAL_BACKOFF => '10ms',
+            \Sqlx\DriverOptions::OPT_RETRY_JITTER => 'full',
+            // Undefined table: PostgreSQL and MySQL
+            \Sqlx\DriverOptions::OPT_RETRY_ON => ['42P01', '42S02'],
+        ]);
+
+        try {
+            $retries = [];
+            $driver->onRetry(function (int $attempt, float $delayMs, string $error) use (&$retries) {
+                $retries[] = [$attempt, $delayMs, $error];
+            });
+
+            try {
+                $driver->queryAll('SELECT * FROM nonexistent_table');
+                $this->fail('Expected QueryException');
+            } catch (\Sqlx\Exceptions\QueryException $e) {
+                $this->assertSame(3, $e->getAttempt());
+            }
+
+            $this->assertSame([1, 2], array_column($retries, 0));
+            foreach ($retries as [, $delayMs, $error]) {
+                $this->assertGreaterThanOrEqual(0.0, $delayMs);
+                $this->assertLessThanOrEqual(20.0, $delayMs);
+                $this->assertStringContainsString('nonexistent_table', $error);
+            }
+        } finally {
+            $driver->close();
+        }
+    }
+
+    public function testRetryBudgetStopsRetries(): void
+    {
+        if (str_starts_with($this->getConnectionUrl(), 'mssql')) {
+            $this->markTestSkipped('MSSQL errors carry no SQLSTATE');
+        }
+
+        $driver = DriverFactory::make([
+            \Sqlx\DriverOptions::OPT_URL => $this->getConnectionUrl(),
+            \Sqlx\DriverOptions::OPT_RETRY_MAX_ATTEMPTS => 5,
+            \Sqlx\DriverOptions::OPT_RETRY_INITIAL_BACKOFF => '1s',
+            \Sqlx\DriverOptions::OPT_RETRY_BUDGET => '500ms',
+            \Sqlx\DriverOptions::OPT_RETRY_ON => ['42P01', '42S02'],
+        ]);
+
+        try {
+            $driver->queryAll('SELECT * FROM nonexistent_table');
+            $this->fail('Expected QueryException');
+        } catch (\Sqlx\Exceptions\QueryException $e) {
+            // The first 1s delay already exceeds the budget
+            $this->assertSame(1, $e->getAttempt());
+        } finally {
+            $driver->close();
+        }
+    }
+
+    // =========================================================================
+    // Circuit Breaker Tests
+    // =========================================================================
+
+    public function testCircuitBreakerIgnoresQueryErrors(): void
+    {
+        $this->assertSame('closed', $this->driver->circuitState());
+
+        $driver = DriverFactory::make([
+            \Sqlx\DriverOptions::OPT_URL => $this->getConnectionUrl(),
+            \Sqlx\DriverOptions::OPT_CIRCUIT_BREAKER => ['failures' => 1, 'cooldown' => '60s'],
+        ]);
+
+        try {
+            try {
+                $driver->queryAll('SELECT * FROM nonexistent_table');
+                $this->fail('Expected QueryException');
+            } catch (\Sqlx\Exceptions\QueryException $e) {
+                // The database answered, so the circuit stays closed
+            }
+
+            $this->assertSame('closed', $driver->circuitState());
+            $this->assertEquals(1, $driver->queryValue('SELECT 1'));
+        } finally {
+            $driver->close();
+        }
+    }
+
+    public function testCircuitBreakerRejectsInvalidConfig(): void
+    {
+        $this->expectException(\Sqlx\Exceptions\ConfigurationException::class);
+
+        DriverFactory::make([
+            \Sqlx\DriverOptions::OPT_URL => $this->getConnectionUrl(),
+            \Sqlx\DriverOptions::OPT_CIRCUIT_BREAKER => ['failures' => 0],
+        ]);
+    }
+
+    // =========================================================================
+    // Deadline Tests
+    // =========================================================================
+
+    public function testDeadline(): void
+    {
+        $this->assertNull($this->driver->getDeadline());
+
+        $deadline = (int) (microtime(true) * 1000) + 60_000;
+        $this->driver->setDeadline($deadline);
+        try {
+            $this->assertSame($deadline, $this->driver->getDeadline());
+            $this->assertEquals(1, $this->driver->queryValue('SELECT 1'));
+
+            // Once the deadline has passed, calls fail without reaching the database
+            $this->driver->setDeadline((int) (microtime(true) * 1000) - 1);
+            foreach ([
+                fn () => $this->driver->queryValue('SELECT 1'),
+                fn () => $this->driver->begin(),
+            ] as $call) {
+                try {
+                    $call();
+                    $this->fail('Expected TimeoutException');
+                } catch (\Sqlx\Exceptions\TimeoutException $e) {
+                    $this->assertSame(SqlxException::TIMEOUT, $e->getCode());
+                    $this->assertStringContainsString('deadline', $e->getMessage());
+                }
+            }
+        } finally {
+            $this->driver->setDeadline(null);
+        }
+
+        $this->assertNull($this->driver->getDeadline());
+        $this->assertEquals(1, $this->driver->queryValue('SELECT 1'));
+    }
+
+    // =========================================================================
+    // Persistent Pool Registry Tests
+    // =========================================================================
+
+    public function testPersistentPoolRegistry(): void
+    {
+        $name = 'registry_test_' . bin2hex(random_bytes(4));
+        $options = [
+            \Sqlx\DriverOptions::OPT_URL => $this->getConnectionUrl(),
+            \Sqlx\DriverOptions::OPT_PERSISTENT_NAME => $name,
+            \Sqlx\DriverOptions::OPT_PERSISTENT_RECREATE => true,
+            \Sqlx\DriverOptions::OPT_MAX_CONNECTIONS => 2,
+        ];
+
+        try {
+            $first = DriverFactory::make($options);
+            $this->assertEquals(1, $first->queryValue('SELECT 1'));
+
+            $pools = array_values(array_filter(
+                DriverFactory::persistentPools(),
+                fn (array $pool) => $pool['name'] === $name,
+            ));
+            $this->assertCount(1, $pools);
+            $this->assertSame(2, $pools[0]['max_connections']);
+            $this->assertFalse($pools[0]['closed']);
+            $this->assertGreaterThanOrEqual(0.0, $pools[0]['age_seconds']);
+            $password = parse_url($this->getConnectionUrl(), PHP_URL_PASS);
+            if ($password) {
+                $this->assertStringNotContainsString($password, $pools[0]['url']);
+            }
+
+            // Same options: the pool is reused
+            DriverFactory::make($options);
+            $this->assertFalse($first->isClosed());
+
+            // Different options: the pool is replaced
+            $second = DriverFactory::make([\Sqlx\DriverOptions::OPT_MAX_CONNECTIONS => 3] + $options);
+            $pool = array_values(array_filter(
+                DriverFactory::persistentPools(),
+                fn (array $pool) => $pool['name'] === $name,
+            ))[0];
+            $this->assertSame(3, $pool['max_connections']);
+
+            $this->assertTrue(DriverFactory::closePersistent($name));
+            $this->assertTrue($second->isClosed());
+            $this->assertFalse(DriverFactory::closePersistent($name));
+        } finally {
+            DriverFactory::closePersistent($name);
+        }
+    }
+
+    // =========================================================================
+    // Connection Manager Tests
+    // =========================================================================
+
+    public function testConnectionManager(): void
+    {
+        $manager = new \Sqlx\ConnectionManager([
+            'main' => $this->getConnectionUrl(),
+            'capped' => [
+                \Sqlx\DriverOptions::OPT_URL => $this->getConnectionUrl(),
+                \Sqlx\DriverOptions::OPT_MAX_CONNECTIONS => 1,
+            ],
+        ], 'main');
+
+        $this->assertSame(['capped', 'main'], $manager->names());
+        $this->assertSame('main', $manager->getDefault());
+        $this->assertFalse($manager->isConnected('main'));
+
+        $driver = $manager->get();
+        $this->assertInstanceOf(\Sqlx\DriverInterface::class, $driver);
+        $this->assertEquals(1, $driver->queryValue('SELECT 1'));
+        $this->assertTrue($manager->isConnected('main'));
+        $this->assertFalse($manager->isConnected('capped'));
+
+        // Drivers of the same connection share their state
+        $deadline = (int) (microtime(true) * 1000) + 60_000;
+        $driver->setDeadline($deadline);
+        $this->assertSame($deadline, $manager->get('main')->getDeadline());
+        $driver->setDeadline(null);
+
+        $this->assertTrue($manager->disconnect('main'));
+        $this->assertFalse($manager->isConnected('main'));
+        $driver->close();
+
+        try {
+            $manager->get('missing');
+            $this->fail('Expected ConfigurationException');
+        } catch (\Sqlx\Exceptions\ConfigurationException $e) {
+            $this->assertStringContainsString('missing', $e->getMessage());
+        }
+
+        $this->expectException(\Sqlx\Exceptions\ConfigurationException::class);
+        $manager->register('broken', [\Sqlx\DriverOptions::OPT_MAX_CONNECTIONS => 'many']);
+    }
+
+    public function testConnectionManagerPersistent(): void
+    {
+        $name = 'manager_test_' . bin2hex(random_bytes(4));
+        $poolsNamed = fn () => array_values(array_filter(
+            DriverFactory::persistentPools(),
+            fn (array $pool) => str_starts_with($pool['name'], "$name-"),
+        ));
+        $manager = new \Sqlx\ConnectionManager();
+        $this->assertNull($manager->getDefault());
+
+        try {
+            $manager->register($name, $this->getConnectionUrl(), true);
+            $this->assertSame($name, $manager->getDefault());
+            $this->assertEquals(1, $manager->get()->queryValue('SELECT 1'));
+            $this->assertCount(1, $poolsNamed());
+
+            // Same options: the pool is shared
+            $same = new \Sqlx\ConnectionManager();
+            $same->register($name, $this->getConnectionUrl(), true);
+            $this->assertEquals(1, $same->get()->queryValue('SELECT 1'));
+            $this->assertCount(1, $poolsNamed());
+
+            // Same name, other options: a pool of its own
+            $other = new \Sqlx\ConnectionManager();
+            $other->register($name, [
+                \Sqlx\DriverOptions::OPT_URL => $this->getConnectionUrl(),
+                \Sqlx\DriverOptions::OPT_MAX_CONNECTIONS => 1,
+            ], true);
+            $this->assertEquals(1, $other->get()->queryValue('SELECT 1'));
+            $this->assertCount(2, $poolsNamed());
+        } finally {
+            foreach ($poolsNamed() as $pool) {
+                DriverFactory::closePersistent($pool['name']);
+            }
+        }
+    }
+
+    // =========================================================================
+    // Concurrent Query Tests
+    // =========================================================================
+
+    public function testParallel(): void
+    {
+        $this->createTestTable();
+
+        try {
+            $this->driver->execute("INSERT INTO test_users (name, email) VALUES ('Alice', 'alice@example.com')");
+            $this->driver->execute("INSERT INTO test_users (name, email) VALUES ('Bob', 'bob@example.com')");
+
+            $results = $this->driver->parallel([
+                'all' => 'SELECT name FROM test_users ORDER BY name',
+                'alice' => ['SELECT name FROM test_users WHERE name = ?', ['Alice']],
+                'builder' => $this->driver->readBuilder()
+                    ->select('name')
+                    ->from('test_users')
+                    ->where([['name', '=', 'Bob']]),
+                'broken' => 'SELECT missing_column FROM test_users',
+                'invalid' => 42,
+            ]);
+
+            $this->assertSame(['all', 'alice', 'builder', 'broken', 'invalid'], array_keys($results));
+            $this->assertSame(['Alice', 'Bob'], array_map(fn ($row) => $row->name, $results['all']));
+            $this->assertCount(1, $results['alice']);
+            $this->assertEquals('Alice', $results['alice'][0]->name);
+            $this->assertEquals('Bob', $results['builder'][0]->name);
+            $this->assertInstanceOf(\Sqlx\Exceptions\QueryException::class, $results['broken']);
+            $this->assertInstanceOf(\Sqlx\Exceptions\ParameterException::class, $results['invalid']);
+        } finally {
+            $this->dropTestTable();
+        }
+    }
+
+    public function testQueryAllAsync(): void
+    {
+        $pending = $this->driver->queryAllAsync('SELECT 1 AS n');
+        $this->assertInstanceOf(\Sqlx\Pending::class, $pending);
+
+        $rows = $pending->wait();
+        $this->assertTrue($pending->isReady());
+        $this->assertEquals(1, $rows[0]->n);
+        $this->assertSame($rows, $pending->wait());
+
+        $failing = $this->driver->queryAllAsync('SELECT missing_column FROM missing_table');
+        try {
+            $failing->wait();
+            $this->fail('Expected QueryException');
+        } catch (\Sqlx\Exceptions\QueryException $e) {
+            $this->assertStringContainsString('missing_table', $e->getMessage());
+        }
+
+        $this->expectException(SqlxException::class);
+        $failing->wait();
+    }
+
+    public function testQueryMaybeRowAndExecuteAsync(): void
+    {
+        $this->createTestTable();
+
+        try {
+            $inserted = $this->driver->executeAsync(
+                'INSERT INTO test_users (name, email) VALUES (?, ?)',
+                ['Alice', 'alice@example.com'],
+            );
+            $this->assertSame(1, $inserted->wait());
+            $this->driver->execute("INSERT INTO test_users (name, email) VALUES ('Bob', 'bob@example.com')");
+
+            $row = $this->driver->queryMaybeRowAsync('SELECT name FROM test_users ORDER BY name');
+            $this->assertEquals('Alice', $row->wait()->name);
+            $none = $this->driver->queryMaybeRowAsync('SELECT name FROM test_users WHERE name = ?', ['Carol']);
+            $this->assertNull($none->wait());
+
+            $updated = $this->driver->executeAsync("UPDATE test_users SET email = 'x@example.com'");
+            $this->assertSame(2, $updated->wait());
+
+            $failing = $this->driver->executeAsync('DELETE FROM missing_table');
+            $this->expectException(\Sqlx\Exceptions\QueryException::class);
+            $failing->wait();
+        } finally {
+            $this->dropTestTable();
+        }
+    }
+
+    public function testPendingCompletionFd(): void
+    {
+        $pending = $this->driver->queryAllAsync('SELECT 1 AS n');
+        $fd = $pending->completionFd();
+        $this->assertSame($fd, $pending->completionFd());
+
+        $stream = fopen("php://fd/$fd", 'r');
+        try {
+            $read = [$stream];
+            $write = $except = null;
+            $this->assertSame(1, stream_select($read, $write, $except, 5));
+            $this->assertTrue($pending->isReady());
+            $this->assertEquals(1, $pending->wait()[0]->n);
+        } finally {
+            fclose($stream);
+        }
+    }
+
+    public function testAsyncAdapter(): void
+    {
+        require_once __DIR__ . '/../../async.php';
+
+        $async = new \Sqlx\Async($this->driver);
+        $this->assertEquals(1, $async->queryMaybeRow('SELECT 1 AS n')->n);
+        $this->assertNull($async->queryMaybeRow('SELECT 1 AS n WHERE 1 = 0'));
+
+        $this->createTestTable();
+        try {
+            $this->assertSame(1, $async->execute(
+                'INSERT INTO test_users (name, email) VALUES (?, ?)',
+                ['Alice', 'alice@example.com'],
+            ));
+            $this->assertEquals('Alice', $async->queryMaybeRow('SELECT name FROM test_users')->name);
+        } finally {
+            $this->dropTestTable();
+        }
+
+        $results = $async->all(['one' => 'SELECT 1 AS n', 'two' => ['SELECT ? AS n', [2]]]);
+        $this->assertEquals(2, $results['two'][0]->n);
+
+        $this->expectException(\Sqlx\Exceptions\QueryException::class);
+        $async->all(['ok' => 'SELECT 1 AS n', 'broken' => 'SELECT missing_column FROM missing_table']);
+    }
+
+    // =========================================================================
+    // Batch Tests
+    // =========================================================================
+
+    public function testBatch(): void
+    {
+        $this->createTestTable();
+
+        try {
+            $results = $this->driver->batch(function (\Sqlx\Batch $b) {
+                $this->assertSame(0, $b->execute(
+                    'INSERT INTO test_users (name, email) VALUES (?, ?)',
+                    ["O'Brien", 'obrien@example.com'],
+                ));
+                $b->execute('INSERT INTO test_users (name, email) VALUES (?, ?)', ['Bob', 'bob@example.com']);
+                $this->assertSame(2, $b->queryAll('SELECT name FROM test_users ORDER BY name'));
+                $this->assertSame(3, $b->count());
+            });
+
+            $this->assertCount(3, $results);
+            $this->assertEquals(1, $results[0]);
+            $this->assertEquals(1, $results[1]);
+            $this->assertSame(['Bob', "O'Brien"], array_map(fn ($row) => $row->name, $results[2]));
+            $this->assertSame([], $this->driver->batch(function (\Sqlx\Batch $b) {}));
+        } finally {
+            $this->dropTestTable();
+        }
+    }
+
+    public function testBatchInTransaction(): void
+    {
+        $this->createTestTable();
+
+        try {
+            $this->driver->begin(function ($driver) {
+                $driver->batch(function (\Sqlx\Batch $b) {
+                    $b->execute("INSERT INTO test_users (name, email) VALUES ('Alice', 'alice@example.com')");
+                });
+                return false; // roll back
+            });
+            $this->assertEquals(0, $this->driver->queryValue('SELECT count(*) FROM test_users'));
+
+            // Outside a transaction, the statements before a failing one stay applied
+            try {
+                $this->driver->batch(function (\Sqlx\Batch $b) {
+                    $b->execute('INSERT INTO test_users (name, email) VALUES (?, ?)', ['Bob', 'bob@example.com']);
+                    $b->execute('SELECT missing_column FROM missing_table');
+                });
+                $this->fail('The batch should have failed');
+            } catch (\Sqlx\Exceptions\QueryException $e) {
+                $this->assertSame(1, $e->getIndex());
+            }
+            $this->assertEquals(1, $this->driver->queryValue('SELECT count(*) FROM test_users'));
+
+            $this->expectException(\Sqlx\Exceptions\QueryException::class);
+            $this->driver->batch(function (\Sqlx\Batch $b) {
+                $b->execute('SELECT missing_column FROM missing_table');
+            });
+        } finally {
+            $this->dropTestTable();
+        }
+    }
+
+    // =========================================================================
+    // Query Hook Tests
+    // =========================================================================
+
+    public function testQueryHook(): void
+    {
+        $this->createTestTable();
+
+        try {
+            $queries = [];
+            $this->driver->onQuery(function (string $sql, string $sqlInline, float $durationMs) use (&$queries) {
+                $queries[] = [
+                    'sql' => $sql,
+                    'sqlInline' => $sqlInline,
+                    'durationMs' => $durationMs,
+                ];
+            });
+
+            $this->driver->execute("INSERT INTO test_users (name, email) VALUES ('Alice', 'alice@example.com')");
+            $this->driver->queryAll('SELECT * FROM test_users');
+
+            $this->driver->onQuery(null); // Disable hook
+
+            $this->assertCount(2, $queries);
+            $this->assertGreaterThan(0, $queries[0]['durationMs']);
+            $this->assertGreaterThan(0, $queries[1]['durationMs']);
+        } finally {
+            $this->dropTestTable();
+        }
+    }
+
+    public function testQueryHookCoversPreparedQueriesBuildersAndTransactions(): void
+    {
+        $sqls = [];
+        $this->driver->onQuery(function (string $sql, string $sqlInline, float $durationMs, ?int $rows) use (&$sqls) {
+            $sqls[] = [$sql, $rows];
+        });
+
+        try {
+            $this->driver->begin();
+            $this->driver->prepare('SELECT 1')->queryValue();
+            $this->driver->savepoint('sp1');
+            $this->driver->releaseSavepoint('sp1');
+            $this->driver->commit();
+
+            $this->assertSame(
+                [['BEGIN', null], ['SELECT 1', 1], ['SAVEPOINT sp1', null], ['RELEASE SAVEPOINT sp1', null], ['COMMIT', null]],
+                $sqls
+            );
+        } finally {
+            $this->driver->onQuery(null);
+        }
+    }
+
+    public function testQueryHookReportsStreamWhenExhausted(): void
+    {
+        $this->createTestTable();
+
+        try {
+            $this->driver->execute("INSERT INTO test_users (name, email) VALUES ('Alice', 'a@example.com'), ('Bob', 'b@example.com')");
+
+            $reports = [];
+            $this->driver->onQuery(function (string $sql, string $sqlInline, float $durationMs, ?int $rows) use (&$reports) {
+                $reports[] = $rows;
+            });
+
+            $result = $this->driver->query('SELECT * FROM test_users');
+            $this->assertSame([], $reports, 'A stream reports only once it is finished');
+
+            foreach ($result as $row) {
+                // consume
+            }
+            $this->assertSame([2], $reports);
+
+            unset($result);
+            $this->assertSame([2], $reports, 'A stream reports only once');
+
+            // Dropping a stream early reports the rows streamed so far
+            $result = $this->driver->query('SELECT * FROM test_users', null, 1);
+            $result->rewind();
+            unset($result);
+            $this->assertSame([2, 1], $reports);
+        } finally {
+            $this->driver->onQuery(null);
+            $this->dropTestTable();
+        }
+    }
+
+    public function testBeforeQueryHookRewritesSqlAndParams(): void
+    {
+        $seen = [];
+        $this->driver->onBeforeQuery(function (string $sql, array $params, string $kind) use (&$seen) {
+            $seen[] = [$sql, $params, $kind];
+            if ($sql === 'SELECT 1') {
+                return 'SELECT 2';
+            }
+            if (str_contains($sql, ':value')) {
+                return ['params' => ['value' => 42]];
+            }
+            return null;
+        });
+
+        try {
+            $this->assertEquals(2, $this->driver->queryValue('SELECT 1'));
+            $this->assertEquals(42, $this->driver->queryValue('SELECT :value', ['value' => 1]));
+
+            $this->assertSame(['SELECT 1', [], 'query'], $seen[0]);
+            $this->assertSame(['SELECT :value', ['value' => 1], 'query'], $seen[1]);
+        } finally {
+            $this->driver->onBeforeQuery(null);
+        }
+    }
+
+    public function testBeforeQueryHookCanVeto(): void
+    {
+        $this->createTestTable();
+
+        try {
+            $this->driver->onBeforeQuery(function (string $sql, array $params, string $kind) {
+                if ($kind === 'execute') {
+                    throw new \RuntimeException('maintenance');
+                }
+                return null;
+            });
+
+            try {
+                $this->driver->execute("INSERT INTO test_users (name, email) VALUES ('Alice', 'alice@example.com')");
+                $this->fail('Expected the write to be vetoed');
+            } catch (\Sqlx\Exceptions\NotPermittedException $e) {
+                $this->assertStringContainsString('maintenance', $e->getMessage());
+                $this->assertInstanceOf(\RuntimeException::class, $e->getPrevious());
+                $this->assertSame('maintenance', $e->getPrevious()->getMessage());
+            }
+
+            $this->driver->onBeforeQuery(null);
+            $this->assertEquals(0, $this->driver->queryValue('SELECT COUNT(*) FROM test_users'));
+        } finally {
+            $this->driver->onBeforeQuery(null);
+            $this->dropTestTable();
+        }
+    }
+
+    public function testBeforeQueryHookSeesTransactionsAndCannotVetoRollback(): void
+    {
+        $kinds = [];
+        $this->driver->onBeforeQuery(function (string $sql, array $params, string $kind) use (&$kinds) {
+            $kinds[] = $kind;
+            if ($kind === 'rollback') {
+                throw new \RuntimeException('ignored');
+            }
+            return null;
+        });
+
+        try {
+            $this->driver->begin();
+            $this->driver->queryValue('SELECT 1');
+            $this->driver->rollback();
+
+            $this->assertSame(['begin', 'query', 'rollback'], $kinds);
+        } finally {
+            $this->driver->onBeforeQuery(null);
+        }
+    }
+
+    public function testBeforeQueryHookMetaReachesAfterHook(): void
+    {
+        $captured = 'unset';
+        $this->driver->onBeforeQuery(fn () => ['meta' => ['request' => 'abc']]);
+        $this->driver->onQuery(function ($sql, $sqlInline, $durationMs, $rows, $error, $meta) use (&$captured) {
+            $captured = $meta;
+        });
+
+        try {
+            $this->driver->queryValue('SELECT 1');
+            $this->assertSame(['request' => 'abc'], $captured);
+        } finally {
+            $this->driver->onBeforeQuery(null);
+            $this->driver->onQuery(null);
+        }
+    }
+
+    public function testBeforeQueryHookMayQueryAndReplaceItself(): void
+    {
+        $calls = 0;
+        $driver = $this->driver;
+        $this->driver->onBeforeQuery(function (string $sql) use (&$calls, $driver) {
+            $calls++;
+            // Runs without calling the hook again
+            $driver->queryValue('SELECT 2');
+            $driver->onBeforeQuery(null);
+            return null;
+        });
+
+        try {
+            $this->assertEquals(1, $this->driver->queryValue('SELECT 1'));
+            $this->assertSame(1, $calls);
+
+            $this->driver->queryValue('SELECT 1');
+            $this->assertSame(1, $calls);
+        } finally {
+            $this->driver->onBeforeQuery(null);
+        }
+    }
+
+    public function testSlowQueryLogWritesJsonLines(): void
+    {
+        $logFile = tempnam(sys_get_temp_dir(), 'sqlx-slow-');
+        $driver = DriverFactory::make([
+            \Sqlx\DriverOptions::OPT_URL => $this->getConnectionUrl(),
+            \Sqlx\DriverOptions::OPT_SLOW_QUERY_THRESHOLD => 0,
+            \Sqlx\DriverOptions::OPT_SLOW_QUERY_LOG => $logFile,
+            \Sqlx\DriverOptions::OPT_SLOW_QUERY_LOG_INLINE => true,
+        ]);
+
+        try {
+            $driver->queryValue("SELECT 'top-secret', 424242");
+
+            // Entries are written asynchronously
+            $contents = '';
+            for ($i = 0; $i < 50 && $contents === ''; $i++) {
+                usleep(20_000);
+                $contents = (string) file_get_contents($logFile);
+            }
+
+            $lines = array_values(array_filter(explode("\n", $contents)));
+            $this->assertNotEmpty($lines);
+            $entry = json_decode($lines[0], true, flags: JSON_THROW_ON_ERROR);
+            $this->assertArrayHasKey('timestamp', $entry);
+            $this->assertArrayHasKey('duration_ms', $entry);
+            $this->assertSame(1, $entry['rows']);
+            $this->assertNull($entry['error']);
+            $this->assertStringContainsString('top-secret', $entry['sql']);
+            $this->assertStringNotContainsString('top-secret', $entry['sql_inline']);
+            $this->assertStringNotContainsString('424242', $entry['sql_inline']);
+            $this->assertNotEmpty($entry['driver']);
+        } finally {
+            $driver->close();
+            @unlink($logFile);
+        }
+    }
+
+    public function testSlowQueryLogWarnsWhenFileCannotBeOpened(): void
+    {
+        $warnings = [];
+        set_error_handler(function (int $errno, string $message) use (&$warnings) {
+            $warnings[] = $message;
+            return true;
+        }, E_WARNING);
+        try {
+            $driver = DriverFactory::make([
+                \Sqlx\DriverOptions::OPT_URL => $this->getConnectionUrl(),
+                \Sqlx\DriverOptions::OPT_SLOW_QUERY_THRESHOLD => 0,
+                \Sqlx\DriverOptions::OPT_SLOW_QUERY_LOG => sys_get_temp_dir() . '/sqlx-missing-dir/slow.log',
+            ]);
+        } finally {
+            restore_error_handler();
+        }
+
+        try {
+            $this->assertCount(1, $warnings);
+            $this->assertStringContainsString('cannot open the slow query log', $warnings[0]);
+            // The driver works without the log
+            $this->assertEquals(1, $driver->queryValue('SELECT 1'));
+        } finally {
+            $driver->close();
+        }
+    }
+
+    // =========================================================================
+    // Connection Tagging Tests
+    // =========================================================================
+
+    public function testSetApplicationName(): void
+    {
+        // Just verify it doesn't throw
+        $this->driver->setApplicationName('test-application');
+        $this->assertTrue(true);
+    }
+
+    public function testSetClientInfo(): void
+    {
+        // Just verify it doesn't throw
+        $this->driver->setClientInfo('test-app', [
+            'request_id' => 'abc123',
+            'user_id' => 42,
+        ]);
+        $this->assertTrue(true);
+    }
+
+    public function testQueryTagsAreAppendedAsComment(): void
+    {
+        $this->driver->setQueryTags(['route' => '/users', 'app' => 'billing']);
+
+        [$sql] = $this->driver->dry('SELECT 1');
+        $this->assertStringEndsWith("/*app='billing',route='%2Fusers'*/", $sql);
+        $this->assertEquals(1, $this->driver->queryValue('SELECT 1'));
+
+        $this->driver->setQueryTags([]);
+        [$sql] = $this->driver->dry('SELECT 1');
+        $this->assertStringNotContainsString('/*', $sql);
+    }
+
+    public function testQueryTagsCannotBreakOutOfComment(): void
+    {
+        $this->driver->setQueryTags(['evil' => "*/ DROP TABLE test_users; --'"]);
+
+        [$sql] = $this->driver->dry('SELECT 1');
+        $this->assertSame(1, substr_count($sql, '*/'));
+        $this->assertStringNotContainsString('DROP TABLE', $sql);
+        $this->assertEquals(1, $this->driver->queryValue('SELECT 1'));
+    }
+
+    public function testWithQueryTagsIsScoped(): void
+    {
+        $this->driver->setQueryTags(['app' => 'billing']);
+
+        $sql = $this->driver->withQueryTags(['app' => 'reports', 'job' => 'nightly'], function ($driver) {
+            return $driver->dry('SELECT 1')[0];
+        });
+        $this->assertStringEndsWith("/*app='reports',job='nightly'*/", $sql);
+
+        [$sql] = $this->driver->dry('SELECT 1');
+        $this->assertStringEndsWith("/*app='billing'*/", $sql);
+    }
+
+    public function testQueryTagsOnPreparedQueryAndBuilder(): void
+    {
+        $sqls = [];
+        $this->driver->onQuery(function (string $sql) use (&$sqls) {
+            $sqls[] = $sql;
+        });
+
+        $prepared = $this->driver->prepare('SELECT 1');
+        $prepared->setQueryTags(['feature' => 'prepared']);
+        $prepared->queryAll();
+
+        $builder = $this->driver->builder()->setQueryTags(['feature' => 'builder']);
+        $builder->raw('SELECT 1');
+        $builder->queryAll();
+
+        $this->driver->onQuery(null);
+
+        $this->assertCount(2, $sqls);
+        $this->assertStringEndsWith("/*feature='prepared'*/", $sqls[0]);
+        $this->assertStringEndsWith("/*feature='builder'*/", $sqls[1]);
     }
 
     // =========================================================================
@@ -100,6 +100,103 @@ class MSSQLDriverTest extends AbstractDriverTest
         }
     }
 
+    public function testBinaryType(): void
+    {
+        $this->driver->execute("
+            IF OBJECT_ID('test_binary', 'U') IS NOT NULL DROP TABLE test_binary
+        ");
+        $this->driver->execute('
+            CREATE TABLE test_binary (
+                id INT IDENTITY(1,1) PRIMARY KEY,
+                content VARBINARY(MAX)
+            )
+        ');
+
+        try {
+            $bytes = "\x00\xff\xfe'\\";
+            $this->driver->execute('INSERT INTO test_binary (content) VALUES (?)', [new \Sqlx\Binary($bytes)]);
+            $this->driver->execute('INSERT INTO test_binary (content) VALUES (?x)', ['plain']);
+
+            $this->assertSame(
+                [$bytes, 'plain'],
+                $this->driver->queryColumn('SELECT content FROM test_binary ORDER BY id')
+            );
+            $this->assertSame('0x00fffe275c', $this->driver->quote(new \Sqlx\Binary($bytes)));
+        } finally {
+            $this->driver->execute("
+                IF OBJECT_ID('test_binary', 'U') IS NOT NULL DROP TABLE test_binary
+            ");
+        }
+    }
+
+    public function testOpenBlob(): void
+    {
+        $this->driver->execute("
+            IF OBJECT_ID('test_blob', 'U') IS NOT NULL DROP TABLE test_blob
+        ");
+        $this->driver->execute('
+            CREATE TABLE test_blob (
+                id INT PRIMARY KEY,
+                content VARBINARY(MAX)
+            )
+        ');
+
+        try {
+            $bytes = random_bytes(5000);
+            $this->driver->execute('INSERT INTO test_blob (id, content) VALUES (1, ?)', [new \Sqlx\Binary($bytes)]);
+
+            $blob = $this->driver->openBlob('test_blob', 'content', ['id' => 1]);
+            $this->assertSame(5000, $blob->size());
+            $blob->seek(4990);
+            $this->assertSame(substr($bytes, 4990), $blob->read());
+            $this->assertTrue($blob->eof());
+
+            $blob->seek(0);
+            $out = fopen('php://memory', 'w+b');
+            $this->assertSame(5000, $blob->copyTo($out));
+            rewind($out);
+            $this->assertSame($bytes, stream_get_contents($out));
+
+            $this->expectException(\Sqlx\Exceptions\SqlxException::class);
+            $this->driver->largeObject();
+        } finally {
+            $this->driver->execute("
+                IF OBJECT_ID('test_blob', 'U') IS NOT NULL DROP TABLE test_blob
+            ");
+        }
+    }
+
+    public function testDatetimeOffset(): void
+    {
+        $this->driver->execute("
+            IF OBJECT_ID('test_datetimeoffset', 'U') IS NOT NULL DROP TABLE test_datetimeoffset
+        ");
+        $this->driver->execute('
+            CREATE TABLE test_datetimeoffset (
+                id INT IDENTITY PRIMARY KEY,
+                at DATETIMEOFFSET NOT NULL
+            )
+        ');
+
+        try {
+            $at = new \DateTimeImmutable('2024-01-01 10:00:00+01:00');
+            $this->driver->execute('INSERT INTO test_datetimeoffset (at) VALUES (?)', [$at]);
+
+            $this->assertSame('2024-01-01 09:00:00 UTC', $this->driver->queryValue('SELECT at FROM test_datetimeoffset'));
+
+            $driver = \Sqlx\DriverFactory::make([
+                \Sqlx\DriverOptions::OPT_URL => $this->getConnectionUrl(),
+                \Sqlx\DriverOptions::OPT_TIMEZONE => '-05:00',
+            ]);
+            $this->assertSame('2024-01-01 04:00:00-05:00', $driver->queryValue('SELECT at FROM test_datetimeoffset'));
+            $driver->close();
+        } finally {
+            $this->driver->execute("
+                IF OBJECT_ID('test_datetimeoffset', 'U') IS NOT NULL DROP TABLE test_datetimeoffset
+            ");
+        }
+    }
+
     public function testMergeStatement(): void
     {
         $this->driver->execute("
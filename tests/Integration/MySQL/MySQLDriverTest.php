@@ -190,6 +190,92 @@ class MySQLDriverTest extends AbstractDriverTest
         }
     }
 
+    public function testBinaryType(): void
+    {
+        $this->driver->execute('DROP TABLE IF EXISTS test_binary');
+        $this->driver->execute('
+            CREATE TABLE test_binary (
+                id INT AUTO_INCREMENT PRIMARY KEY,
+                content BLOB
+            ) ENGINE=InnoDB
+        ');
+
+        try {
+            $bytes = "\x00\xff\xfe'\\";
+            $this->driver->execute('INSERT INTO test_binary (content) VALUES (?)', [new \Sqlx\Binary($bytes)]);
+            $this->driver->execute('INSERT INTO test_binary (content) VALUES (?x)', ['plain']);
+
+            $this->assertSame(
+                [$bytes, 'plain'],
+                $this->driver->queryColumn('SELECT content FROM test_binary ORDER BY id')
+            );
+            $this->assertSame("X'00fffe275c'", $this->driver->quote(new \Sqlx\Binary($bytes)));
+        } finally {
+            $this->driver->execute('DROP TABLE IF EXISTS test_binary');
+        }
+    }
+
+    public function testOpenBlob(): void
+    {
+        $this->driver->execute('DROP TABLE IF EXISTS test_blob');
+        $this->driver->execute('
+            CREATE TABLE test_blob (
+                id INT PRIMARY KEY,
+                content LONGBLOB
+            ) ENGINE=InnoDB
+        ');
+
+        try {
+            $bytes = random_bytes(5000);
+            $this->driver->execute('INSERT INTO test_blob (id, content) VALUES (1, ?)', [new \Sqlx\Binary($bytes)]);
+
+            $blob = $this->driver->openBlob('test_blob', 'content', ['id' => 1]);
+            $this->assertSame(5000, $blob->size());
+            $blob->seek(4990);
+            $this->assertSame(substr($bytes, 4990), $blob->read());
+            $this->assertTrue($blob->eof());
+
+            $blob->seek(0);
+            $out = fopen('php://memory', 'w+b');
+            $this->assertSame(5000, $blob->copyTo($out));
+            rewind($out);
+            $this->assertSame($bytes, stream_get_contents($out));
+
+            $this->expectException(\Sqlx\Exceptions\SqlxException::class);
+            $this->driver->largeObject();
+        } finally {
+            $this->driver->execute('DROP TABLE IF EXISTS test_blob');
+        }
+    }
+
+    public function testTimestampTimeZone(): void
+    {
+        $driver = \Sqlx\DriverFactory::make([
+            \Sqlx\DriverOptions::OPT_URL => $this->getConnectionUrl(),
+            \Sqlx\DriverOptions::OPT_TIMEZONE => '+00:00',
+        ]);
+        $driver->execute('DROP TABLE IF EXISTS test_timestamp');
+        $driver->execute('
+            CREATE TABLE test_timestamp (
+                id INT AUTO_INCREMENT PRIMARY KEY,
+                at TIMESTAMP NOT NULL
+            ) ENGINE=InnoDB
+        ');
+
+        try {
+            $this->assertSame('+00:00', $driver->queryValue('SELECT @@session.time_zone'));
+
+            // Converted to the wall-clock time of the session time zone
+            $at = new \DateTimeImmutable('2024-01-01 10:00:00+01:00');
+            $driver->execute('INSERT INTO test_timestamp (at) VALUES (?)', [$at]);
+
+            $this->assertSame('2024-01-01 09:00:00+00:00', $driver->queryValue('SELECT at FROM test_timestamp'));
+        } finally {
+            $driver->execute('DROP TABLE IF EXISTS test_timestamp');
+            $driver->close();
+        }
+    }
+
     public function testUpsert(): void
     {
         $this->driver->execute('DROP TABLE IF EXISTS test_upsert');
@@ -298,4 +384,23 @@ class MySQLDriverTest extends AbstractDriverTest
             $this->driver->execute('DROP TABLE IF EXISTS test_insert_many');
         }
     }
+
+    /**
+     * MySQL only supports EXPLAIN ANALYZE on multi-table DELETE statements.
+     */
+    public function testExplainAnalyzeRollsBackWrites(): void
+    {
+        $this->createTestTable();
+
+        try {
+            $this->driver->execute("INSERT INTO test_users (name, email) VALUES ('Alice', 'alice@example.com')");
+
+            $plan = $this->driver->explain('DELETE u FROM test_users u', null, ['analyze' => true]);
+
+            $this->assertIsString($plan['plan']);
+            $this->assertEquals(1, $this->driver->queryValue('SELECT COUNT(*) FROM test_users'));
+        } finally {
+            $this->dropTestTable();
+        }
+    }
 }
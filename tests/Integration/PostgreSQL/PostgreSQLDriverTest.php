@@ -329,6 +329,176 @@ class PostgreSQLDriverTest extends AbstractDriverTest
         }
     }
 
+    public function testBinaryType(): void
+    {
+        $this->driver->execute('DROP TABLE IF EXISTS test_binary');
+        $this->driver->execute('
+            CREATE TABLE test_binary (
+                id SERIAL PRIMARY KEY,
+                content BYTEA
+            )
+        ');
+
+        try {
+            $bytes = "\x00\xff\xfe'\\";
+            $this->driver->execute('INSERT INTO test_binary (content) VALUES (?)', [new \Sqlx\Binary($bytes)]);
+            $this->driver->execute('INSERT INTO test_binary (content) VALUES (?x)', ['plain']);
+            $this->driver->copyIn('test_binary', [['content' => new \Sqlx\Binary($bytes)]]);
+
+            $this->assertSame(
+                [$bytes, 'plain', $bytes],
+                $this->driver->queryColumn('SELECT content FROM test_binary ORDER BY id')
+            );
+            $this->assertSame(
+                "'\\x00fffe275c'::bytea",
+                $this->driver->quote(new \Sqlx\Binary($bytes))
+            );
+            $this->assertSame(
+                2,
+                $this->driver->queryValue(
+                    'SELECT count(*) FROM test_binary WHERE content = ' . $this->driver->quote(new \Sqlx\Binary($bytes))
+                )
+            );
+        } finally {
+            $this->driver->execute('DROP TABLE IF EXISTS test_binary');
+        }
+    }
+
+    public function testOpenBlob(): void
+    {
+        $this->driver->execute('DROP TABLE IF EXISTS test_blob');
+        $this->driver->execute('
+            CREATE TABLE test_blob (
+                id INT PRIMARY KEY,
+                content BYTEA
+            )
+        ');
+
+        try {
+            $bytes = random_bytes(3 * 1024 * 1024 + 17);
+            $this->driver->execute('INSERT INTO test_blob (id, content) VALUES (1, ?), (2, NULL)', [new \Sqlx\Binary($bytes)]);
+
+            $blob = $this->driver->openBlob('test_blob', 'content', ['id' => 1]);
+            $this->assertSame(strlen($bytes), $blob->size());
+            $this->assertNull($blob->oid());
+            $this->assertSame(substr($bytes, 0, 10), $blob->read(10));
+            $blob->seek(strlen($bytes) - 5);
+            $this->assertSame(substr($bytes, -5), $blob->read());
+            $this->assertTrue($blob->eof());
+            $this->assertSame('', $blob->read());
+
+            $blob->seek(0);
+            $out = fopen('php://memory', 'w+b');
+            $this->assertSame(strlen($bytes), $blob->copyTo($out));
+            rewind($out);
+            $this->assertSame($bytes, stream_get_contents($out));
+
+            $blob->seek(10);
+            $stream = $blob->toStream();
+            $this->assertIsResource($stream);
+            $this->assertSame(substr($bytes, 10, 5), fread($stream, 5));
+            $this->assertSame(substr($bytes, 15), stream_get_contents($stream));
+            $this->assertTrue($blob->eof());
+
+            $this->assertNull($this->driver->openBlob('test_blob', 'content', ['id' => 2]));
+
+            $this->expectException(\Sqlx\Exceptions\SqlxException::class);
+            $blob->write('read-only');
+        } finally {
+            $this->driver->execute('DROP TABLE IF EXISTS test_blob');
+        }
+    }
+
+    public function testOpenBlobReadsCompressedValueThroughLargeObject(): void
+    {
+        $this->driver->execute('DROP TABLE IF EXISTS test_blob');
+        $this->driver->execute('CREATE TABLE test_blob (id INT PRIMARY KEY, content BYTEA)');
+        $countObjects = fn () => $this->driver->queryValue('SELECT count(*) FROM pg_largeobject_metadata');
+
+        try {
+            $bytes = str_repeat('compressible ', 400_000);
+            $this->driver->execute('INSERT INTO test_blob (id, content) VALUES (1, ?)', [new \Sqlx\Binary($bytes)]);
+            $this->assertTrue($this->driver->queryValue(
+                'SELECT pg_column_size(content) < octet_length(content) FROM test_blob WHERE id = 1'
+            ));
+            $before = $countObjects();
+
+            $blob = $this->driver->openBlob('test_blob', 'content', ['id' => 1]);
+            $this->assertSame($before + 1, $countObjects());
+            $this->assertNull($blob->oid());
+            $this->assertSame(strlen($bytes), $blob->size());
+            $blob->seek(strlen($bytes) - 13);
+            $this->assertSame('compressible ', $blob->read());
+            $blob->seek(0);
+            $out = fopen('php://memory', 'w+b');
+            $this->assertSame(strlen($bytes), $blob->copyTo($out));
+            rewind($out);
+            $this->assertSame($bytes, stream_get_contents($out));
+
+            $blob->close();
+            $this->assertSame($before, $countObjects());
+        } finally {
+            $this->driver->execute('DROP TABLE IF EXISTS test_blob');
+        }
+    }
+
+    public function testLargeObject(): void
+    {
+        $blob = $this->driver->largeObject();
+        $oid = $blob->oid();
+
+        try {
+            $this->assertIsInt($oid);
+            $this->assertSame(0, $blob->size());
+
+            $bytes = random_bytes(2 * 1024 * 1024 + 3);
+            $stream = fopen('php://memory', 'w+b');
+            fwrite($stream, $bytes);
+            rewind($stream);
+            $this->assertSame(strlen($bytes), $blob->copyFrom($stream));
+            $this->assertSame(5, $blob->write('tail!'));
+            $blob->close();
+
+            $blob = $this->driver->largeObject($oid);
+            $this->assertSame(strlen($bytes) + 5, $blob->size());
+            $blob->seek(strlen($bytes));
+            $this->assertSame('tail!', $blob->read());
+            $blob->seek(0);
+            $this->assertSame(substr($bytes, 0, 100), $blob->read(100));
+        } finally {
+            $this->driver->execute('SELECT lo_unlink(CAST(? AS oid))', [$oid]);
+        }
+    }
+
+    public function testTimestamptzTimeZone(): void
+    {
+        $driver = \Sqlx\DriverFactory::make([
+            \Sqlx\DriverOptions::OPT_URL => $this->getConnectionUrl(),
+            \Sqlx\DriverOptions::OPT_TIMEZONE => 'Europe/Berlin',
+        ]);
+        $driver->execute('DROP TABLE IF EXISTS test_timestamptz');
+        $driver->execute('
+            CREATE TABLE test_timestamptz (
+                id SERIAL PRIMARY KEY,
+                at TIMESTAMPTZ NOT NULL
+            )
+        ');
+
+        try {
+            $this->assertSame('Europe/Berlin', $driver->queryValue('SHOW TIME ZONE'));
+
+            // The offset of the parameter is kept: 10:00 in New York is 15:00 UTC
+            $at = new \DateTimeImmutable('2024-01-01 10:00:00', new \DateTimeZone('America/New_York'));
+            $driver->execute('INSERT INTO test_timestamptz (at) VALUES (?)', [$at]);
+
+            $this->assertSame('2024-01-01 16:00:00+01:00', $driver->queryValue('SELECT at FROM test_timestamptz'));
+            $this->assertSame('2024-01-01 15:00:00 UTC', $this->driver->queryValue('SELECT at FROM test_timestamptz'));
+        } finally {
+            $driver->execute('DROP TABLE IF EXISTS test_timestamptz');
+            $driver->close();
+        }
+    }
+
     public function testReturningClause(): void
     {
         $this->createTestTable();
@@ -476,6 +646,30 @@ class PostgreSQLDriverTest extends AbstractDriverTest
         }
     }
 
+    public function testExecuteManyCommitFailure(): void
+    {
+        $this->driver->execute('DROP TABLE IF EXISTS test_deferred');
+        $this->driver->execute('
+            CREATE TABLE test_deferred (
+                code VARCHAR(16) UNIQUE DEFERRABLE INITIALLY DEFERRED
+            )
+        ');
+
+        try {
+            $insert = $this->driver->prepare('INSERT INTO test_deferred (code) VALUES (?)');
+            try {
+                // Both sets run; the deferred constraint fails the commit
+                $insert->executeMany([['a'], ['a']]);
+                $this->fail('Expected a TransactionException');
+            } catch (\Sqlx\Exceptions\TransactionException $e) {
+                $this->assertStringContainsString('Failed to commit transaction', $e->getMessage());
+            }
+            $this->assertEquals(0, $this->driver->queryValue('SELECT count(*) FROM test_deferred'));
+        } finally {
+            $this->driver->execute('DROP TABLE IF EXISTS test_deferred');
+        }
+    }
+
     public function testCopyIn(): void
     {
         $this->driver->execute('DROP TABLE IF EXISTS test_copy_in');